@@ -0,0 +1,54 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use jsonrpc_base::{helpers, Request};
+use serde_json::{Map, Value};
+use std::hint::black_box;
+
+fn big_value() -> Value {
+    let fields: Map<String, Value> = (0..100)
+        .map(|i| (format!("field{}", i), Value::from(format!("value {}", i))))
+        .collect();
+    Value::Object(fields)
+}
+
+fn request(c: &mut Criterion) {
+    let request = Request::new("method").with_params_value(big_value());
+    let json = serde_json::to_string(&request).unwrap();
+    let framed = request.to_string();
+    let buffer = framed.repeat(16);
+
+    c.bench_function("request to_string", |b| {
+        b.iter(|| {
+            Request::new("method")
+                .with_params_value(black_box(big_value()))
+                .to_string()
+        })
+    });
+
+    c.bench_function("request parse_json", |b| {
+        b.iter(|| Request::parse_json(black_box(&json)).unwrap())
+    });
+
+    c.bench_function("request parse", |b| {
+        b.iter(|| Request::parse(black_box(&framed)).unwrap())
+    });
+
+    c.bench_function("get_content_length", |b| {
+        b.iter(|| {
+            let mut s = black_box(buffer.as_str());
+            while !s.is_empty() {
+                s = helpers::get_content_length(s).unwrap().1;
+            }
+        })
+    });
+
+    c.bench_function("request round trip", |b| {
+        b.iter(|| {
+            let framed = black_box(&request).to_string();
+            let (body, _) = helpers::get_content_length(&framed).unwrap();
+            Request::parse_json(body).unwrap()
+        })
+    });
+}
+
+criterion_group!(benches, request);
+criterion_main!(benches);
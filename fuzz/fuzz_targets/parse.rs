@@ -0,0 +1,13 @@
+#![no_main]
+
+use jsonrpc_base::{helpers, Message};
+use libfuzzer_sys::fuzz_target;
+
+// Untrusted input must be rejected with an error, never a panic
+fuzz_target!(|data: &[u8]| {
+    let _ = helpers::get_content_length_bytes(data);
+    let _ = Message::try_from_reader(data);
+    if let Ok(s) = core::str::from_utf8(data) {
+        let _ = Message::parse(s);
+    }
+});
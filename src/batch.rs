@@ -0,0 +1,501 @@
+use super::{helpers, Error, IdSpace, JsonRpcVersion, Message, Notification, Request, Response};
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::{self, Vec},
+};
+use core::{fmt, ops::Deref, slice};
+use serde::{ser::SerializeSeq, Serialize, Serializer};
+use serde_json::Value;
+
+/// JSON-RPC batch of messages.
+///
+/// Serializes as a JSON array of the messages, framed in a single `Content-Length` block by
+/// `Display`. The specification forbids sending an empty batch.
+#[derive(Debug, Clone, Default)]
+pub struct Batch {
+    messages: Vec<Message>,
+}
+
+impl Batch {
+    /// Create an empty batch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a message to the batch.
+    pub fn push<M>(&mut self, message: M)
+    where
+        M: Into<Message>,
+    {
+        self.messages.push(message.into());
+    }
+
+    /// Messages of the batch
+    pub fn as_slice(&self) -> &[Message] {
+        &self.messages
+    }
+
+    /// Return the messages of the batch.
+    pub fn into_inner(self) -> Vec<Message> {
+        self.messages
+    }
+
+    /// Align the responses to a batch with its requests, as the server may answer in any order.
+    ///
+    /// Returns a slot per request, in the order of the batch, with `None` for the requests the
+    /// server never answered; notifications have no slot. IDs are matched as in
+    /// [`BatchMap::resolve`]. Fails with an [`Error::INTERNAL_ERROR`] whose data is the array
+    /// of the leftover responses, such as those with an unknown ID or answering an already
+    /// answered request.
+    pub fn reorder_responses(
+        &self,
+        responses: Vec<Response>,
+    ) -> Result<Vec<Option<Response>>, Error> {
+        let mut index: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        let mut slots = Vec::new();
+        for request in self.messages.iter().filter_map(Message::as_request) {
+            index
+                .entry(request.id.to_string())
+                .or_default()
+                .insert(0, slots.len());
+            slots.push(None);
+        }
+
+        let mut leftover = Vec::new();
+        for response in responses {
+            match index.get_mut(&response.id.to_string()).and_then(Vec::pop) {
+                Some(i) => slots[i] = Some(response),
+                None => leftover.push(response),
+            }
+        }
+
+        if !leftover.is_empty() {
+            return Err(Error {
+                code: Error::INTERNAL_ERROR,
+                message: alloc::format!(
+                    "{} responses match no request of the batch",
+                    leftover.len()
+                ),
+                data: serde_json::to_value(leftover).ok(),
+            });
+        }
+        Ok(slots)
+    }
+}
+
+impl Deref for Batch {
+    type Target = [Message];
+
+    fn deref(&self) -> &Self::Target {
+        &self.messages
+    }
+}
+
+impl From<Vec<Message>> for Batch {
+    fn from(messages: Vec<Message>) -> Self {
+        Self { messages }
+    }
+}
+
+impl FromIterator<Message> for Batch {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = Message>,
+    {
+        Self {
+            messages: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<Message> for Batch {
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = Message>,
+    {
+        self.messages.extend(iter)
+    }
+}
+
+impl IntoIterator for Batch {
+    type Item = Message;
+    type IntoIter = vec::IntoIter<Message>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.messages.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Batch {
+    type Item = &'a Message;
+    type IntoIter = slice::Iter<'a, Message>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.messages.iter()
+    }
+}
+
+impl Serialize for Batch {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.messages.len()))?;
+        for message in &self.messages {
+            match message {
+                Message::Request(r) => seq.serialize_element(r)?,
+                Message::Notification(n) => seq.serialize_element(n)?,
+                Message::Response(r) => seq.serialize_element(r)?,
+            }
+        }
+        seq.end()
+    }
+}
+
+impl fmt::Display for Batch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        helpers::write_framed(f, self)
+    }
+}
+
+/// Builder of a client-side batch, assigning the IDs of its requests.
+///
+/// Each call returns a [`BatchHandle`]; once the responses arrive, the [`BatchMap`] produced by
+/// [`BatchBuilder::build`] matches them back to their handles.
+#[derive(Debug)]
+pub struct BatchBuilder<'a> {
+    batch: Batch,
+    ids: BatchIds<'a>,
+    expected: Vec<Value>,
+}
+
+#[derive(Debug)]
+enum BatchIds<'a> {
+    Sequential(u64),
+    Space(&'a mut IdSpace),
+}
+
+impl BatchBuilder<'static> {
+    /// Create a builder assigning sequential numeric IDs, starting at `0`.
+    pub fn new() -> Self {
+        Self {
+            batch: Batch::new(),
+            ids: BatchIds::Sequential(0),
+            expected: Vec::new(),
+        }
+    }
+}
+
+impl Default for BatchBuilder<'static> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> BatchBuilder<'a> {
+    /// Create a builder assigning the IDs generated by the namespace.
+    pub fn in_space(space: &'a mut IdSpace) -> Self {
+        Self {
+            batch: Batch::new(),
+            ids: BatchIds::Space(space),
+            expected: Vec::new(),
+        }
+    }
+
+    /// Append a request to the batch, returning the handle of its response.
+    pub fn call<M, P>(&mut self, method: M, params: P) -> Result<BatchHandle, Error>
+    where
+        M: ToString,
+        P: Serialize,
+    {
+        let request = Request {
+            id: Value::Null,
+            jsonrpc: JsonRpcVersion::default(),
+            method: method.to_string(),
+            params: None,
+            #[cfg(feature = "meta")]
+            meta: None,
+        }
+        .with_params(params)?;
+        let id = match &mut self.ids {
+            BatchIds::Sequential(next) => {
+                *next += 1;
+                Value::from(*next - 1)
+            }
+            BatchIds::Space(space) => space.next_id(),
+        };
+        self.batch.push(Request {
+            id: id.clone(),
+            ..request
+        });
+        self.expected.push(id);
+        Ok(BatchHandle(self.expected.len() - 1))
+    }
+
+    /// Append a notification to the batch.
+    pub fn notify<M, P>(&mut self, method: M, params: P) -> Result<(), Error>
+    where
+        M: ToString,
+        P: Serialize,
+    {
+        let notification = Notification::new(method).with_params(params)?;
+        self.batch.push(notification);
+        Ok(())
+    }
+
+    /// Frame the batch, returning it with the map of its expected responses.
+    ///
+    /// Fails with an [`Error::INVALID_REQUEST`] if nothing was appended, as the specification
+    /// forbids sending an empty batch.
+    pub fn build(self) -> Result<(String, BatchMap), Error> {
+        if self.batch.is_empty() {
+            return Err(Error {
+                code: Error::INVALID_REQUEST,
+                message: "the batch is empty".to_string(),
+                data: None,
+            });
+        }
+        let map = BatchMap {
+            expected: self.expected,
+        };
+        Ok((self.batch.to_string(), map))
+    }
+}
+
+/// Handle of a request appended to a [`BatchBuilder`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BatchHandle(usize);
+
+/// IDs of the requests of a batch, by handle.
+#[derive(Debug, Clone)]
+pub struct BatchMap {
+    expected: Vec<Value>,
+}
+
+impl BatchMap {
+    /// Number of responses expected for the batch
+    pub fn len(&self) -> usize {
+        self.expected.len()
+    }
+
+    /// Return `true` if the batch expects no response, as when it has only notifications.
+    pub fn is_empty(&self) -> bool {
+        self.expected.is_empty()
+    }
+
+    /// ID assigned to the request of the handle
+    pub fn id(&self, handle: BatchHandle) -> Option<&Value> {
+        self.expected.get(handle.0)
+    }
+
+    /// Match the responses to the handles of the batch, in any order.
+    ///
+    /// Responses with an unknown ID, or with the ID of an already matched response, are ignored.
+    pub fn resolve<I>(&self, responses: I) -> BatchResults
+    where
+        I: IntoIterator<Item = Response>,
+    {
+        let index: BTreeMap<String, usize> = self
+            .expected
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (id.to_string(), i))
+            .collect();
+
+        let mut results: Vec<Option<Result<Value, Error>>> =
+            self.expected.iter().map(|_| None).collect();
+        for response in responses {
+            if let Some(&i) = index.get(&response.id.to_string()) {
+                if results[i].is_none() {
+                    results[i] = Some(response.into_result());
+                }
+            }
+        }
+        BatchResults { results }
+    }
+}
+
+/// Results of the requests of a batch, by handle.
+#[derive(Debug, Clone)]
+pub struct BatchResults {
+    results: Vec<Option<Result<Value, Error>>>,
+}
+
+impl BatchResults {
+    /// Result of the request of the handle, or `None` if the server never answered it
+    pub fn get(&self, handle: BatchHandle) -> Option<&Result<Value, Error>> {
+        self.results.get(handle.0).and_then(Option::as_ref)
+    }
+
+    /// Take the result of the request of the handle, or `None` if the server never answered it
+    pub fn take(&mut self, handle: BatchHandle) -> Option<Result<Value, Error>> {
+        self.results.get_mut(handle.0).and_then(Option::take)
+    }
+
+    /// Handles of the requests the server never answered
+    pub fn missing(&self) -> Vec<BatchHandle> {
+        self.results
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| r.is_none())
+            .map(|(i, _)| BatchHandle(i))
+            .collect()
+    }
+}
+
+#[test]
+fn test_batch_extend_and_iterate() {
+    use super::{Notification, Request, Response};
+    use serde_json::Value;
+
+    let mut batch = Batch::new();
+    batch.extend([
+        Request::new("foo").with_id(1).into(),
+        Notification::new("bar").into(),
+    ]);
+    batch.push(Response::ok(2, true));
+    assert_eq!(batch.len(), 3);
+
+    let mut methods = Vec::new();
+    for message in &batch {
+        if let Message::Request(r) = message {
+            methods.push(r.method.as_str());
+        }
+    }
+    assert_eq!(methods, ["foo"]);
+    assert!(matches!(batch[1], Message::Notification(_)));
+
+    let frame = alloc::string::ToString::to_string(&batch);
+    let (body, _) = helpers::get_content_length(&frame).unwrap();
+    let json: Value = serde_json::from_str(body).unwrap();
+    assert_eq!(json[0]["method"], "foo");
+    assert_eq!(json[1]["method"], "bar");
+    assert_eq!(json[2]["result"], true);
+
+    let batch: Batch = batch
+        .into_iter()
+        .filter(|m| !matches!(m, Message::Response(_)))
+        .collect();
+    assert_eq!(batch.as_slice().len(), 2);
+}
+
+#[test]
+fn test_batch_builder_out_of_order() {
+    let mut builder = BatchBuilder::new();
+    let sum = builder.call("sum", [1, 2]).unwrap();
+    builder.notify("log", ["summing"]).unwrap();
+    let missing = builder.call("missing", ()).unwrap();
+    let (frame, map) = builder.build().unwrap();
+    assert_eq!(map.len(), 2);
+
+    let (body, _) = helpers::get_content_length(&frame).unwrap();
+    let json: Value = serde_json::from_str(body).unwrap();
+    assert_eq!(json[0]["id"], 0);
+    assert_eq!(json[1]["method"], "log");
+    assert_eq!(json[2]["id"], 1);
+
+    let responses = [
+        Response::err(
+            1,
+            Error {
+                code: Error::METHOD_NOT_FOUND,
+                message: "method not found".to_string(),
+                data: None,
+            },
+        ),
+        Response::ok(7, "unknown"),
+        Response::ok(0, 3),
+    ];
+    let mut results = map.resolve(responses);
+    assert!(results.missing().is_empty());
+    assert_eq!(results.take(sum).unwrap().unwrap(), 3);
+    assert_eq!(
+        results.get(missing).unwrap().as_ref().unwrap_err().code,
+        Error::METHOD_NOT_FOUND
+    );
+    assert!(results.take(sum).is_none());
+}
+
+#[test]
+fn test_batch_builder_missing_response() {
+    let mut space = IdSpace::new("c");
+    let mut builder = BatchBuilder::in_space(&mut space);
+    let first = builder.call("first", ()).unwrap();
+    let second = builder.call("second", ()).unwrap();
+    let (_, map) = builder.build().unwrap();
+    assert_eq!(map.id(second), Some(&Value::from("c-1")));
+
+    let results = map.resolve([Response::ok("c-1", true)]);
+    assert_eq!(results.missing(), [first]);
+    assert!(results.get(first).is_none());
+    assert_eq!(results.get(second).unwrap().as_ref().unwrap(), true);
+    assert_eq!(space.next_id(), "c-2");
+}
+
+#[test]
+fn test_batch_builder_notifications_only() {
+    let mut builder = BatchBuilder::new();
+    builder.notify("exit", ()).unwrap();
+    builder.notify("bye", ["now"]).unwrap();
+    let (frame, map) = builder.build().unwrap();
+    assert!(map.is_empty());
+    assert!(map.resolve([]).missing().is_empty());
+
+    let (body, _) = helpers::get_content_length(&frame).unwrap();
+    let json: Value = serde_json::from_str(body).unwrap();
+    assert_eq!(json.as_array().unwrap().len(), 2);
+}
+
+#[test]
+fn test_batch_builder_empty() {
+    let err = BatchBuilder::new().build().unwrap_err();
+    assert_eq!(err.code, Error::INVALID_REQUEST);
+}
+
+#[test]
+fn test_batch_reorder_responses() {
+    let batch: Batch = [
+        Message::from(Request::new("a").with_id(1)),
+        Message::from(Notification::new("log")),
+        Message::from(Request::new("b").with_id_string("1")),
+        Message::from(Request::new("c").with_id(2)),
+        Message::from(Notification::new("log")),
+    ]
+    .into_iter()
+    .collect();
+
+    let responses = alloc::vec![
+        Response::ok(2, "c"),
+        Response::ok(1, "a"),
+        Response::ok("1", "b"),
+    ];
+    let slots = batch.reorder_responses(responses).unwrap();
+    let results: Vec<_> = slots.iter().map(|r| r.as_ref().unwrap().result()).collect();
+    assert_eq!(
+        results,
+        [
+            Some(&Value::from("a")),
+            Some(&Value::from("b")),
+            Some(&Value::from("c"))
+        ]
+    );
+
+    let slots = batch
+        .reorder_responses(alloc::vec![Response::ok(2, "c")])
+        .unwrap();
+    assert_eq!(slots.len(), 3);
+    assert!(slots[0].is_none() && slots[1].is_none());
+    assert_eq!(slots[2].as_ref().unwrap().id, Value::from(2));
+
+    let responses = alloc::vec![
+        Response::ok(1, "a"),
+        Response::ok(1, "again"),
+        Response::ok(3, "unknown"),
+    ];
+    let err = batch.reorder_responses(responses).unwrap_err();
+    assert_eq!(err.code, Error::INTERNAL_ERROR);
+    let leftover = err.data.unwrap();
+    assert_eq!(leftover[0]["result"], "again");
+    assert_eq!(leftover[1]["id"], 3);
+}
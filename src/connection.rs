@@ -0,0 +1,848 @@
+use super::{
+    helpers, middleware,
+    reader::{self, Rewind},
+    telemetry, Error, Message, MessageEnvelope, Middleware, Notification, ReadOptions, Request,
+    Response, Service,
+};
+use alloc::{
+    collections::VecDeque,
+    string::{String, ToString},
+};
+use serde_json::Value;
+use std::{
+    fmt, io,
+    io::prelude::*,
+    net::{TcpStream, ToSocketAddrs},
+    time::{Duration, Instant},
+};
+
+/// Number of requests that timed out whose late response is remembered, to be discarded
+const MAX_TIMED_OUT: usize = 256;
+
+/// Options applied to the socket of [`Connection::connect_tcp_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TcpOptions {
+    /// Set `TCP_NODELAY` on the socket
+    pub nodelay: bool,
+    /// Read timeout of the socket
+    pub read_timeout: Option<Duration>,
+    /// Write timeout of the socket
+    pub write_timeout: Option<Duration>,
+}
+
+/// Transport with a configurable read timeout, used by
+/// [`Connection::send_request_with_timeout`] to bound every read.
+pub trait SetReadTimeout {
+    /// Current read timeout of the transport
+    fn read_timeout(&self) -> io::Result<Option<Duration>>;
+
+    /// Replace the read timeout of the transport; `None` blocks indefinitely.
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()>;
+}
+
+impl SetReadTimeout for TcpStream {
+    fn read_timeout(&self) -> io::Result<Option<Duration>> {
+        TcpStream::read_timeout(self)
+    }
+
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        TcpStream::set_read_timeout(self, timeout)
+    }
+}
+
+#[cfg(unix)]
+impl SetReadTimeout for std::os::unix::net::UnixStream {
+    fn read_timeout(&self) -> io::Result<Option<Duration>> {
+        std::os::unix::net::UnixStream::read_timeout(self)
+    }
+
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        std::os::unix::net::UnixStream::set_read_timeout(self, timeout)
+    }
+}
+
+impl Default for TcpOptions {
+    fn default() -> Self {
+        Self {
+            nodelay: true,
+            read_timeout: None,
+            write_timeout: None,
+        }
+    }
+}
+
+/// Blocking JSON-RPC connection over a reader and writer pair.
+///
+/// While waiting for the response of [`Connection::send_request`], every other frame read from
+/// the peer - requests, notifications, and responses with an unknown ID - is queued in arrival
+/// order, and surfaced by [`Connection::next_incoming`].
+///
+/// Every message written passes through the outgoing hooks of its [`Middleware`], and every
+/// message read through the incoming hooks, as it is received.
+///
+/// A response that arrives after its request timed out is discarded, for the last 256 requests
+/// that timed out; an older one is queued as incoming.
+pub struct Connection<R, W> {
+    reader: Rewind<R>,
+    writer: W,
+    incoming: VecDeque<Message>,
+    middleware: middleware::Stack,
+    default_timeout: Option<Duration>,
+    timed_out: VecDeque<String>,
+    read_options: ReadOptions,
+}
+
+impl<R, W> fmt::Debug for Connection<R, W>
+where
+    R: fmt::Debug,
+    W: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Connection")
+            .field("reader", self.reader.get_ref())
+            .field("writer", &self.writer)
+            .field("incoming", &self.incoming)
+            .field("middleware", &self.middleware.len())
+            .field("default_timeout", &self.default_timeout)
+            .field("timed_out", &self.timed_out)
+            .field("read_options", &self.read_options)
+            .finish()
+    }
+}
+
+impl<R, W> Connection<R, W>
+where
+    R: Read,
+    W: Write,
+{
+    /// Create a new connection from the provided reader and writer.
+    pub fn new(reader: R, writer: W) -> Self {
+        Self {
+            reader: Rewind::new(reader).with_recording(),
+            writer,
+            incoming: VecDeque::new(),
+            middleware: middleware::Stack::new(),
+            default_timeout: None,
+            timed_out: VecDeque::new(),
+            read_options: ReadOptions::default(),
+        }
+    }
+
+    /// Replace the options of the reads of the connection.
+    ///
+    /// With [`ReadOptions::tolerate_length_mismatch`], the bodies are read a byte at a time while
+    /// the end of their value is scanned, so wrap an unbuffered reader in a
+    /// [`BufReader`](std::io::BufReader).
+    pub fn with_read_options(mut self, options: ReadOptions) -> Self {
+        self.read_options = options;
+        self
+    }
+
+    /// Push a middleware on top of the stack of the connection.
+    ///
+    /// Incoming messages run through the stack in the order the middleware was pushed, and
+    /// outgoing messages in the reverse order.
+    pub fn with_middleware<M>(mut self, middleware: M) -> Self
+    where
+        M: Middleware + Send + 'static,
+    {
+        self.middleware.push(Box::new(middleware));
+        self
+    }
+
+    /// Replace the timeout of [`Connection::send_request`]; `None` waits indefinitely.
+    ///
+    /// The timeout is best-effort: it is checked before and after each read call, but a read that
+    /// blocks is not interrupted. Bound the reads of the transport too, as with
+    /// [`TcpOptions::read_timeout`], or use [`Connection::send_request_with_timeout`]. A read
+    /// timeout of the transport before the deadline is waited on, keeping the frame read so far.
+    pub fn set_default_timeout(&mut self, timeout: Option<Duration>) {
+        self.default_timeout = timeout;
+    }
+
+    /// Timeout of [`Connection::send_request`]
+    pub fn default_timeout(&self) -> Option<Duration> {
+        self.default_timeout
+    }
+
+    /// Send a request and block until its response is received, or the default timeout expires.
+    ///
+    /// Returns the result of the response, or its error. On timeout, returns an
+    /// [`Error::TIMEOUT`] with the ID of the request as data.
+    pub fn send_request(&mut self, request: Request) -> Result<Value, Error> {
+        let deadline = self.default_timeout.map(|t| Instant::now() + t);
+        let id = request.id.clone();
+        self.write_message(request.into())?;
+        self.wait_response(id, deadline, |_, _| Ok(()))
+    }
+
+    /// Send a notification, returning the number of bytes written.
+    pub fn send_notification(&mut self, notification: Notification) -> Result<usize, Error> {
+        self.write_message(notification.into())
+    }
+
+    /// Send a response to a request of the peer, returning the number of bytes written.
+    pub fn reply(&mut self, response: Response) -> Result<usize, Error> {
+        self.write_message(response.into())
+    }
+
+    /// Fetch the next message sent by the peer.
+    ///
+    /// Queued messages are returned first. Returns `None` if the peer closed the connection.
+    pub fn next_incoming(&mut self) -> Result<Option<Message>, Error> {
+        match self.incoming.pop_front() {
+            Some(message) => Ok(Some(message)),
+            None => self.read_message(),
+        }
+    }
+
+    /// Dispatch the incoming messages to the service until the peer closes the connection.
+    ///
+    /// Requests are answered with the response of the service; responses are discarded.
+    pub fn serve<S>(&mut self, service: &S) -> Result<(), Error>
+    where
+        S: Service + ?Sized,
+    {
+        self.serve_with(service, |_| ())
+    }
+
+    /// Dispatch the incoming messages to the service, passing each one through the middleware
+    /// first.
+    ///
+    /// The middleware receives every message with empty [`Extensions`](crate::Extensions), and
+    /// the service receives the extensions it attached. It runs after the incoming hooks of the
+    /// [`Middleware`] stack; a message short-circuited by the stack never reaches it.
+    pub fn serve_with<S, M>(&mut self, service: &S, mut middleware: M) -> Result<(), Error>
+    where
+        S: Service + ?Sized,
+        M: FnMut(&mut MessageEnvelope),
+    {
+        while let Some(message) = self.next_incoming()? {
+            let mut envelope = MessageEnvelope::from(message);
+            middleware(&mut envelope);
+            let MessageEnvelope {
+                message,
+                extensions,
+            } = envelope;
+
+            match message {
+                Message::Request(request) => {
+                    self.reply(service.call_with(request, &extensions))?;
+                }
+                Message::Notification(notification) => {
+                    service.notify_with(notification, &extensions)
+                }
+                Message::Response(_) => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// Split the connection into its reader and writer.
+    ///
+    /// Queued incoming messages, and the bytes read past the last frame, are dropped.
+    pub fn into_parts(self) -> (R, W) {
+        (self.reader.into_inner(), self.writer)
+    }
+
+    /// Read messages until the response to the ID, queueing the others.
+    ///
+    /// `before_read` is called with the time left before each read if there is a deadline.
+    fn wait_response<F>(
+        &mut self,
+        id: Value,
+        deadline: Option<Instant>,
+        mut before_read: F,
+    ) -> Result<Value, Error>
+    where
+        F: FnMut(&mut R, Duration) -> io::Result<()>,
+    {
+        let expired = |now: Instant| deadline.is_some_and(|d| now >= d);
+        loop {
+            if let Some(deadline) = deadline {
+                match deadline.checked_duration_since(Instant::now()) {
+                    Some(left) if !left.is_zero() => {
+                        before_read(self.reader.get_mut(), left).map_err(helpers::io_error)?
+                    }
+                    _ => return Err(self.time_out(id)),
+                }
+            }
+
+            match self.read_message() {
+                Ok(Some(Message::Response(response))) if response.id == id => {
+                    return response.into()
+                }
+                Ok(Some(message)) => self.incoming.push_back(message),
+                Ok(None) => {
+                    return Err(Error {
+                        code: Error::INVALID_REQUEST,
+                        message: "the connection was closed before the response was received"
+                            .to_string(),
+                        data: Some(id),
+                    })
+                }
+                // The frame cut by a read timeout was rewound: wait on until the deadline
+                Err(_) if self.reader.read_timed_out() && deadline.is_some() => (),
+                Err(_) if self.reader.read_timed_out() => return Err(self.time_out(id)),
+                Err(e) => return Err(e),
+            }
+
+            if expired(Instant::now()) {
+                return Err(self.time_out(id));
+            }
+        }
+    }
+
+    /// Abandon the request with the ID, so its late response is discarded.
+    fn time_out(&mut self, id: Value) -> Error {
+        if self.timed_out.len() == MAX_TIMED_OUT {
+            self.timed_out.pop_front();
+        }
+        self.timed_out.push_back(id.to_string());
+        Error {
+            code: Error::TIMEOUT,
+            message: "the response was not received in time".to_string(),
+            data: Some(id),
+        }
+    }
+
+    /// Read the next message through the incoming hooks, skipping the responses to requests that
+    /// timed out and the messages short-circuited by the hooks.
+    fn read_message(&mut self) -> Result<Option<Message>, Error> {
+        loop {
+            let message = match self.read_frame()? {
+                Some(Message::Response(r)) if self.forget_timed_out(&r.id) => continue,
+                Some(message) => message,
+                None => return Ok(None),
+            };
+            let is_request = matches!(message, Message::Request(_));
+            match middleware::incoming(&mut self.middleware, message) {
+                Ok(message) => return Ok(Some(message)),
+                Err(response) if is_request => {
+                    self.reply(*response)?;
+                }
+                Err(_) => (),
+            }
+        }
+    }
+
+    /// Forget the request with the ID if it timed out, returning whether it did.
+    fn forget_timed_out(&mut self, id: &Value) -> bool {
+        let id = id.to_string();
+        match self.timed_out.iter().position(|timed_out| *timed_out == id) {
+            Some(i) => self.timed_out.remove(i).is_some(),
+            None => false,
+        }
+    }
+
+    /// Read the next frame; a read that times out keeps its bytes, to resume the frame.
+    fn read_frame(&mut self) -> Result<Option<Message>, Error> {
+        self.reader.start_frame();
+        reader::read_message(&mut self.reader, &self.read_options)
+            .map(|read| read.map(|(message, _)| message))
+            .inspect_err(|_| {
+                if self.reader.read_timed_out() {
+                    self.reader.rewind_frame();
+                }
+            })
+    }
+
+    fn write_message(&mut self, message: Message) -> Result<usize, Error> {
+        let message = middleware::outgoing(&mut self.middleware, message);
+        let span = telemetry::IoSpan::write();
+        let bytes = message.to_string();
+        self.writer
+            .write_all(bytes.as_bytes())
+            .and_then(|_| self.writer.flush())
+            .map_err(|e| Error {
+                code: Error::PARSE_ERROR,
+                message: e.to_string(),
+                data: serde_json::to_value(&message).ok(),
+            })?;
+        span.finish(&message, bytes.len());
+        Ok(bytes.len())
+    }
+}
+
+impl<R, W> Connection<R, W>
+where
+    R: Read + SetReadTimeout,
+    W: Write,
+{
+    /// Send a request and block until its response is received, or the timeout expires.
+    ///
+    /// The read timeout of the reader is set to the time left before every read, and restored
+    /// once the call returns. On timeout, returns an [`Error::TIMEOUT`] with the ID of the
+    /// request as data; the connection stays usable, and the late response is discarded. A
+    /// timeout within a frame keeps its bytes read so far, and the next read resumes it.
+    pub fn send_request_with_timeout(
+        &mut self,
+        request: Request,
+        timeout: Duration,
+    ) -> Result<Value, Error> {
+        let deadline = Instant::now() + timeout;
+        let previous = self
+            .reader
+            .get_ref()
+            .read_timeout()
+            .map_err(helpers::io_error)?;
+        let id = request.id.clone();
+        let result = self.write_message(request.into()).and_then(|_| {
+            self.wait_response(id, Some(deadline), |reader, left| {
+                reader.set_read_timeout(Some(left))
+            })
+        });
+        self.reader
+            .get_mut()
+            .set_read_timeout(previous)
+            .map_err(helpers::io_error)?;
+        result
+    }
+}
+
+impl Connection<TcpStream, TcpStream> {
+    /// Connect to the provided address with the default [`TcpOptions`].
+    pub fn connect_tcp<A>(addr: A) -> Result<Self, Error>
+    where
+        A: ToSocketAddrs,
+    {
+        Self::connect_tcp_with(addr, TcpOptions::default())
+    }
+
+    /// Connect to the provided address, using a clone of the stream as reader.
+    pub fn connect_tcp_with<A>(addr: A, options: TcpOptions) -> Result<Self, Error>
+    where
+        A: ToSocketAddrs,
+    {
+        let writer = TcpStream::connect(addr).map_err(helpers::io_error)?;
+        writer
+            .set_nodelay(options.nodelay)
+            .map_err(helpers::io_error)?;
+        writer
+            .set_read_timeout(options.read_timeout)
+            .map_err(helpers::io_error)?;
+        writer
+            .set_write_timeout(options.write_timeout)
+            .map_err(helpers::io_error)?;
+        let reader = writer.try_clone().map_err(helpers::io_error)?;
+
+        Ok(Self::new(reader, writer))
+    }
+}
+
+#[test]
+fn test_connection_interleaved_notification() {
+    use super::test_helpers::pipe;
+    use std::thread;
+
+    let (client, server) = pipe();
+    let request = Request::new("work").with_id(7);
+    let expected = request.to_string();
+    let peer = thread::spawn(move || {
+        let (_, message) = Message::try_from_reader(&server).unwrap();
+        assert_eq!(message.to_string(), expected);
+        Message::from(Notification::new("progress"))
+            .try_to_writer(&server)
+            .unwrap();
+        Message::from(Response::ok(7, "done"))
+            .try_to_writer(&server)
+            .unwrap();
+    });
+
+    let mut connection = Connection::new(&client, &client);
+    let result = connection.send_request(request).unwrap();
+    assert_eq!(result, Value::from("done"));
+    peer.join().unwrap();
+
+    match connection.next_incoming().unwrap() {
+        Some(Message::Notification(n)) => assert_eq!(n.method, "progress"),
+        m => panic!("unexpected message {:?}", m),
+    }
+    assert!(connection.next_incoming().unwrap().is_none());
+}
+
+#[test]
+fn test_connection_unknown_response_is_incoming() {
+    use super::test_helpers::pipe;
+    use std::thread;
+
+    let (client, server) = pipe();
+    let peer = thread::spawn(move || {
+        Message::try_from_reader(&server).unwrap();
+        Message::from(Response::ok(3, false))
+            .try_to_writer(&server)
+            .unwrap();
+        Message::from(Response::ok(1, true))
+            .try_to_writer(&server)
+            .unwrap();
+        // The second request is never answered
+        Message::try_from_reader(&server).unwrap();
+    });
+
+    let mut connection = Connection::new(&client, &client);
+    let result = connection.send_request(Request::new("a").with_id(1));
+    assert_eq!(result.unwrap(), Value::Bool(true));
+
+    match connection.next_incoming().unwrap() {
+        Some(Message::Response(r)) => assert_eq!(r.id, Value::from(3)),
+        m => panic!("unexpected message {:?}", m),
+    }
+
+    let err = connection
+        .send_request(Request::new("b").with_id(2))
+        .unwrap_err();
+    assert_eq!(err.data, Some(Value::from(2)));
+    peer.join().unwrap();
+}
+
+#[test]
+fn test_connection_read_options() {
+    use super::test_helpers::pipe;
+    use std::thread;
+
+    let body = Response::ok(1, "é").to_compact_json().unwrap();
+    // A peer counting UTF-16 code units
+    let frame = alloc::format!("Content-Length: {}\r\n\r\n{}", body.len() - 1, body);
+
+    for tolerate_length_mismatch in [false, true] {
+        let (client, server) = pipe();
+        let frame = frame.clone();
+        let peer = thread::spawn(move || {
+            Message::try_from_reader(&server).unwrap();
+            (&server).write_all(frame.as_bytes()).unwrap();
+        });
+
+        let mut connection = Connection::new(&client, &client).with_read_options(ReadOptions {
+            tolerate_length_mismatch,
+        });
+        let result = connection.send_request(Request::new("a").with_id(1));
+        peer.join().unwrap();
+        match tolerate_length_mismatch {
+            true => {
+                assert_eq!(result.unwrap(), Value::from("é"));
+                assert!(connection.next_incoming().unwrap().is_none());
+            }
+            false => assert_eq!(result.unwrap_err().code, Error::PARSE_ERROR),
+        }
+    }
+}
+
+#[test]
+fn test_connection_send_request() {
+    use super::test_helpers::{pipe, MockServer};
+
+    let (client, server) = pipe();
+    let server = MockServer::new()
+        .expect_notification("initialized")
+        .expect_request("echo")
+        .respond_ok("echo")
+        .spawn(server);
+
+    let mut connection = Connection::new(&client, &client);
+    connection
+        .send_notification(Notification::new("initialized"))
+        .unwrap();
+    let result = connection.send_request(Request::new("echo")).unwrap();
+    assert_eq!(result, Value::from("echo"));
+    server.finish();
+}
+
+#[test]
+fn test_connection_tcp_round_trip() {
+    use std::{net::TcpListener, thread};
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let (_, message) = Message::try_from_reader(&stream).unwrap();
+        let request = Request::try_from(message).unwrap();
+        Message::from(Response::ok(request.id, request.method))
+            .try_to_writer(&stream)
+            .unwrap();
+    });
+
+    let mut connection = Connection::connect_tcp(addr).unwrap();
+    let result = connection.send_request(Request::new("echo")).unwrap();
+    assert_eq!(result, Value::from("echo"));
+    server.join().unwrap();
+}
+
+#[test]
+fn test_connection_tcp_refused() {
+    use std::net::TcpListener;
+
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let err = Connection::connect_tcp(addr).unwrap_err();
+    assert_eq!(err.code, Error::INTERNAL_ERROR);
+    assert_eq!(err.data, Some(Value::from("ConnectionRefused")));
+}
+
+#[test]
+fn test_connection_serve() {
+    use super::test_helpers::{pipe, MockClient};
+    use std::thread;
+
+    let (client, server) = pipe();
+    let served = thread::spawn(move || {
+        Connection::new(&server, &server)
+            .serve(&|request: Request| Response::ok(request.id, request.method))
+    });
+
+    let mut client = MockClient::new(client);
+    let first = client.call("echo", ());
+    client.notify("ignored", ());
+    let second = client.call("again", ());
+    assert_eq!(first.result(), Some(&Value::from("echo")));
+    assert_eq!(second.id, Value::from(2));
+    client.close();
+    served.join().unwrap().unwrap();
+}
+
+#[test]
+fn test_connection_serve_with_extensions() {
+    use super::{
+        test_helpers::{pipe, MockClient},
+        Extensions,
+    };
+    use std::{thread, time::Instant};
+
+    struct Elapsed;
+
+    impl Service for Elapsed {
+        fn call(&self, request: Request) -> Response {
+            Response::ok(request.id, false)
+        }
+
+        fn call_with(&self, request: Request, extensions: &Extensions) -> Response {
+            let received = extensions.get::<Instant>().copied();
+            Response::ok(request.id, received.is_some())
+        }
+    }
+
+    let (client, server) = pipe();
+    let expected = Message::from(Request::new("elapsed").with_id(1)).to_string();
+    let served = thread::spawn(move || {
+        Connection::new(&server, &server).serve_with(&Elapsed, |envelope| {
+            envelope.extensions.insert(Instant::now());
+            assert_eq!(envelope.to_string(), expected);
+            assert_eq!(
+                serde_json::to_value(&*envelope).unwrap(),
+                serde_json::to_value(&envelope.message).unwrap()
+            );
+        })
+    });
+
+    let mut client = MockClient::new(client);
+    let response = client.call("elapsed", ());
+    assert_eq!(response.result(), Some(&Value::Bool(true)));
+    client.close();
+    served.join().unwrap().unwrap();
+}
+
+#[test]
+fn test_connection_timed_out_cap() {
+    let mut connection = Connection::new(&b""[..], Vec::new());
+    for id in 0..=MAX_TIMED_OUT {
+        connection.time_out(Value::from(id));
+    }
+    assert_eq!(connection.timed_out.len(), MAX_TIMED_OUT);
+    assert!(!connection.forget_timed_out(&Value::from(0)));
+    assert!(connection.forget_timed_out(&Value::from(MAX_TIMED_OUT)));
+    assert_eq!(connection.timed_out.len(), MAX_TIMED_OUT - 1);
+}
+
+#[test]
+fn test_connection_timeout() {
+    use std::{collections::VecDeque, thread};
+
+    enum Step {
+        Send(String),
+        Stall(Duration),
+    }
+
+    // Transport replaying the peer, honoring the read timeout while stalled
+    struct Scripted {
+        steps: VecDeque<Step>,
+        timeout: Option<Duration>,
+    }
+
+    impl Read for Scripted {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            loop {
+                match self.steps.front_mut() {
+                    None => return Ok(0),
+                    Some(Step::Send(bytes)) => {
+                        let n = bytes.len().min(buf.len());
+                        buf[..n].copy_from_slice(&bytes.as_bytes()[..n]);
+                        bytes.drain(..n);
+                        if bytes.is_empty() {
+                            self.steps.pop_front();
+                        }
+                        return Ok(n);
+                    }
+                    Some(Step::Stall(stall)) => match self.timeout {
+                        Some(timeout) if timeout < *stall => {
+                            thread::sleep(timeout);
+                            *stall -= timeout;
+                            return Err(io::ErrorKind::WouldBlock.into());
+                        }
+                        _ => {
+                            thread::sleep(*stall);
+                            self.steps.pop_front();
+                        }
+                    },
+                }
+            }
+        }
+    }
+
+    impl SetReadTimeout for Scripted {
+        fn read_timeout(&self) -> io::Result<Option<Duration>> {
+            Ok(self.timeout)
+        }
+
+        fn set_read_timeout(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+            self.timeout = timeout;
+            Ok(())
+        }
+    }
+
+    let ms = Duration::from_millis;
+
+    // The peer never answers
+    let peer = Scripted {
+        steps: VecDeque::from([Step::Stall(Duration::from_secs(10))]),
+        timeout: Some(Duration::from_secs(30)),
+    };
+    let mut connection = Connection::new(peer, Vec::new());
+    let started = Instant::now();
+    let err = connection
+        .send_request_with_timeout(Request::new("a").with_id(1), ms(50))
+        .unwrap_err();
+    assert_eq!(err.code, Error::TIMEOUT);
+    assert_eq!(err.data, Some(Value::from(1)));
+    assert!(started.elapsed() < Duration::from_secs(5));
+    let (peer, _) = connection.into_parts();
+    assert_eq!(peer.timeout, Some(Duration::from_secs(30)));
+
+    // The late response is discarded, not delivered to the next call
+    let peer = Scripted {
+        steps: VecDeque::from([
+            Step::Stall(ms(200)),
+            Step::Send(Response::ok(1, "late").to_string()),
+            Step::Send(Notification::new("progress").to_string()),
+            Step::Send(Response::ok(2, "on time").to_string()),
+        ]),
+        timeout: None,
+    };
+    let mut connection = Connection::new(peer, Vec::new());
+    let err = connection
+        .send_request_with_timeout(Request::new("a").with_id(1), ms(50))
+        .unwrap_err();
+    assert_eq!(err.code, Error::TIMEOUT);
+    let result = connection
+        .send_request_with_timeout(Request::new("b").with_id(2), Duration::from_secs(5))
+        .unwrap();
+    assert_eq!(result, Value::from("on time"));
+    match connection.next_incoming().unwrap() {
+        Some(Message::Notification(n)) => assert_eq!(n.method, "progress"),
+        m => panic!("unexpected message {:?}", m),
+    }
+    assert!(connection.next_incoming().unwrap().is_none());
+
+    // A timeout within a frame keeps its bytes for the next read
+    let late = Response::ok(1, "late").to_string();
+    let (head, tail) = late.split_at(late.len() / 2);
+    let peer = Scripted {
+        steps: VecDeque::from([
+            Step::Send(head.to_string()),
+            Step::Stall(ms(200)),
+            Step::Send(tail.to_string()),
+            Step::Send(Response::ok(2, "on time").to_string()),
+        ]),
+        timeout: None,
+    };
+    let mut connection = Connection::new(peer, Vec::new());
+    let err = connection
+        .send_request_with_timeout(Request::new("a").with_id(1), ms(50))
+        .unwrap_err();
+    assert_eq!(err.code, Error::TIMEOUT);
+    let result = connection
+        .send_request_with_timeout(Request::new("b").with_id(2), Duration::from_secs(5))
+        .unwrap();
+    assert_eq!(result, Value::from("on time"));
+    assert!(connection.next_incoming().unwrap().is_none());
+
+    // An error other than a timeout is not one once the deadline passed
+    let peer = Scripted {
+        steps: VecDeque::from([
+            Step::Stall(ms(80)),
+            Step::Send("Content-Length: 3\r\n\r\n{x}".to_string()),
+        ]),
+        timeout: None,
+    };
+    let mut connection = Connection::new(peer, Vec::new());
+    connection.set_default_timeout(Some(ms(50)));
+    let err = connection
+        .send_request(Request::new("a").with_id(1))
+        .unwrap_err();
+    assert_eq!(err.code, Error::PARSE_ERROR);
+
+    // A read timeout shorter than the default timeout waits on, and the late response is
+    // discarded once the deadline passes
+    let peer = Scripted {
+        steps: VecDeque::from([
+            Step::Stall(ms(60)),
+            Step::Send(Response::ok(1, "slow").to_string()),
+            Step::Stall(ms(150)),
+            Step::Send(Response::ok(2, "late").to_string()),
+            Step::Send(Notification::new("progress").to_string()),
+            Step::Send(Response::ok(3, "on time").to_string()),
+        ]),
+        timeout: Some(ms(10)),
+    };
+    let mut connection = Connection::new(peer, Vec::new());
+    connection.set_default_timeout(Some(Duration::from_secs(1)));
+    let result = connection.send_request(Request::new("a").with_id(1));
+    assert_eq!(result.unwrap(), Value::from("slow"));
+    connection.set_default_timeout(Some(ms(100)));
+    let err = connection
+        .send_request(Request::new("b").with_id(2))
+        .unwrap_err();
+    assert_eq!(err.code, Error::TIMEOUT);
+    connection.set_default_timeout(Some(Duration::from_secs(5)));
+    let result = connection.send_request(Request::new("c").with_id(3));
+    assert_eq!(result.unwrap(), Value::from("on time"));
+    match connection.next_incoming().unwrap() {
+        Some(Message::Notification(n)) => assert_eq!(n.method, "progress"),
+        m => panic!("unexpected message {:?}", m),
+    }
+
+    // The default timeout is checked between the reads of any transport
+    let mut steps = VecDeque::new();
+    for _ in 0..10 {
+        steps.push_back(Step::Stall(ms(30)));
+        steps.push_back(Step::Send(Notification::new("tick").to_string()));
+    }
+    let mut connection = Connection::new(
+        Scripted {
+            steps,
+            timeout: None,
+        },
+        Vec::new(),
+    );
+    connection.set_default_timeout(Some(ms(50)));
+    assert_eq!(connection.default_timeout(), Some(ms(50)));
+    let err = connection
+        .send_request(Request::new("c").with_id(3))
+        .unwrap_err();
+    assert_eq!(err.code, Error::TIMEOUT);
+    assert!(matches!(
+        connection.next_incoming().unwrap(),
+        Some(Message::Notification(_))
+    ));
+}
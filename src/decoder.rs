@@ -0,0 +1,519 @@
+use super::{helpers, telemetry, Error, Message};
+use alloc::{string::ToString, vec::Vec};
+
+/// Options of the parse and read paths of `Content-Length` frames.
+///
+/// The default is strict.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReadOptions {
+    /// Recover the frames whose body is not valid JSON with the declared length, such as those of
+    /// peers counting UTF-16 code units, by scanning for the end of the JSON value of the body
+    pub tolerate_length_mismatch: bool,
+}
+
+/// Longest body scanned for the end of its value, for a declared length.
+///
+/// UTF-8 takes at most three bytes per UTF-16 code unit; the slack covers the peers that miscount
+/// by a few bytes.
+pub(crate) fn scan_limit(declared: usize) -> usize {
+    const SLACK: usize = 16;
+    declared.saturating_mul(3).saturating_add(SLACK)
+}
+
+/// Declared `Content-Length` of a recovered frame, and the length of its body
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LengthMismatch {
+    /// Length declared by the header
+    pub declared: usize,
+    /// Length of the JSON value of the body
+    pub actual: usize,
+}
+
+/// Parse the body of a frame with the actual length, instead of its declared one.
+///
+/// Returns `None` if the lengths match, or the body is not valid JSON either.
+pub(crate) fn recover_frame(
+    b: &[u8],
+    header_len: usize,
+    declared: usize,
+    actual: usize,
+) -> Option<(Message, LengthMismatch)> {
+    if actual == declared {
+        return None;
+    }
+    let body = b.get(header_len..header_len + actual)?;
+    let message = helpers::parse_body(body, header_len, Message::parse_json).ok()?;
+    telemetry::length_mismatch(declared, actual);
+    Some((message, LengthMismatch { declared, actual }))
+}
+
+/// Incremental decoder of `Content-Length` frames, for non-blocking transports.
+///
+/// Bytes are [pushed](ContentLengthDecoder::push) as they are received, and messages
+/// [polled](ContentLengthDecoder::poll) once their frame is complete. Once the header of the
+/// current frame is read, [`ContentLengthDecoder::bytes_needed`] tells the size of the read that
+/// completes it.
+///
+/// A frame with an invalid body is consumed. An invalid header is not, as its end is unknown, nor
+/// one declaring a frame longer than the [maximum](ContentLengthDecoder::with_max_frame_len): the
+/// decoder keeps failing until it is [cleared](ContentLengthDecoder::clear).
+#[derive(Debug, Clone)]
+pub struct ContentLengthDecoder {
+    buf: Vec<u8>,
+    bounds: Option<(usize, usize)>,
+    options: ReadOptions,
+    mismatch: Option<LengthMismatch>,
+    max_frame_len: usize,
+    /// Strict error of the current frame, while the end of its value is scanned
+    failed: Option<Error>,
+    boundary: JsonBoundary,
+}
+
+impl Default for ContentLengthDecoder {
+    fn default() -> Self {
+        Self {
+            buf: Vec::new(),
+            bounds: None,
+            options: ReadOptions::default(),
+            mismatch: None,
+            max_frame_len: helpers::MAX_FRAME_LEN,
+            failed: None,
+            boundary: JsonBoundary::default(),
+        }
+    }
+}
+
+impl ContentLengthDecoder {
+    /// Create a decoder with an empty buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cap the length of a frame, header included, to `max_frame_len` bytes.
+    ///
+    /// The default is [`helpers::MAX_FRAME_LEN`].
+    pub fn with_max_frame_len(mut self, max_frame_len: usize) -> Self {
+        self.max_frame_len = max_frame_len;
+        self
+    }
+
+    /// Create a decoder with an empty buffer and the provided options.
+    ///
+    /// With [`ReadOptions::tolerate_length_mismatch`], a body cut short by its header is polled
+    /// once the bytes completing its value are received. A body whose value does not end within
+    /// three times its declared length fails as in strict mode, consuming its declared length.
+    pub fn with_options(options: ReadOptions) -> Self {
+        Self {
+            options,
+            ..Self::default()
+        }
+    }
+
+    /// Take the length mismatch of the last polled message, if its frame was recovered
+    pub fn take_mismatch(&mut self) -> Option<LengthMismatch> {
+        self.mismatch.take()
+    }
+
+    /// Append received bytes to the buffer.
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+        if self.bounds.is_none() {
+            self.bounds = self.frame_bounds().ok().flatten();
+        }
+    }
+
+    /// Lengths of the header and of the body of the current frame, if it is within the maximum
+    fn frame_bounds(&self) -> Result<Option<(usize, usize)>, Error> {
+        let Some((header_len, length)) = helpers::frame_bounds(&self.buf)? else {
+            return Ok(None);
+        };
+        helpers::checked_frame_len(header_len, length, self.max_frame_len)?;
+        Ok(Some((header_len, length)))
+    }
+
+    /// Number of bytes missing to complete the current frame.
+    ///
+    /// Returns `None` while the header is incomplete, and `Some(0)` if the frame is complete.
+    pub fn bytes_needed(&self) -> Option<usize> {
+        self.bounds
+            .map(|(header_len, length)| (header_len + length).saturating_sub(self.buf.len()))
+    }
+
+    /// Parse the next complete message, consuming its frame.
+    ///
+    /// Returns `None` if the frame is not complete.
+    pub fn poll(&mut self) -> Result<Option<Message>, Error> {
+        let (header_len, length) = match self.bounds {
+            Some(bounds) => bounds,
+            None => match self.frame_bounds()? {
+                Some(bounds) => bounds,
+                None => return Ok(None),
+            },
+        };
+        self.mismatch = None;
+        let frame_len = header_len + length;
+        if self.buf.len() < frame_len {
+            return Ok(None);
+        }
+
+        let failed = match self.failed.take() {
+            Some(failed) => failed,
+            None => {
+                let body = &self.buf[header_len..frame_len];
+                match helpers::parse_body(body, header_len, Message::parse_json) {
+                    Err(e) if self.options.tolerate_length_mismatch => e,
+                    message => {
+                        self.consume(frame_len);
+                        return message.map(Some);
+                    }
+                }
+            }
+        };
+
+        // The scan resumes where the previous poll stopped, up to the limit of the body
+        let limit = header_len.saturating_add(scan_limit(length));
+        let scanned = &self.buf[header_len..self.buf.len().min(limit)];
+        let recovered = match self.boundary.scan(scanned) {
+            Ok(Some(actual)) => recover_frame(&self.buf, header_len, length, actual),
+            Ok(None) if self.buf.len() < limit => {
+                self.failed = Some(failed);
+                return Ok(None);
+            }
+            _ => None,
+        };
+        match recovered {
+            Some((message, mismatch)) => {
+                self.consume(header_len + mismatch.actual);
+                self.mismatch = Some(mismatch);
+                Ok(Some(message))
+            }
+            None => {
+                self.consume(frame_len);
+                Err(failed)
+            }
+        }
+    }
+
+    /// Drain the frame of the provided length, moving to the next one.
+    fn consume(&mut self, frame_len: usize) {
+        self.buf.drain(..frame_len);
+        self.bounds = self.frame_bounds().ok().flatten();
+        self.failed = None;
+        self.boundary = JsonBoundary::default();
+    }
+
+    /// Number of buffered bytes, including those of complete frames not yet polled
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Discard the buffered bytes.
+    pub fn clear(&mut self) {
+        self.buf.clear();
+        self.bounds = None;
+        self.failed = None;
+        self.boundary = JsonBoundary::default();
+    }
+}
+
+/// Incremental decoder of header-less streams of concatenated JSON messages.
+///
+/// The end of each message is found by tracking the nesting of its braces and brackets, skipping
+/// strings. Bytes are [pushed](ConcatenatedDecoder::push) as they are received, and messages
+/// [polled](ConcatenatedDecoder::poll) once their value is complete.
+///
+/// A top-level scalar is not a message, and the decoder keeps failing until it is
+/// [cleared](ConcatenatedDecoder::clear).
+#[derive(Debug, Clone, Default)]
+pub struct ConcatenatedDecoder {
+    buf: Vec<u8>,
+    boundary: JsonBoundary,
+}
+
+impl ConcatenatedDecoder {
+    /// Create a decoder with an empty buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append received bytes to the buffer.
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    /// Parse the next complete message, consuming its value.
+    ///
+    /// Returns `None` if the value is not complete.
+    pub fn poll(&mut self) -> Result<Option<Message>, Error> {
+        let end = match self.boundary.scan(&self.buf)? {
+            Some(end) => end,
+            None => return Ok(None),
+        };
+        let message = helpers::parse_body(&self.buf[..end], 0, Message::parse_json);
+        self.buf.drain(..end);
+        message.map(Some)
+    }
+
+    /// Number of buffered bytes, including those of complete values not yet polled
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Discard the buffered bytes.
+    pub fn clear(&mut self) {
+        self.buf.clear();
+        self.boundary = JsonBoundary::default();
+    }
+}
+
+/// Scanner of the end of the first JSON object or array of a stream, resumable as bytes arrive.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct JsonBoundary {
+    pos: usize,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl JsonBoundary {
+    /// Scan the bytes from where the previous call stopped, returning the end of the first value.
+    ///
+    /// The bytes must start with those of the previous call. The scanner is reset once a value is
+    /// found, for the bytes that follow it.
+    pub(crate) fn scan(&mut self, bytes: &[u8]) -> Result<Option<usize>, Error> {
+        while let Some(&b) = bytes.get(self.pos) {
+            self.pos += 1;
+            if self.depth == 0 {
+                match b {
+                    b' ' | b'\t' | b'\n' | b'\r' => continue,
+                    b'{' | b'[' => {
+                        self.depth = 1;
+                        continue;
+                    }
+                    _ => {
+                        self.pos -= 1;
+                        return Err(Error {
+                            code: Error::INVALID_REQUEST,
+                            message: "the provided message is not a JSON object or array"
+                                .to_string(),
+                            data: Some(self.pos.into()),
+                        });
+                    }
+                }
+            }
+            if self.in_string {
+                match b {
+                    _ if self.escaped => self.escaped = false,
+                    b'\\' => self.escaped = true,
+                    b'"' => self.in_string = false,
+                    _ => (),
+                }
+                continue;
+            }
+            match b {
+                b'"' => self.in_string = true,
+                b'{' | b'[' => self.depth += 1,
+                b'}' | b']' => {
+                    self.depth -= 1;
+                    if self.depth == 0 {
+                        let end = self.pos;
+                        *self = Self::default();
+                        return Ok(Some(end));
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[test]
+fn test_decoder_bytes_needed() {
+    use super::Request;
+    use alloc::string::ToString;
+
+    let frame = Request::new("foo").with_id(1).to_string();
+    let header_len = frame.find("\r\n\r\n").unwrap() + 4;
+    let mut decoder = ContentLengthDecoder::new();
+    assert_eq!(decoder.bytes_needed(), None);
+
+    decoder.push(&frame.as_bytes()[..header_len - 1]);
+    assert_eq!(decoder.bytes_needed(), None);
+    assert!(decoder.poll().unwrap().is_none());
+
+    decoder.push(&frame.as_bytes()[header_len - 1..header_len + 3]);
+    let needed = decoder.bytes_needed().unwrap();
+    assert_eq!(needed, frame.len() - header_len - 3);
+    assert!(decoder.poll().unwrap().is_none());
+
+    decoder.push(&frame.as_bytes()[header_len + 3..]);
+    assert_eq!(decoder.bytes_needed(), Some(0));
+    let message = decoder.poll().unwrap().unwrap();
+    assert_eq!(message.as_request().unwrap().method, "foo");
+    assert_eq!(decoder.bytes_needed(), None);
+    assert_eq!(decoder.buffered(), 0);
+}
+
+#[test]
+fn test_decoder_byte_at_a_time() {
+    use super::{Notification, Response};
+    use alloc::string::ToString;
+
+    let mut stream = Notification::new("progress").to_string();
+    stream.push_str(&Response::ok(1, true).to_string());
+
+    let mut decoder = ContentLengthDecoder::new();
+    let mut messages = Vec::new();
+    for b in stream.as_bytes() {
+        decoder.push(core::slice::from_ref(b));
+        while let Some(message) = decoder.poll().unwrap() {
+            messages.push(message);
+        }
+    }
+    assert_eq!(messages.len(), 2);
+    assert!(messages[0].as_notification().is_some());
+    assert!(messages[1].as_response().is_some());
+}
+
+#[test]
+fn test_decoder_invalid_frames() {
+    use super::Request;
+    use alloc::string::ToString;
+
+    let mut decoder = ContentLengthDecoder::new();
+    decoder.push(b"Content-Length: 3\r\n\r\n{]}");
+    decoder.push(Request::new("foo").to_string().as_bytes());
+    assert_eq!(decoder.poll().unwrap_err().code, Error::PARSE_ERROR);
+    assert!(decoder.bytes_needed().is_some());
+    assert!(decoder.poll().unwrap().is_some());
+
+    decoder.push(b"Foo\r\n\r\n");
+    assert!(decoder.poll().is_err());
+    assert!(decoder.poll().is_err());
+    decoder.clear();
+    assert!(decoder.poll().unwrap().is_none());
+}
+
+#[test]
+fn test_decoder_length_mismatch_unclosed() {
+    use super::{Notification, Request, Response};
+    use alloc::{string::String, string::ToString};
+
+    let mut stream = String::from("Content-Length: 5\r\n\r\n{\"a\":");
+    stream.push_str(&Request::new("foo").with_id(1).to_string());
+    stream.push_str(&Notification::new("bar").to_string());
+    stream.push_str(&Response::ok(1, true).to_string());
+
+    let mut decoder = ContentLengthDecoder::with_options(ReadOptions {
+        tolerate_length_mismatch: true,
+    });
+    let mut polled = Vec::new();
+    for b in stream.as_bytes() {
+        decoder.push(&[*b]);
+        while let Some(polled_message) = decoder.poll().transpose() {
+            polled.push(polled_message);
+        }
+    }
+    assert_eq!(polled.len(), 4);
+    assert_eq!(polled[0].as_ref().unwrap_err().code, Error::PARSE_ERROR);
+    assert!(polled[1].as_ref().unwrap().as_request().is_some());
+    assert!(polled[2].as_ref().unwrap().as_notification().is_some());
+    assert!(polled[3].as_ref().unwrap().as_response().is_some());
+    assert_eq!(decoder.buffered(), 0);
+}
+
+#[test]
+fn test_decoder_max_frame_len() {
+    use super::Request;
+    use alloc::string::ToString;
+
+    let mut decoder = ContentLengthDecoder::new();
+    decoder.push(b"Content-Length: 18446744073709551615\r\n\r\n{}");
+    assert_eq!(decoder.bytes_needed(), None);
+    assert_eq!(decoder.poll().unwrap_err().code, Error::INVALID_REQUEST);
+    assert!(decoder.poll().is_err());
+    decoder.clear();
+
+    let frame = Request::new("foo").with_id(1).to_string();
+    let mut decoder = ContentLengthDecoder::new().with_max_frame_len(frame.len() - 1);
+    decoder.push(frame.as_bytes());
+    let err = decoder.poll().unwrap_err();
+    assert_eq!(err.data, Some((frame.len() - 1).into()));
+
+    let mut decoder = ContentLengthDecoder::new().with_max_frame_len(frame.len());
+    decoder.push(frame.as_bytes());
+    assert!(decoder.poll().unwrap().is_some());
+}
+
+#[test]
+fn test_concatenated_decoder() {
+    use super::{Notification, Request};
+    use alloc::string::String;
+
+    let mut stream = String::from(r#"{"jsonrpc":"2.0","method":"a}\"{","params":["]"]}"#);
+    stream.push_str(&Request::new("b").with_id(1).to_compact_json().unwrap());
+    stream.push('\n');
+    stream.push_str(&Notification::new("c").to_compact_json().unwrap());
+
+    let mut decoder = ConcatenatedDecoder::new();
+    let (head, tail) = stream.split_at(30);
+    decoder.push(head.as_bytes());
+    assert!(decoder.poll().unwrap().is_none());
+    decoder.push(tail.as_bytes());
+
+    let first = decoder.poll().unwrap().unwrap();
+    assert_eq!(first.as_notification().unwrap().method, "a}\"{");
+    let second = decoder.poll().unwrap().unwrap();
+    assert_eq!(second.as_request().unwrap().method, "b");
+    let third = decoder.poll().unwrap().unwrap();
+    assert_eq!(third.as_notification().unwrap().method, "c");
+    assert!(decoder.poll().unwrap().is_none());
+    assert_eq!(decoder.buffered(), 0);
+
+    decoder.push(b" 42 {}");
+    assert_eq!(decoder.poll().unwrap_err().code, Error::INVALID_REQUEST);
+    assert!(decoder.poll().is_err());
+    decoder.clear();
+    assert!(decoder.poll().unwrap().is_none());
+}
+
+#[test]
+fn test_decoder_length_mismatch() {
+    use super::{Notification, Request};
+    use alloc::{format, string::ToString};
+
+    let body = Request::new("foo").with_id(1).to_compact_json().unwrap();
+    let next = Notification::new("bar").to_string();
+
+    for declared in [body.len() - 3, body.len() + 3] {
+        let stream = format!("Content-Length: {}\r\n\r\n{}{}", declared, body, next);
+
+        let mut strict = ContentLengthDecoder::new();
+        strict.push(stream.as_bytes());
+        assert!(strict.poll().is_err());
+
+        let mut decoder = ContentLengthDecoder::with_options(ReadOptions {
+            tolerate_length_mismatch: true,
+        });
+        let mut polled = Vec::new();
+        for b in stream.as_bytes() {
+            decoder.push(&[*b]);
+            while let Some(message) = decoder.poll().unwrap() {
+                polled.push((message, decoder.take_mismatch()));
+            }
+        }
+        assert_eq!(polled.len(), 2);
+        assert_eq!(polled[0].0.as_request().unwrap().method, "foo");
+        assert_eq!(
+            polled[0].1,
+            Some(LengthMismatch {
+                declared,
+                actual: body.len()
+            })
+        );
+        assert!(polled[1].0.as_notification().is_some());
+        assert!(polled[1].1.is_none());
+        assert_eq!(decoder.buffered(), 0);
+    }
+}
@@ -1,9 +1,17 @@
-use alloc::string::String;
-use core::fmt;
+use super::{helpers, Response};
+use alloc::string::{String, ToString};
+use core::{fmt, ops::RangeInclusive};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 /// JSON-RPC protocol error.
+///
+/// The specification reserves the codes [`Error::RESERVED`], `-32768..=-32000`, for itself:
+///
+/// - the predefined protocol errors, such as [`Error::PARSE_ERROR`];
+/// - the implementation-defined server errors [`Error::SERVER_ERRORS`], `-32099..=-32000`.
+///
+/// Every other code is available to the application.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Error {
     /// Error code
@@ -19,6 +27,254 @@ impl Error {
     pub const PARSE_ERROR: i32 = -32700;
     /// Protocol level invalid request reserved code
     pub const INVALID_REQUEST: i32 = -32600;
+    /// Protocol level method not found reserved code
+    pub const METHOD_NOT_FOUND: i32 = -32601;
+    /// Protocol level invalid method parameters reserved code
+    pub const INVALID_PARAMS: i32 = -32602;
+    /// Protocol level internal error reserved code
+    pub const INTERNAL_ERROR: i32 = -32603;
+    /// Implementation-defined server error code of a request whose response was not received
+    /// in time
+    pub const TIMEOUT: i32 = -32002;
+    /// Codes reserved by the specification
+    pub const RESERVED: RangeInclusive<i32> = -32768..=-32000;
+    /// Codes of the implementation-defined server errors
+    pub const SERVER_ERRORS: RangeInclusive<i32> = -32099..=-32000;
+
+    /// Create an implementation-defined server error.
+    ///
+    /// Fails if the code is outside [`Error::SERVER_ERRORS`].
+    ///
+    /// ```
+    /// use jsonrpc_base::{Error, Response};
+    ///
+    /// let error = Error::server(-32001, "backend unavailable").unwrap();
+    /// let response = Response::err(7, error);
+    /// assert_eq!(response.error().unwrap().code, -32001);
+    ///
+    /// assert!(Error::server(-3200, "typo").is_err());
+    /// ```
+    pub fn server<M>(code: i32, message: M) -> Result<Self, Error>
+    where
+        M: ToString,
+    {
+        Self::in_range(
+            code,
+            message,
+            Self::SERVER_ERRORS.contains(&code),
+            "a server error",
+        )
+    }
+
+    /// Create an application error.
+    ///
+    /// Fails if the code is inside [`Error::RESERVED`].
+    pub fn application<M>(code: i32, message: M) -> Result<Self, Error>
+    where
+        M: ToString,
+    {
+        Self::in_range(
+            code,
+            message,
+            !Self::RESERVED.contains(&code),
+            "an application error",
+        )
+    }
+
+    fn in_range<M>(code: i32, message: M, valid: bool, kind: &str) -> Result<Self, Error>
+    where
+        M: ToString,
+    {
+        if !valid {
+            return Err(Error {
+                code: Error::INTERNAL_ERROR,
+                message: alloc::format!("the code {} is not valid for {}", code, kind),
+                data: Some(Value::from(code)),
+            });
+        }
+        Ok(Error {
+            code,
+            message: message.to_string(),
+            data: None,
+        })
+    }
+
+    /// Check if the error code is one of the predefined protocol errors.
+    ///
+    /// These are transport or protocol failures, as opposed to application errors.
+    pub fn is_protocol(&self) -> bool {
+        matches!(
+            self.code,
+            Error::PARSE_ERROR
+                | Error::INVALID_REQUEST
+                | Error::METHOD_NOT_FOUND
+                | Error::INVALID_PARAMS
+                | Error::INTERNAL_ERROR
+        )
+    }
+
+    /// Check if the error code is outside the range `-32768..=-32000` reserved by the spec.
+    ///
+    /// The implementation-defined server errors `-32099..=-32000` are neither protocol nor
+    /// application errors.
+    pub fn is_application(&self) -> bool {
+        !Self::RESERVED.contains(&self.code)
+    }
+
+    /// Error code, with the codes defined by the specification named
+    pub fn code(&self) -> ErrorCode {
+        ErrorCode::from(self.code)
+    }
+
+    /// Set the error code
+    pub fn set_code(&mut self, code: ErrorCode) {
+        self.code = code.into();
+    }
+
+    /// Replace the error code with the provided value
+    pub fn with_code(mut self, code: i32) -> Self {
+        self.code = code;
+        self
+    }
+
+    /// Replace the error message with the provided value
+    pub fn with_message<M>(mut self, message: M) -> Self
+    where
+        M: ToString,
+    {
+        self.message = message.to_string();
+        self
+    }
+
+    /// Bound the size of the data, such as the offending JSON attached by the parsers.
+    ///
+    /// A string longer than `max_bytes` is cut at a char boundary and suffixed with `…`, the
+    /// suffix included in the bound; below its 3 bytes, the string is cut without it. Arrays and
+    /// objects keep their leading elements for their JSON, brackets and separators included, to
+    /// fit in `max_bytes`, down to being empty. Other values are left as is.
+    pub fn truncate_data(&mut self, max_bytes: usize) {
+        match &mut self.data {
+            Some(Value::String(s)) if s.len() > max_bytes => {
+                let (mut end, suffix) = match max_bytes.checked_sub('…'.len_utf8()) {
+                    Some(end) => (end, Some('…')),
+                    None => (max_bytes, None),
+                };
+                while !s.is_char_boundary(end) {
+                    end -= 1;
+                }
+                s.truncate(end);
+                s.extend(suffix);
+            }
+            Some(Value::Array(values)) => {
+                let keep = fitting(values.iter().map(helpers::json_len), max_bytes);
+                values.truncate(keep);
+            }
+            Some(Value::Object(map)) => {
+                let members = map
+                    .iter()
+                    .map(|(k, v)| Ok(helpers::json_len(k)? + 1 + helpers::json_len(v)?));
+                let keep = fitting(members, max_bytes);
+                *map = core::mem::take(map).into_iter().take(keep).collect();
+            }
+            _ => (),
+        }
+    }
+
+    /// Serialize the error object to compact JSON
+    pub fn to_compact_json(&self) -> Result<String, Error> {
+        helpers::to_json_compact(self)
+    }
+
+    /// Serialize the error object to indented JSON
+    pub fn to_pretty_json(&self) -> Result<String, Error> {
+        helpers::to_json_pretty(self)
+    }
+
+    /// Create an error response to the request with the provided ID
+    pub fn into_response<I>(self, id: I) -> Response
+    where
+        I: Into<Value>,
+    {
+        Response::err(id, self)
+    }
+
+    /// Create an error with the provided code from a standard error.
+    ///
+    /// The message is the display of the error, and the data is an array with the display of each
+    /// error of its `source` chain, outermost first; there is no data if the chain is empty.
+    #[cfg(feature = "std")]
+    pub fn from_std(e: &dyn std::error::Error, code: i32) -> Error {
+        let sources: Vec<Value> = core::iter::successors(e.source(), |e| e.source())
+            .map(|e| Value::String(e.to_string()))
+            .collect();
+        Error {
+            code,
+            message: e.to_string(),
+            data: (!sources.is_empty()).then_some(Value::Array(sources)),
+        }
+    }
+}
+
+/// Number of leading elements whose JSON lengths, with the brackets and commas of their array or
+/// object, fit in the budget
+fn fitting<I>(lengths: I, max_bytes: usize) -> usize
+where
+    I: Iterator<Item = Result<usize, Error>>,
+{
+    let mut total = "[]".len();
+    lengths
+        .enumerate()
+        .take_while(|(i, len)| {
+            let separator = usize::from(*i > 0);
+            total = total
+                .saturating_add(separator)
+                .saturating_add(*len.as_ref().unwrap_or(&usize::MAX));
+            total <= max_bytes
+        })
+        .count()
+}
+
+#[cfg(feature = "std")]
+impl From<Box<dyn std::error::Error>> for Error {
+    /// Convert a boxed standard error into an [`Error::INTERNAL_ERROR`] with its display as
+    /// message.
+    fn from(e: Box<dyn std::error::Error>) -> Self {
+        Error {
+            code: Error::INTERNAL_ERROR,
+            message: e.to_string(),
+            data: None,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Box<dyn std::error::Error + Send + Sync>> for Error {
+    /// Convert a boxed standard error into an [`Error::INTERNAL_ERROR`] with its display as
+    /// message.
+    fn from(e: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        Error::from(e as Box<dyn std::error::Error>)
+    }
+}
+
+impl From<Error> for Value {
+    /// Convert the error into its JSON-RPC error object, without loss.
+    ///
+    /// The inverse is `Error::try_from(value)`.
+    fn from(error: Error) -> Self {
+        serde_json::to_value(error).unwrap_or(Value::Null)
+    }
+}
+
+impl TryFrom<Value> for Error {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        serde_json::from_value(value.clone()).map_err(|e| Error {
+            code: Error::PARSE_ERROR,
+            message: e.to_string(),
+            data: Some(value),
+        })
+    }
 }
 
 impl fmt::Display for Error {
@@ -26,3 +282,362 @@ impl fmt::Display for Error {
         write!(f, "{}", self.message)
     }
 }
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Error code, naming the codes defined by the specification.
+///
+/// Serialized as the plain number; every `i32` converts to a code and back without loss. Codes
+/// compare, hash and display as the number they encode, so a variant built with the payload of
+/// another one, such as `Other(-32601)`, equals the variant [`From<i32>`] returns for it.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(from = "i32", into = "i32")]
+pub enum ErrorCode {
+    /// [`Error::PARSE_ERROR`]
+    ParseError,
+    /// [`Error::INVALID_REQUEST`]
+    InvalidRequest,
+    /// [`Error::METHOD_NOT_FOUND`]
+    MethodNotFound,
+    /// [`Error::INVALID_PARAMS`]
+    InvalidParams,
+    /// [`Error::INTERNAL_ERROR`]
+    InternalError,
+    /// Implementation-defined server error, in [`Error::SERVER_ERRORS`]
+    ServerError(i32),
+    /// Any other code
+    Other(i32),
+}
+
+impl From<i32> for ErrorCode {
+    fn from(code: i32) -> Self {
+        match code {
+            Error::PARSE_ERROR => ErrorCode::ParseError,
+            Error::INVALID_REQUEST => ErrorCode::InvalidRequest,
+            Error::METHOD_NOT_FOUND => ErrorCode::MethodNotFound,
+            Error::INVALID_PARAMS => ErrorCode::InvalidParams,
+            Error::INTERNAL_ERROR => ErrorCode::InternalError,
+            c if Error::SERVER_ERRORS.contains(&c) => ErrorCode::ServerError(c),
+            c => ErrorCode::Other(c),
+        }
+    }
+}
+
+impl From<ErrorCode> for i32 {
+    fn from(code: ErrorCode) -> Self {
+        match code {
+            ErrorCode::ParseError => Error::PARSE_ERROR,
+            ErrorCode::InvalidRequest => Error::INVALID_REQUEST,
+            ErrorCode::MethodNotFound => Error::METHOD_NOT_FOUND,
+            ErrorCode::InvalidParams => Error::INVALID_PARAMS,
+            ErrorCode::InternalError => Error::INTERNAL_ERROR,
+            ErrorCode::ServerError(c) | ErrorCode::Other(c) => c,
+        }
+    }
+}
+
+impl PartialEq for ErrorCode {
+    fn eq(&self, other: &ErrorCode) -> bool {
+        i32::from(*self) == i32::from(*other)
+    }
+}
+
+impl Eq for ErrorCode {}
+
+impl core::hash::Hash for ErrorCode {
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: core::hash::Hasher,
+    {
+        i32::from(*self).hash(state)
+    }
+}
+
+impl PartialEq<i32> for ErrorCode {
+    fn eq(&self, other: &i32) -> bool {
+        i32::from(*self) == *other
+    }
+}
+
+impl PartialEq<ErrorCode> for i32 {
+    fn eq(&self, other: &ErrorCode) -> bool {
+        other == self
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let code = i32::from(*self);
+        let name = match ErrorCode::from(code) {
+            ErrorCode::ParseError => "parse error",
+            ErrorCode::InvalidRequest => "invalid request",
+            ErrorCode::MethodNotFound => "method not found",
+            ErrorCode::InvalidParams => "invalid params",
+            ErrorCode::InternalError => "internal error",
+            ErrorCode::ServerError(_) => "server error",
+            ErrorCode::Other(_) => "error",
+        };
+        write!(f, "{} ({})", name, code)
+    }
+}
+
+#[test]
+fn test_error_builder() {
+    let err = Error {
+        code: 0,
+        message: String::new(),
+        data: None,
+    }
+    .with_code(Error::INVALID_REQUEST)
+    .with_message(alloc::format!("param '{}' is invalid", "foo"));
+    assert_eq!(err.code, Error::INVALID_REQUEST);
+    assert_eq!(err.message, "param 'foo' is invalid");
+}
+
+#[test]
+fn test_error_into_value() {
+    let err = Error {
+        code: Error::INTERNAL_ERROR,
+        message: "foo".to_string(),
+        data: Some(Value::from(1)),
+    };
+    assert_eq!(
+        Value::from(err),
+        serde_json::json!({"code": -32603, "message": "foo", "data": 1})
+    );
+}
+
+#[test]
+fn test_error_value_round_trip() {
+    let err = Error {
+        code: Error::INVALID_PARAMS,
+        message: "foo".to_string(),
+        data: Some(serde_json::json!({"bar": [1, 2]})),
+    };
+    let back = Error::try_from(Value::from(err.clone())).unwrap();
+    assert_eq!(back.code, err.code);
+    assert_eq!(back.message, err.message);
+    assert_eq!(back.data, err.data);
+
+    let err = Error::try_from(Value::from("foo")).unwrap_err();
+    assert_eq!(err.code, Error::PARSE_ERROR);
+    assert_eq!(err.data, Some(Value::from("foo")));
+}
+
+#[test]
+fn test_error_classification() {
+    let err = |code| Error {
+        code,
+        message: String::new(),
+        data: None,
+    };
+
+    for code in [-32700, -32600, -32601, -32602, -32603] {
+        assert!(err(code).is_protocol());
+        assert!(!err(code).is_application());
+    }
+    for code in [-32000, -32050, -32099, -32768, -32604] {
+        assert!(!err(code).is_protocol());
+        assert!(!err(code).is_application());
+    }
+    for code in [-31999, -32769, 0, 1, i32::MIN] {
+        assert!(!err(code).is_protocol());
+        assert!(err(code).is_application());
+    }
+}
+
+#[test]
+fn test_error_into_response() {
+    let response = Error {
+        code: Error::METHOD_NOT_FOUND,
+        message: "foo".to_string(),
+        data: None,
+    }
+    .into_response(3);
+    assert_eq!(response.id, Value::from(3));
+    assert!(response.result().is_none());
+    assert_eq!(response.error().unwrap().code, Error::METHOD_NOT_FOUND);
+}
+
+#[test]
+fn test_error_server_and_application_ranges() {
+    for code in [-32099, -32050, -32000] {
+        let error = Error::server(code, "foo").unwrap();
+        assert_eq!(error.code, code);
+        assert!(!error.is_protocol());
+        assert!(!error.is_application());
+        assert!(Error::application(code, "foo").is_err());
+    }
+
+    for code in [-32100, -31999, -3200, Error::INTERNAL_ERROR] {
+        let err = Error::server(code, "foo").unwrap_err();
+        assert_eq!(err.code, Error::INTERNAL_ERROR);
+        assert_eq!(err.data, Some(Value::from(code)));
+    }
+
+    for code in [-32769, -31999, 0, 1] {
+        assert!(Error::application(code, "foo").unwrap().is_application());
+    }
+    for code in [-32768, -32700, -32000] {
+        assert!(Error::application(code, "foo").is_err());
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_error_from_std() {
+    #[derive(Debug)]
+    struct Layer(&'static str, Option<Box<Layer>>);
+
+    impl fmt::Display for Layer {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str(self.0)
+        }
+    }
+
+    impl std::error::Error for Layer {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            self.1.as_deref().map(|e| e as _)
+        }
+    }
+
+    let root = Layer("connection refused", None);
+    let middle = Layer("database unavailable", Some(Box::new(root)));
+    let outer = Layer("failed to load user", Some(Box::new(middle)));
+
+    let err = Error::from_std(&outer, Error::INTERNAL_ERROR);
+    assert_eq!(err.code, Error::INTERNAL_ERROR);
+    assert_eq!(err.message, "failed to load user");
+    assert_eq!(
+        err.data,
+        Some(serde_json::json!([
+            "database unavailable",
+            "connection refused"
+        ]))
+    );
+
+    let err = Error::from_std(&Layer("leaf", None), -32001);
+    assert!(err.data.is_none());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_error_from_boxed_std() {
+    fn handle(port: &str) -> Result<Response, Error> {
+        let port: u16 = port.parse().map_err(Box::<dyn std::error::Error>::from)?;
+        Ok(Response::ok(1, port))
+    }
+
+    assert!(handle("8080").is_ok());
+    let err = handle("http").unwrap_err();
+    assert_eq!(err.code, Error::INTERNAL_ERROR);
+    assert_eq!(err.message, "invalid digit found in string");
+    assert!(err.data.is_none());
+
+    let boxed: Box<dyn std::error::Error + Send + Sync> = "timed out".into();
+    let err = Error::from(boxed);
+    assert_eq!(err.code, Error::INTERNAL_ERROR);
+    assert_eq!(err.message, "timed out");
+}
+
+#[test]
+fn test_error_code() {
+    use alloc::string::ToString;
+
+    let mut err = Error {
+        code: Error::METHOD_NOT_FOUND,
+        message: String::new(),
+        data: None,
+    };
+    assert_eq!(err.code(), ErrorCode::MethodNotFound);
+    assert_eq!(err.code(), Error::METHOD_NOT_FOUND);
+    assert_eq!(err.code().to_string(), "method not found (-32601)");
+    err.set_code(ErrorCode::ServerError(-32001));
+    assert_eq!(err.code, -32001);
+
+    for code in [-32000, -32099] {
+        assert!(matches!(ErrorCode::from(code), ErrorCode::ServerError(c) if c == code));
+    }
+    for code in [-31999, -32100, -32768] {
+        assert!(matches!(ErrorCode::from(code), ErrorCode::Other(c) if c == code));
+    }
+
+    let misplaced = ErrorCode::Other(Error::METHOD_NOT_FOUND);
+    assert_eq!(misplaced, ErrorCode::MethodNotFound);
+    assert_eq!(misplaced.to_string(), "method not found (-32601)");
+    assert_eq!(ErrorCode::ServerError(42), ErrorCode::Other(42));
+    assert_eq!(ErrorCode::ServerError(42).to_string(), "error (42)");
+    #[cfg(feature = "std")]
+    {
+        use std::collections::HashSet;
+
+        let codes: HashSet<_> = [misplaced, ErrorCode::MethodNotFound].into_iter().collect();
+        assert_eq!(codes.len(), 1);
+    }
+
+    for code in [
+        Error::PARSE_ERROR,
+        -32001,
+        -32500,
+        0,
+        42,
+        i32::MIN,
+        i32::MAX,
+    ] {
+        let json = serde_json::to_string(&ErrorCode::from(code)).unwrap();
+        assert_eq!(json, code.to_string());
+        let parsed: ErrorCode = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, code);
+        assert_eq!(i32::from(parsed), code);
+    }
+    let parsed: ErrorCode = serde_json::from_str("-32500").unwrap();
+    assert!(matches!(parsed, ErrorCode::Other(-32500)));
+}
+
+#[test]
+fn test_error_truncate_data() {
+    use serde_json::json;
+
+    let mut err = Error {
+        code: Error::PARSE_ERROR,
+        message: String::new(),
+        data: Some(Value::from("ab€cd")),
+    };
+    let truncations = [
+        (7, "ab€cd"),
+        (6, "ab…"),
+        (5, "ab…"),
+        (4, "a…"),
+        (3, "…"),
+        (2, "ab"),
+        (0, ""),
+    ];
+    for (max_bytes, truncated) in truncations {
+        let mut err = err.clone();
+        err.truncate_data(max_bytes);
+        assert_eq!(err.data, Some(Value::from(truncated)));
+    }
+    err.truncate_data(4);
+    err.truncate_data(16);
+    assert_eq!(err.data, Some(Value::from("a…")));
+
+    let mut err = err.with_code(Error::INTERNAL_ERROR);
+    err.data = Some(json!([1, "two", 3]));
+    err.truncate_data(10);
+    assert_eq!(err.data, Some(json!([1, "two"])));
+    err.truncate_data(8);
+    assert_eq!(err.data, Some(json!([1])));
+    err.truncate_data(0);
+    assert_eq!(err.data, Some(json!([])));
+
+    err.data = Some(json!({"a": 1, "b": 2}));
+    err.truncate_data(12);
+    assert_eq!(err.data, Some(json!({"a": 1})));
+    err.truncate_data(7);
+    assert_eq!(err.data, Some(json!({"a": 1})));
+
+    err.data = Some(json!(12345));
+    err.truncate_data(1);
+    assert_eq!(err.data, Some(json!(12345)));
+}
@@ -0,0 +1,136 @@
+use super::Message;
+use alloc::{boxed::Box, collections::BTreeMap};
+use core::{
+    any::{Any, TypeId},
+    fmt,
+};
+use serde::{Serialize, Serializer};
+
+/// Per-message values keyed by their type, such as an arrival timestamp or an authenticated user.
+///
+/// The values are never serialized. The container is not `Clone`, as its values are not required
+/// to be; a middleware forwarding a message elsewhere inserts what the destination needs.
+#[derive(Default)]
+pub struct Extensions {
+    map: BTreeMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl Extensions {
+    /// Create an empty container.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a value, returning the previous value of the same type, if any.
+    pub fn insert<T>(&mut self, value: T) -> Option<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.map
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|v| v.downcast().ok())
+            .map(|v| *v)
+    }
+
+    /// Return a reference to the value of the provided type, if any.
+    pub fn get<T>(&self) -> Option<&T>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.map
+            .get(&TypeId::of::<T>())
+            .and_then(|v| v.downcast_ref())
+    }
+
+    /// Return a mutable reference to the value of the provided type, if any.
+    pub fn get_mut<T>(&mut self) -> Option<&mut T>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.map
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|v| v.downcast_mut())
+    }
+
+    /// Remove the value of the provided type, returning it if present.
+    pub fn remove<T>(&mut self) -> Option<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        self.map
+            .remove(&TypeId::of::<T>())
+            .and_then(|v| v.downcast().ok())
+            .map(|v| *v)
+    }
+
+    /// Number of values in the container
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Return `true` if the container has no values
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+impl fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Extensions")
+            .field("len", &self.map.len())
+            .finish()
+    }
+}
+
+/// Message with the [`Extensions`] attached to it by middleware.
+///
+/// Serializes exactly as the wrapped message.
+#[derive(Debug)]
+pub struct MessageEnvelope {
+    /// Wrapped message
+    pub message: Message,
+    /// Values attached to the message
+    pub extensions: Extensions,
+}
+
+impl From<Message> for MessageEnvelope {
+    fn from(message: Message) -> Self {
+        Self {
+            message,
+            extensions: Extensions::new(),
+        }
+    }
+}
+
+impl Serialize for MessageEnvelope {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.message.serialize(serializer)
+    }
+}
+
+impl fmt::Display for MessageEnvelope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.message.fmt(f)
+    }
+}
+
+#[test]
+fn test_extensions() {
+    #[derive(Debug, PartialEq)]
+    struct User(&'static str);
+
+    let mut extensions = Extensions::new();
+    assert!(extensions.insert(User("alice")).is_none());
+    assert_eq!(extensions.insert(7u64), None);
+    assert_eq!(extensions.insert(User("bob")), Some(User("alice")));
+    assert_eq!(extensions.len(), 2);
+
+    *extensions.get_mut::<u64>().unwrap() += 1;
+    assert_eq!(extensions.get::<u64>(), Some(&8));
+    assert_eq!(extensions.remove::<User>(), Some(User("bob")));
+    assert!(extensions.get::<User>().is_none());
+    assert!(extensions.get::<u32>().is_none());
+}
@@ -1,9 +1,150 @@
-use super::Error;
-use alloc::string::ToString;
+//! Framing helpers of the `Content-Length` transport.
+
+use super::{telemetry, Error};
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use core::fmt::{self, Write};
+use serde::Serialize;
 use serde_json::Value;
 
+/// Frame the provided JSON body with its `Content-Length` header.
+///
+/// The body is written as is; the length is its size in bytes. Every `Display` implementation of
+/// the crate produces the same output, written straight to the formatter.
+pub fn frame(body: &str) -> String {
+    format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+}
+
+/// Write the frame of the provided JSON body, as built by [`frame`], without allocating it.
+pub(crate) fn write_frame<W>(f: &mut W, body: &str) -> fmt::Result
+where
+    W: fmt::Write + ?Sized,
+{
+    write!(f, "Content-Length: {}\r\n\r\n{}", body.len(), body)
+}
+
+/// Serialize the value and write its frame, as [`write_frame`] does with its JSON.
+///
+/// With the feature `std`, the JSON is serialized twice, once to count its length and once
+/// straight into the writer, instead of being buffered.
+pub(crate) fn write_framed<W, T>(f: &mut W, value: &T) -> fmt::Result
+where
+    W: fmt::Write + ?Sized,
+    T: Serialize + ?Sized,
+{
+    #[cfg(feature = "std")]
+    {
+        let len = json_len(value).map_err(|_| fmt::Error)?;
+        write!(f, "Content-Length: {}\r\n\r\n", len)?;
+        serde_json::to_writer(io::FmtWriter(f), value).map_err(|_| fmt::Error)
+    }
+
+    #[cfg(not(feature = "std"))]
+    serde_json::to_string(value)
+        .map_err(|_| fmt::Error)
+        .and_then(|m| write_frame(f, &m))
+}
+
+/// Frame the message as its `to_string` does, but fail with an [`Error::PARSE_ERROR`] if it
+/// cannot be serialized.
+///
+/// `Display` reports serialization failures as a bare `fmt::Error`, on which `to_string`
+/// panics; prefer this function in production code.
+#[cfg(feature = "std")]
+pub fn write_message_to_string(message: &super::Message) -> Result<String, Error> {
+    message.to_compact_json().map(|json| frame(&json))
+}
+
+/// Length of the frame of a JSON body with the provided length, header included.
+pub(crate) fn frame_len(body_len: usize) -> usize {
+    let mut digits = 1;
+    let mut n = body_len;
+    while n >= 10 {
+        n /= 10;
+        digits += 1;
+    }
+    "Content-Length: \r\n\r\n".len() + digits + body_len
+}
+
+/// Length of the JSON serialization of the value.
+///
+/// With the feature `std`, the JSON is written to a counting sink instead of a buffer.
+pub(crate) fn json_len<T>(value: &T) -> Result<usize, Error>
+where
+    T: Serialize + ?Sized,
+{
+    let serialization_failed = |e: serde_json::Error| Error {
+        code: Error::PARSE_ERROR,
+        message: e.to_string(),
+        data: None,
+    };
+
+    #[cfg(feature = "std")]
+    {
+        let mut counter = io::Counter::default();
+        serde_json::to_writer(&mut counter, value).map_err(serialization_failed)?;
+        Ok(counter.0)
+    }
+
+    #[cfg(not(feature = "std"))]
+    serde_json::to_string(value)
+        .map(|json| json.len())
+        .map_err(serialization_failed)
+}
+
+/// Approximate length of the JSON serialization of the value, computed without serializing it.
+///
+/// Escapes and the exact width of numbers are not accounted for.
+pub(crate) fn len_hint(value: &Value) -> usize {
+    match value {
+        Value::Null => 4,
+        Value::Bool(_) => 5,
+        Value::Number(_) => 8,
+        Value::String(s) => s.len() + 2,
+        Value::Array(a) => 2 + a.iter().map(|v| len_hint(v) + 1).sum::<usize>(),
+        Value::Object(o) => {
+            2 + o
+                .iter()
+                .map(|(k, v)| member_len(k, len_hint(v)))
+                .sum::<usize>()
+        }
+    }
+}
+
+/// Length of a member named as the key, with a value of the provided length.
+///
+/// The quotes of the key, its colon, and the comma separating it from the next member are
+/// counted.
+pub(crate) fn member_len(key: &str, value_len: usize) -> usize {
+    "\"\":,".len() + key.len() + value_len
+}
+
+/// Approximate length of an optional member named as the key
+pub(crate) fn member_len_hint(key: &str, value: Option<&Value>) -> usize {
+    value.map_or(0, |v| member_len(key, len_hint(v)))
+}
+
+/// Length of a string member named as the key, with the quotes of its value
+pub(crate) fn str_member_len(key: &str, value: &str) -> usize {
+    member_len(key, "\"\"".len() + value.len())
+}
+
+/// Approximate length of the object of an error
+pub(crate) fn error_len_hint(err: &Error) -> usize {
+    "{}".len()
+        + member_len("code", len_hint(&Value::from(err.code)))
+        + str_member_len("message", &err.message)
+        + member_len("data", len_hint(err.data.as_ref().unwrap_or(&Value::Null)))
+}
+
 /// Read the content length from the argument, returning the parsed value and remainder string.
-pub fn get_content_length(mut s: &str) -> Result<(&str, &str), Error> {
+pub fn get_content_length(s: &str) -> Result<(&str, &str), Error> {
+    split_content_length(s).inspect_err(|e| telemetry::parse_failed(e, s))
+}
+
+fn split_content_length(mut s: &str) -> Result<(&str, &str), Error> {
     let length;
     loop {
         let (line, rest) = s.split_once('\n').ok_or_else(|| Error {
@@ -38,6 +179,9 @@ pub fn get_content_length(mut s: &str) -> Result<(&str, &str), Error> {
         if line.trim().is_empty() {
             break;
         }
+        if is_content_length(line) {
+            return Err(duplicate_content_length(line));
+        }
     }
 
     if s.len() < length {
@@ -48,119 +192,1784 @@ pub fn get_content_length(mut s: &str) -> Result<(&str, &str), Error> {
         });
     }
 
+    // The body read from bytes would be invalid UTF-8
+    if !s.is_char_boundary(length) {
+        return Err(Error {
+            code: Error::PARSE_ERROR,
+            message: "the provided content length ends within a character".to_string(),
+            data: Some(Value::from(length)),
+        });
+    }
+
     Ok(s.split_at(length))
 }
 
-#[test]
-fn test_get_content_length() {
-    let bytes = "Foo: HTTP/1.1\r\nContent-Length: 5\r\n\r\nHelloEXTRA";
-    let (message, remainder) = get_content_length(bytes).unwrap();
-    assert_eq!(message.as_bytes(), b"Hello");
-    assert_eq!(remainder.as_bytes(), b"EXTRA");
+/// Read the content length from the byte slice, returning the body and remainder slices.
+///
+/// The header region must be ASCII; the body is not decoded.
+pub fn get_content_length_bytes(b: &[u8]) -> Result<(&[u8], &[u8]), Error> {
+    split_content_length_bytes(b)
+        .inspect_err(|e| telemetry::parse_failed(e, &String::from_utf8_lossy(b)))
 }
 
-#[cfg(feature = "std")]
-pub use io::get_content_from_reader;
+fn split_content_length_bytes(b: &[u8]) -> Result<(&[u8], &[u8]), Error> {
+    let (header_len, length) = frame_bounds(b)?.ok_or_else(|| Error {
+        code: Error::INVALID_REQUEST,
+        message: "the provided request header is invalid".to_string(),
+        data: Some(Value::String(String::from_utf8_lossy(b).into_owned())),
+    })?;
+    let s = &b[header_len..];
 
-#[cfg(feature = "std")]
-mod io {
-    use super::*;
-    use std::io::{self, prelude::*};
+    if s.len() < length {
+        return Err(Error {
+            code: Error::INVALID_REQUEST,
+            message: "the provided request is invalid".to_string(),
+            data: Some(Value::String(String::from_utf8_lossy(s).into_owned())),
+        });
+    }
 
-    /// Read the contents length of the argument and fill a buffer with its size.
-    ///
-    /// Return the amount of read bytes, and the extracted bytes buffer.
-    pub fn get_content_from_reader<R>(mut reader: R) -> Result<(usize, String), Error>
+    Ok(s.split_at(length))
+}
+
+/// Count the complete frames at the start of the string, without parsing their bodies.
+///
+/// Counting stops at the first incomplete or invalid frame. No allocation is made for valid
+/// frames.
+pub fn count_messages(s: &str) -> usize {
+    let mut b = s.as_bytes();
+    let mut count = 0;
+    while let Some(len) = complete_frame_len(b) {
+        b = &b[len..];
+        count += 1;
+    }
+    count
+}
+
+/// Check if the string starts with a complete frame, without parsing its body.
+pub fn has_complete_message(s: &str) -> bool {
+    complete_frame_len(s.as_bytes()).is_some()
+}
+
+fn complete_frame_len(b: &[u8]) -> Option<usize> {
+    match frame_bounds(b) {
+        Ok(Some((header_len, body_len))) if b.len() - header_len >= body_len => {
+            Some(header_len + body_len)
+        }
+        _ => None,
+    }
+}
+
+/// Default cap of a frame, header included, buffered by the incremental decoders
+pub const MAX_FRAME_LEN: usize = 64 * 1024 * 1024;
+
+/// Length of the frame with the provided header and body lengths, if it is at most `max_len`.
+pub(crate) fn checked_frame_len(
+    header_len: usize,
+    length: usize,
+    max_len: usize,
+) -> Result<usize, Error> {
+    match header_len.checked_add(length) {
+        Some(frame_len) if frame_len <= max_len => Ok(frame_len),
+        _ => Err(Error {
+            code: Error::INVALID_REQUEST,
+            message: format!("the frame exceeds the limit of {} bytes", max_len),
+            data: Some(Value::from(max_len)),
+        }),
+    }
+}
+
+/// Lengths of the header and of the body of the frame at the start of the bytes, or `None` if
+/// the header is not complete.
+///
+/// The body may not be complete.
+pub(crate) fn frame_bounds(b: &[u8]) -> Result<Option<(usize, usize)>, Error> {
+    let invalid = |s: &[u8]| Error {
+        code: Error::INVALID_REQUEST,
+        message: "the provided request header is invalid".to_string(),
+        data: Some(Value::String(String::from_utf8_lossy(s).into_owned())),
+    };
+
+    let mut s = b;
+    let length;
+    loop {
+        let Some((line, rest)) = split_bytes_once(s, b'\n') else {
+            return Ok(None);
+        };
+        s = rest;
+        let (key, value) = split_bytes_once(line, b':').ok_or_else(|| invalid(s))?;
+
+        if key.trim_ascii().eq_ignore_ascii_case(b"content-length") {
+            length = core::str::from_utf8(value.trim_ascii())
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .ok_or_else(|| invalid(s))?;
+            break;
+        }
+    }
+
+    loop {
+        let Some((line, rest)) = split_bytes_once(s, b'\n') else {
+            return Ok(None);
+        };
+        s = rest;
+        if line.trim_ascii().is_empty() {
+            break;
+        }
+        if split_bytes_once(line, b':')
+            .is_some_and(|(key, _)| key.trim_ascii().eq_ignore_ascii_case(b"content-length"))
+        {
+            return Err(duplicate_content_length(&String::from_utf8_lossy(line)));
+        }
+    }
+
+    Ok(Some((b.len() - s.len(), length)))
+}
+
+/// Parse the body of a frame with the provided header length.
+pub(crate) fn parse_body<T, F>(body: &[u8], header_len: usize, parse: F) -> Result<T, Error>
+where
+    F: FnOnce(&str) -> Result<T, Error>,
+{
+    core::str::from_utf8(body)
+        .map_err(|e| Error {
+            code: Error::PARSE_ERROR,
+            message: e.to_string(),
+            data: None,
+        })
+        .and_then(parse)
+        .map_err(|e| in_frame(e, header_len))
+}
+
+fn split_bytes_once(s: &[u8], delimiter: u8) -> Option<(&[u8], &[u8])> {
+    s.iter()
+        .position(|b| *b == delimiter)
+        .map(|i| (&s[..i], &s[i + 1..]))
+}
+
+fn is_content_length(line: &str) -> bool {
+    line.split_once(':')
+        .is_some_and(|(key, _)| key.trim().eq_ignore_ascii_case("content-length"))
+}
+
+fn duplicate_content_length(line: &str) -> Error {
+    Error {
+        code: Error::INVALID_REQUEST,
+        message: "the provided request header has a duplicate content-length".to_string(),
+        data: Some(Value::String(line.to_string())),
+    }
+}
+
+/// Validate method arguments, mapping `null` to no arguments and rejecting scalars.
+pub(crate) fn check_params(params: Value) -> Result<Option<Value>, Error> {
+    let kind = match &params {
+        Value::Null => return Ok(None),
+        Value::Array(_) | Value::Object(_) => return Ok(Some(params)),
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+    };
+    Err(Error {
+        code: Error::INVALID_PARAMS,
+        message: format!(
+            "the provided params must be an array or an object, found {}",
+            kind
+        ),
+        data: Some(params),
+    })
+}
+
+/// Map a failure to deserialize the JSON into an error carrying the location of the failure.
+///
+/// Invalid JSON is a [`Error::PARSE_ERROR`]; valid JSON of the wrong shape, such as an object
+/// without `method` or a bare number, is an [`Error::INVALID_REQUEST`].
+///
+/// The data is an object with the 1-based `line` and `column`, the 0-based `byte_offset` of the
+/// failure in the JSON, and a `snippet` of up to 40 characters on each side of it. The offset of
+/// truncated JSON is its length. The location members are `null` if serde does not report one;
+/// the snippet is then the start of the JSON.
+pub(crate) fn json_error(e: serde_json::Error, json: &str) -> Error {
+    let code = match e.classify() {
+        serde_json::error::Category::Data => Error::INVALID_REQUEST,
+        _ => Error::PARSE_ERROR,
+    };
+    let offset = match e.is_eof() {
+        true => Some(json.len()),
+        false => byte_offset(json, e.line(), e.column()),
+    };
+    let data = match offset {
+        Some(offset) => serde_json::json!({
+            "line": e.line(),
+            "column": e.column(),
+            "byte_offset": offset,
+            "snippet": snippet(json, offset),
+        }),
+        None => serde_json::json!({
+            "line": null,
+            "column": null,
+            "byte_offset": null,
+            "snippet": snippet(json, 0),
+        }),
+    };
+    Error {
+        code,
+        message: e.to_string(),
+        data: Some(data),
+    }
+}
+
+/// Offset of the byte at the 1-based line and column reported by serde, clamped to the JSON.
+fn byte_offset(json: &str, line: usize, column: usize) -> Option<usize> {
+    let line_start = match line {
+        0 => return None,
+        1 => 0,
+        _ => json
+            .match_indices('\n')
+            .nth(line - 2)
+            .map_or(0, |(i, _)| i + 1),
+    };
+    Some((line_start + column.saturating_sub(1)).min(json.len()))
+}
+
+/// Up to 40 characters on each side of the offset
+fn snippet(json: &str, offset: usize) -> &str {
+    let mut offset = offset;
+    while !json.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let start = json[..offset]
+        .char_indices()
+        .rev()
+        .nth(39)
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+    let end = json[offset..]
+        .char_indices()
+        .nth(40)
+        .map(|(i, _)| offset + i)
+        .unwrap_or(json.len());
+    &json[start..end]
+}
+
+/// Report the length of the frame header with the location of a JSON error in the frame body.
+///
+/// The `byte_offset` stays relative to the body; `header_length` is added alongside it.
+pub(crate) fn in_frame(mut err: Error, header_len: usize) -> Error {
+    if let Some(Value::Object(data)) = &mut err.data {
+        if data.contains_key("byte_offset") {
+            data.insert("header_length".into(), header_len.into());
+        }
+    }
+    err
+}
+
+/// Parse the JSON into the provided type, failing if the object contains the member.
+pub(crate) fn parse_json_without<T>(json: &str, member: &str, reason: &str) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    reject_member(json, member, reason)?;
+    serde_json::from_str(json).map_err(|e| json_error(e, json))
+}
+
+/// Fail with an [`Error::INVALID_REQUEST`] if the JSON is an object containing the member.
+///
+/// The members are skipped without being built; invalid JSON is left to the parser. The error
+/// data locates the member as [`json_error`] does.
+pub(crate) fn reject_member(json: &str, member: &str, reason: &str) -> Result<(), Error> {
+    let find = |reject| {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        serde::de::DeserializeSeed::deserialize(HasMember { member, reject }, &mut deserializer)
+    };
+    match find(false) {
+        // The JSON is valid, so the second scan fails only on the member
+        Ok(true) => Err(Error {
+            code: Error::INVALID_REQUEST,
+            message: reason.to_string(),
+            data: find(true).err().and_then(|e| json_error(e, json).data),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Visitor of an object, checking if it contains the member.
+///
+/// With `reject`, the scan fails at the member instead, for the error to report its location.
+struct HasMember<'a> {
+    member: &'a str,
+    reject: bool,
+}
+
+impl<'de> serde::de::DeserializeSeed<'de> for HasMember<'_> {
+    type Value = bool;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<bool, D::Error>
     where
-        R: Read,
+        D: serde::Deserializer<'de>,
     {
-        let mut n = 0;
-        let length;
-        loop {
-            let line = reader
-                .by_ref()
-                .bytes()
-                .take_while(|b| match b {
-                    Ok(b) => *b != b'\n',
-                    Err(_) => true,
-                })
-                .collect::<io::Result<Vec<u8>>>()
-                .map_err(|e| Error {
-                    code: Error::INVALID_REQUEST,
-                    message: e.to_string(),
-                    data: None,
-                })?;
-            n += line.len() + 1;
+        deserializer.deserialize_map(self)
+    }
+}
 
-            let line = String::from_utf8(line).map_err(|e| Error {
-                code: Error::INVALID_REQUEST,
-                message: e.to_string(),
-                data: None,
-            })?;
-            let (key, value) = line.split_once(':').ok_or_else(|| Error {
-                code: Error::INVALID_REQUEST,
-                message: "the provided request header is invalid".to_string(),
-                data: Some(Value::String(line.to_string())),
-            })?;
-            if key.trim().to_lowercase() == "content-length" {
-                length = value.trim().parse::<usize>().map_err(|_| Error {
-                    code: Error::INVALID_REQUEST,
-                    message: "the provided request header is invalid".to_string(),
-                    data: Some(Value::String(value.to_string())),
-                })?;
-                break;
+impl<'de> serde::de::Visitor<'de> for HasMember<'_> {
+    type Value = bool;
+
+    fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str("an object")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<bool, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut found = false;
+        while let Some(key) = map.next_key::<alloc::borrow::Cow<'de, str>>()? {
+            if key == self.member {
+                if self.reject {
+                    return Err(serde::de::Error::custom(self.member));
+                }
+                found = true;
             }
+            map.next_value::<serde::de::IgnoredAny>()?;
         }
+        Ok(found)
+    }
+}
 
-        loop {
-            let line = reader
-                .by_ref()
-                .bytes()
-                .take_while(|b| match b {
-                    Ok(b) => *b != b'\n',
-                    Err(_) => true,
-                })
-                .collect::<io::Result<Vec<u8>>>()
-                .map_err(|e| Error {
-                    code: Error::INVALID_REQUEST,
+/// Deserialize positional params, returning the arity expected by the type along with a failure.
+///
+/// The arity is known once the type deserializes a tuple, and checked against the params.
+pub(crate) fn positional_params<T>(
+    params: &[Value],
+) -> Result<T, (serde_json::Error, Option<usize>)>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let expected = core::cell::Cell::new(None);
+    T::deserialize(Positional {
+        params,
+        expected: &expected,
+    })
+    .map_err(|e| (e, expected.get()))
+}
+
+/// Deserializer of positional params recording the arity of the tuple they are deserialized into.
+struct Positional<'a> {
+    params: &'a [Value],
+    expected: &'a core::cell::Cell<Option<usize>>,
+}
+
+impl Positional<'_> {
+    fn check_arity(&self, len: usize) -> Result<(), serde_json::Error> {
+        self.expected.set(Some(len));
+        match self.params.len() == len {
+            true => Ok(()),
+            false => Err(serde::de::Error::invalid_length(
+                self.params.len(),
+                &format!("{} positional params", len).as_str(),
+            )),
+        }
+    }
+}
+
+impl<'de> serde::Deserializer<'de> for Positional<'_> {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        visitor.visit_seq(serde::de::value::SeqDeserializer::new(
+            self.params.iter().cloned(),
+        ))
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.check_arity(len)?;
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: serde::de::Visitor<'de>,
+    {
+        self.check_arity(len)?;
+        self.deserialize_any(visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq map struct enum identifier ignored_any
+    }
+}
+
+/// Serialize the value to compact JSON
+pub(crate) fn to_json_compact<T>(value: &T) -> Result<String, Error>
+where
+    T: Serialize + ?Sized,
+{
+    serde_json::to_string(value).map_err(|e| Error {
+        code: Error::PARSE_ERROR,
+        message: e.to_string(),
+        data: None,
+    })
+}
+
+/// Serialize the value to indented JSON
+pub(crate) fn to_json_pretty<T>(value: &T) -> Result<String, Error>
+where
+    T: Serialize + ?Sized,
+{
+    serde_json::to_string_pretty(value).map_err(|e| Error {
+        code: Error::PARSE_ERROR,
+        message: e.to_string(),
+        data: None,
+    })
+}
+
+/// Serialize the value to JSON, escaping every non-ASCII character as `\uXXXX`.
+///
+/// Serialized JSON contains non-ASCII characters only inside strings, where the escaped form is
+/// equivalent.
+pub(crate) fn to_json_ascii<T>(value: &T) -> Result<String, Error>
+where
+    T: Serialize + ?Sized,
+{
+    let json = to_json_compact(value)?;
+
+    let mut escaped = String::with_capacity(json.len());
+    for c in json.chars() {
+        if c.is_ascii() {
+            escaped.push(c);
+        } else {
+            for unit in c.encode_utf16(&mut [0; 2]) {
+                write!(escaped, "\\u{:04x}", unit).map_err(|e| Error {
+                    code: Error::PARSE_ERROR,
                     message: e.to_string(),
                     data: None,
                 })?;
-            n += line.len() + 1;
+            }
+        }
+    }
 
-            let line = String::from_utf8(line).map_err(|e| Error {
-                code: Error::INVALID_REQUEST,
-                message: e.to_string(),
-                data: None,
-            })?;
+    Ok(escaped)
+}
 
-            if line.trim().is_empty() {
-                break;
+/// Serialize the value to canonical JSON.
+///
+/// Object keys are sorted by their UTF-8 bytes at every level, and no whitespace is emitted.
+/// Strings are escaped as by `serde_json`: only `"`, `\\`, and control characters are escaped.
+/// Numbers are normalized by [`write_canonical_number`].
+pub(crate) fn to_json_canonical<T>(value: &T) -> Result<String, Error>
+where
+    T: Serialize + ?Sized,
+{
+    let value = serde_json::to_value(value).map_err(|e| Error {
+        code: Error::PARSE_ERROR,
+        message: e.to_string(),
+        data: None,
+    })?;
+    let mut json = String::new();
+    write_canonical(&value, &mut json);
+    Ok(json)
+}
+
+fn write_canonical(value: &Value, json: &mut String) {
+    match value {
+        Value::Array(values) => {
+            json.push('[');
+            for (i, v) in values.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                write_canonical(v, json);
+            }
+            json.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: alloc::vec::Vec<_> = map.iter().collect();
+            entries.sort_unstable_by(|(a, _), (b, _)| a.as_bytes().cmp(b.as_bytes()));
+            json.push('{');
+            for (i, (k, v)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                write_canonical(&Value::String(k.clone()), json);
+                json.push(':');
+                write_canonical(v, json);
             }
+            json.push('}');
         }
+        Value::Number(number) => write_canonical_number(&number.to_string(), json),
+        // Strings, booleans and null have a single serde_json representation
+        scalar => json.push_str(&scalar.to_string()),
+    }
+}
 
-        let mut buffer = vec![0u8; length];
-        n += buffer.len();
-        reader.read_exact(&mut buffer).map_err(|e| Error {
-            code: Error::INVALID_REQUEST,
-            message: e.to_string(),
-            data: None,
-        })?;
+/// Write the decimal number in the ECMAScript form of its value, as in RFC 8785.
+///
+/// Numbers of equal value written differently, such as `1`, `1.0` and `10e-1`, or `1.5` and
+/// `1.50` with the feature `arbitrary-precision`, are written identically. Integral values are
+/// written in full while at most 20 zeros follow their significant digits, so large integers
+/// keep their digits; other values take the fewest digits, with an exponent outside `1e-6..1e21`.
+fn write_canonical_number(number: &str, json: &mut String) {
+    let (negative, unsigned) = match number.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, number),
+    };
+    let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+        Some((mantissa, exponent)) => match exponent.parse::<i64>() {
+            Ok(exponent) => (mantissa, exponent),
+            // An exponent beyond i64 is left as is
+            Err(_) => return json.push_str(number),
+        },
+        None => (unsigned, 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
 
-        let contents = String::from_utf8(buffer).map_err(|e| Error {
-            code: Error::PARSE_ERROR,
-            message: e.to_string(),
-            data: None,
-        })?;
+    // The value is `0.{digits} * 10^point`
+    let digits = [int, frac].concat();
+    let leading = digits.len() - digits.trim_start_matches('0').len();
+    let digits = digits.trim_matches('0');
+    if digits.is_empty() {
+        return json.push('0');
+    }
+    let point = int.len() as i64 - leading as i64 + exponent;
+    let k = digits.len() as i64;
+
+    if negative {
+        json.push('-');
+    }
+    match point {
+        n if k <= n && n - k <= 20 => {
+            json.push_str(digits);
+            json.extend(core::iter::repeat_n('0', (n - k) as usize));
+        }
+        n if 0 < n && n <= 21 => {
+            let (int, frac) = digits.split_at(n as usize);
+            let _ = write!(json, "{}.{}", int, frac);
+        }
+        n if -6 < n && n <= 0 => {
+            json.push_str("0.");
+            json.extend(core::iter::repeat_n('0', -n as usize));
+            json.push_str(digits);
+        }
+        n => {
+            let (first, rest) = digits.split_at(1);
+            json.push_str(first);
+            if !rest.is_empty() {
+                json.push('.');
+                json.push_str(rest);
+            }
+            let _ = write!(json, "e{}{}", if n > 0 { "+" } else { "-" }, (n - 1).abs());
+        }
+    }
+}
+
+#[test]
+fn test_canonical_numbers() {
+    let canonical = |json: &str| {
+        let value: Value = serde_json::from_str(json).unwrap();
+        to_json_canonical(&value).unwrap()
+    };
 
-        Ok((n, contents))
+    for (a, b) in [
+        ("1", "1.0"),
+        ("1.5", "1.50"),
+        ("1.5", "15e-1"),
+        ("0", "-0.0"),
+        ("100", "1e2"),
+        ("-12345.6", "-123.456e2"),
+        ("0.001", "1E-3"),
+    ] {
+        assert_eq!(canonical(a), canonical(b), "{} {}", a, b);
     }
 
-    #[test]
-    fn test_get_buffer_from_reader() {
-        let bytes = "Foo: HTTP/1.1\r\nContent-Length: 5\r\n\r\nHelloEXTRA";
-        let (n, contents) = get_content_from_reader(bytes.as_bytes()).unwrap();
-        assert_eq!(n, 41);
-        assert_eq!(contents.as_bytes(), b"Hello");
+    let json = "[1.0,1.50,-0.0,1e20,1e21,0.000001,1e-7,-2.5e-9,18446744073709551615]";
+    assert_eq!(
+        canonical(json),
+        "[1,1.5,0,100000000000000000000,1e+21,0.000001,1e-7,-2.5e-9,18446744073709551615]"
+    );
+}
+
+#[test]
+fn test_get_content_length() {
+    let bytes = "Foo: HTTP/1.1\r\nContent-Length: 5\r\n\r\nHelloEXTRA";
+    let (message, remainder) = get_content_length(bytes).unwrap();
+    assert_eq!(message.as_bytes(), b"Hello");
+    assert_eq!(remainder.as_bytes(), b"EXTRA");
+}
+
+#[test]
+fn test_get_content_length_bytes() {
+    let bytes = b"Foo: HTTP/1.1\r\nContent-Length: 5\r\n\r\nHelloEXTRA";
+    let (message, remainder) = get_content_length_bytes(bytes).unwrap();
+    assert_eq!(message, b"Hello");
+    assert_eq!(remainder, b"EXTRA");
+
+    let bytes = b"Content-Length: 2\r\n\r\n\xff\xfe";
+    let (message, remainder) = get_content_length_bytes(bytes).unwrap();
+    assert_eq!(message, b"\xff\xfe");
+    assert!(remainder.is_empty());
+
+    let bytes = b"Content-Length: 5\r\ncontent-length: 10\r\n\r\nHelloEXTRA";
+    let err = get_content_length_bytes(bytes).unwrap_err();
+    assert_eq!(err.data, Some(Value::from("content-length: 10\r")));
+
+    for bytes in [&b"Content-Length: 9\r\n\r\nHello"[..], b"Foo\r\n\r\n", b""] {
+        let err = get_content_length_bytes(bytes).unwrap_err();
+        assert_eq!(err.code, Error::INVALID_REQUEST);
+    }
+}
+
+#[test]
+fn test_get_content_length_char_boundary() {
+    let err = get_content_length("Content-Length: 1\r\n\r\n\u{e9}").unwrap_err();
+    assert_eq!(err.code, Error::PARSE_ERROR);
+
+    let (message, remainder) = get_content_length("Content-Length: 2\r\n\r\n\u{e9}").unwrap();
+    assert_eq!(message, "\u{e9}");
+    assert!(remainder.is_empty());
+}
+
+#[test]
+fn test_get_content_length_duplicate() {
+    let bytes = "Content-Length: 5\r\ncontent-length: 10\r\n\r\nHelloEXTRA";
+    let err = get_content_length(bytes).unwrap_err();
+    assert_eq!(err.code, Error::INVALID_REQUEST);
+    assert_eq!(err.data, Some(Value::from("content-length: 10\r")));
+}
+
+#[test]
+fn test_count_messages() {
+    use super::{Notification, Request};
+
+    let mut s = Request::new("foo").with_id(1).to_string();
+    s.push_str(&Notification::new("bar").to_string());
+    assert_eq!(count_messages(&s), 2);
+    assert!(has_complete_message(&s));
+
+    let full = s.len();
+    s.push_str(&Request::new("baz").with_id(2).to_string());
+    assert_eq!(count_messages(&s[..s.len() - 1]), 2);
+    assert_eq!(count_messages(&s), 3);
+    assert_eq!(count_messages(&s[..full + 10]), 2);
+
+    assert_eq!(count_messages(""), 0);
+    assert!(!has_complete_message("Content-Length: 2\r\n\r\n{"));
+    assert!(has_complete_message("Content-Length: 2\r\n\r\n{}"));
+    assert!(!has_complete_message("Content-Length: x\r\n\r\n{}"));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_write_message_to_string() {
+    use super::{Message, Notification, Request};
+
+    for message in [
+        Message::from(Request::new("foo").with_id(1)),
+        Message::from(Notification::new("bar").with_params_value(serde_json::json!(["ü"]))),
+    ] {
+        assert_eq!(
+            write_message_to_string(&message).unwrap(),
+            message.to_string()
+        );
+    }
+}
+
+#[test]
+fn test_frame() {
+    use super::Request;
+
+    let request = Request::new("\u{e9}t\u{e9}").with_id(1);
+    let body = serde_json::to_string(&request).unwrap();
+    assert_eq!(frame(&body), request.to_string());
+    assert!(frame(&body).starts_with(&format!("Content-Length: {}\r\n\r\n", body.len())));
+}
+
+#[test]
+fn test_json_error_classification() {
+    use super::{Message, Notification, Request, Response};
+
+    let truncated = r#"{"jsonrpc":"2.0","id":1,"method":"fo"#;
+    let number = "42";
+    let no_method = r#"{"jsonrpc":"2.0","id":1,"params":[]}"#;
+
+    for (json, code) in [
+        (truncated, Error::PARSE_ERROR),
+        ("{x}", Error::PARSE_ERROR),
+        (number, Error::INVALID_REQUEST),
+        (no_method, Error::INVALID_REQUEST),
+    ] {
+        let errors = [
+            Request::parse_json(json).unwrap_err(),
+            Notification::parse_json(json).unwrap_err(),
+        ];
+        for err in errors {
+            assert_eq!(err.code, code, "{}", json);
+            assert_eq!(err.data.unwrap()["snippet"], json);
+        }
+    }
+
+    let data = Notification::parse_json(no_method)
+        .unwrap_err()
+        .data
+        .unwrap();
+    let key = no_method.find(r#""id""#).unwrap();
+    assert_eq!(data["line"], 1);
+    assert_eq!(data["byte_offset"], key + r#""id""#.len() - 1);
+
+    for json in [truncated, "{x}"] {
+        assert_eq!(
+            Message::parse_json(json).unwrap_err().code,
+            Error::PARSE_ERROR
+        );
+        assert_eq!(
+            Response::parse_json(json).unwrap_err().code,
+            Error::PARSE_ERROR
+        );
+    }
+    assert_eq!(
+        Message::parse_json(number).unwrap_err().code,
+        Error::INVALID_REQUEST
+    );
+    assert_eq!(
+        Response::parse_json(number).unwrap_err().code,
+        Error::INVALID_REQUEST
+    );
+}
+
+#[test]
+fn test_json_error_location() {
+    use super::Request;
+
+    let json = r#"{"jsonrpc":"2.0","id":1 "method":"foo"}"#;
+    let data = Request::parse_json(json).unwrap_err().data.unwrap();
+    let offset = json.find(r#" "method""#).unwrap() + 1;
+    assert_eq!(data["line"], 1);
+    assert_eq!(data["column"], offset + 1);
+    assert_eq!(data["byte_offset"], offset);
+    assert_eq!(data["snippet"], json);
+
+    let json = "{\n  \"jsonrpc\": \"2.0\",\n  \"id\": 1,,\n  \"method\": \"foo\"\n}";
+    let data = Request::parse_json(json).unwrap_err().data.unwrap();
+    assert_eq!(data["line"], 3);
+    assert_eq!(data["byte_offset"], json.find(",,").unwrap() + 1);
+
+    let padding = "\u{e9}".repeat(100);
+    let json = alloc::format!(r#"{{"id":1,"method":"{}","params":[1}}"#, padding);
+    let data = Request::parse_json(&json).unwrap_err().data.unwrap();
+    let offset = json.find("1}").unwrap() + 1;
+    assert_eq!(data["byte_offset"], offset);
+    let snippet = data["snippet"].as_str().unwrap();
+    assert_eq!(snippet.chars().count(), 41);
+    assert!(json[..=offset].ends_with(snippet));
+
+    let data = Request::parse_json("{").unwrap_err().data.unwrap();
+    assert_eq!(data["byte_offset"], 1);
+    assert_eq!(data["snippet"], "{");
+
+    let json = r#"{"jsonrpc":"2.0","id":1 "method":"foo"}"#;
+    let framed = frame(json);
+    let data = Request::parse(&framed).unwrap_err().data.unwrap();
+    assert_eq!(data["byte_offset"], json.find(r#" "method""#).unwrap() + 1);
+    assert_eq!(data["header_length"], framed.len() - json.len());
+}
+
+#[test]
+fn test_check_params() {
+    use super::{Notification, Request};
+    use serde_json::json;
+
+    for params in [json!([1, "a"]), json!({"a": 1}), json!([]), json!({})] {
+        let request = Request::new("foo").with_params(&params).unwrap();
+        assert_eq!(request.params, Some(params.clone()));
+        let notification = Notification::new("foo").with_params(&params).unwrap();
+        assert_eq!(notification.params, Some(params));
+    }
+
+    let request = Request::new("foo").with_params(()).unwrap();
+    assert!(request.params.is_none());
+    let notification = Notification::new("foo")
+        .try_with_params_value(Value::Null)
+        .unwrap();
+    assert!(notification.params.is_none());
+    assert!(!notification.to_string().contains("params"));
+
+    for (params, kind) in [
+        (json!(42), "a number"),
+        (json!("foo"), "a string"),
+        (json!(true), "a boolean"),
+    ] {
+        let err = Request::new("foo").with_params(&params).unwrap_err();
+        assert_eq!(err.code, Error::INVALID_PARAMS);
+        assert!(err.message.ends_with(kind));
+        assert_eq!(err.data, Some(params.clone()));
+        let err = Notification::new("foo")
+            .try_with_params_value(params)
+            .unwrap_err();
+        assert_eq!(err.code, Error::INVALID_PARAMS);
+    }
+}
+
+#[test]
+fn test_write_framed() {
+    use super::{Notification, Request};
+
+    let request = Request::new("\u{e9}\"\n\u{1f600}")
+        .with_id(1)
+        .with_params_value(serde_json::json!({"k\u{e9}y": ["\u{7f}\\", 1.5, null]}));
+    let body = serde_json::to_string(&request).unwrap();
+    assert_eq!(request.to_string(), frame(&body));
+
+    let notification = Notification::new("foo");
+    let (id, display) = request.prepare_ref();
+    assert_eq!(id, &request.id);
+    assert_eq!(
+        format!("{}{}", display, notification),
+        request.prepare().1 + &notification.to_string()
+    );
+}
+
+#[test]
+fn test_to_json_ascii() {
+    let json = to_json_ascii("a\u{e9}\u{1f600}").unwrap();
+    assert_eq!(json, r#""a\u00e9\ud83d\ude00""#);
+    assert_eq!(
+        serde_json::from_str::<String>(&json).unwrap(),
+        "a\u{e9}\u{1f600}"
+    );
+}
+
+#[cfg(feature = "std")]
+pub use io::{
+    get_content_from_reader, get_content_from_reader_into, get_content_from_reader_limited,
+    pipe_messages, MAX_DECOMPRESSED_LEN,
+};
+
+#[cfg(feature = "compression")]
+pub use gzip::frame_gzip;
+
+#[cfg(feature = "std")]
+pub(crate) use io::{
+    io_error, read_body, read_error, read_frame, read_frame_into, read_header,
+    try_get_content_from_reader, write_vectored,
+};
+
+#[cfg(feature = "std")]
+mod io {
+    use super::*;
+    use std::io::{self, prelude::*, Write};
+
+    /// Default cap of the decoded body of a frame read with a `Content-Encoding` or a chunked
+    /// `Transfer-Encoding`
+    pub const MAX_DECOMPRESSED_LEN: usize = 64 * 1024 * 1024;
+
+    /// Read the contents length of the argument and fill a buffer with its size.
+    ///
+    /// Return the amount of read bytes, and the extracted bytes buffer.
+    pub fn get_content_from_reader<R>(reader: R) -> Result<(usize, String), Error>
+    where
+        R: Read,
+    {
+        read_frame(reader).map(|(n, _, contents)| (n, contents))
+    }
+
+    /// Read a frame as [`get_content_from_reader`], also returning the length of its header.
+    ///
+    /// The header length locates the errors of the body in the frame; the body may be longer
+    /// than the bytes read once decoded.
+    pub(crate) fn read_frame<R>(reader: R) -> Result<(usize, usize, String), Error>
+    where
+        R: Read,
+    {
+        let mut buf = Vec::new();
+        read_content(reader, &mut buf, MAX_DECOMPRESSED_LEN)
+            .and_then(|(n, header_len)| {
+                String::from_utf8(buf)
+                    .map(|contents| (n, header_len, contents))
+                    .map_err(utf8_error)
+            })
+            .inspect_err(|e| telemetry::parse_failed(e, ""))
+    }
+
+    /// Read a frame as [`get_content_from_reader`], reusing the provided buffer.
+    ///
+    /// The buffer is cleared, used as scratch space for the header lines, and left holding the
+    /// body. Return the amount of read bytes, and the body.
+    ///
+    /// A frame with a chunked `Transfer-Encoding` instead of a `Content-Length` has its chunks
+    /// assembled, and a body with a `Content-Encoding` is decoded, each up to
+    /// [`MAX_DECOMPRESSED_LEN`] bytes. The amount of read bytes is the size on the wire.
+    pub fn get_content_from_reader_into<R>(
+        reader: R,
+        buf: &mut Vec<u8>,
+    ) -> Result<(usize, &str), Error>
+    where
+        R: Read,
+    {
+        read_frame_into(reader, buf, MAX_DECOMPRESSED_LEN).map(|(n, _, contents)| (n, contents))
+    }
+
+    /// Read a frame as [`get_content_from_reader_into`], capping its decoded body.
+    ///
+    /// Fails with [`Error::INVALID_REQUEST`] if the assembled chunks or the decompressed body
+    /// exceed `max_decoded_len` bytes.
+    pub fn get_content_from_reader_limited<R>(
+        reader: R,
+        buf: &mut Vec<u8>,
+        max_decoded_len: usize,
+    ) -> Result<(usize, &str), Error>
+    where
+        R: Read,
+    {
+        read_frame_into(reader, buf, max_decoded_len).map(|(n, _, contents)| (n, contents))
+    }
+
+    /// Read a frame as [`get_content_from_reader_limited`], also returning the length of its
+    /// header.
+    pub(crate) fn read_frame_into<R>(
+        reader: R,
+        buf: &mut Vec<u8>,
+        max_decoded_len: usize,
+    ) -> Result<(usize, usize, &str), Error>
+    where
+        R: Read,
+    {
+        read_content(reader, buf, max_decoded_len)
+            .and_then(|(n, header_len)| {
+                core::str::from_utf8(buf)
+                    .map(|contents| (n, header_len, contents))
+                    .map_err(utf8_error)
+            })
+            .inspect_err(|e| telemetry::parse_failed(e, ""))
+    }
+
+    fn utf8_error<E>(e: E) -> Error
+    where
+        E: core::fmt::Display,
+    {
+        Error {
+            code: Error::PARSE_ERROR,
+            message: e.to_string(),
+            data: None,
+        }
+    }
+
+    fn read_content<R>(
+        mut reader: R,
+        buf: &mut Vec<u8>,
+        max_decoded_len: usize,
+    ) -> Result<(usize, usize), Error>
+    where
+        R: Read,
+    {
+        let header = read_header(&mut reader, buf)?;
+        let header_len = header.len;
+        read_body(reader, buf, header, max_decoded_len).map(|n| (n, header_len))
+    }
+
+    /// Header of a frame, read by [`read_header`]
+    pub(crate) struct Header {
+        /// Length of the header
+        pub(crate) len: usize,
+        length: Option<usize>,
+        encoding: Option<String>,
+    }
+
+    impl Header {
+        /// Declared length of the body, if it is neither chunked nor encoded
+        pub(crate) fn plain_length(&self) -> Option<usize> {
+            self.length.filter(|_| self.encoding.is_none())
+        }
+    }
+
+    /// Read the header of a frame, using the buffer as scratch space for its lines.
+    pub(crate) fn read_header<R>(mut reader: R, buf: &mut Vec<u8>) -> Result<Header, Error>
+    where
+        R: Read,
+    {
+        let mut n = 0;
+        let mut encoding = None;
+        let mut chunked = false;
+        let length = loop {
+            n += read_line(&mut reader, buf)?;
+            let line = header_line(buf)?;
+            if chunked && line.trim().is_empty() {
+                break None;
+            }
+            let (key, value) = line.split_once(':').ok_or_else(|| Error {
+                code: Error::INVALID_REQUEST,
+                message: "the provided request header is invalid".to_string(),
+                data: Some(Value::String(line.to_string())),
+            })?;
+            if key.trim().eq_ignore_ascii_case("content-encoding") {
+                encoding = Some(value.trim().to_string());
+            }
+            if key.trim().eq_ignore_ascii_case("transfer-encoding") {
+                chunked = is_chunked(value)?;
+            }
+            if key.trim().to_lowercase() == "content-length" {
+                if chunked {
+                    return Err(chunked_with_content_length());
+                }
+                break Some(value.trim().parse::<usize>().map_err(|_| Error {
+                    code: Error::INVALID_REQUEST,
+                    message: "the provided request header is invalid".to_string(),
+                    data: Some(Value::String(value.to_string())),
+                })?);
+            }
+        };
+
+        while length.is_some() {
+            n += read_line(&mut reader, buf)?;
+            let line = header_line(buf)?;
+            if line.trim().is_empty() {
+                break;
+            }
+            if is_content_length(line) {
+                return Err(duplicate_content_length(line));
+            }
+            if let Some((key, value)) = line.split_once(':') {
+                if key.trim().eq_ignore_ascii_case("content-encoding") {
+                    encoding = Some(value.trim().to_string());
+                }
+                if key.trim().eq_ignore_ascii_case("transfer-encoding") {
+                    return Err(chunked_with_content_length());
+                }
+            }
+        }
+
+        Ok(Header {
+            len: n,
+            length,
+            encoding,
+        })
+    }
+
+    /// Read the body of the frame with the header into the buffer, decoding it.
+    ///
+    /// Returns the number of bytes read for the frame, header included.
+    pub(crate) fn read_body<R>(
+        mut reader: R,
+        buf: &mut Vec<u8>,
+        header: Header,
+        max_decoded_len: usize,
+    ) -> Result<usize, Error>
+    where
+        R: Read,
+    {
+        let mut n = header.len;
+        match header.length {
+            Some(length) => {
+                // The buffer grows with the body read, not with the announced length
+                buf.clear();
+                (&mut reader)
+                    .take(length as u64)
+                    .read_to_end(buf)
+                    .and_then(|read| match read < length {
+                        true => Err(io::ErrorKind::UnexpectedEof.into()),
+                        false => Ok(()),
+                    })
+                    .map_err(read_error)?;
+                n += length;
+            }
+            None => n += read_chunked(&mut reader, buf, max_decoded_len)?,
+        }
+
+        if let Some(encoding) = header.encoding {
+            if let Some(decoded) = decode_content(&encoding, buf, max_decoded_len)? {
+                *buf = decoded;
+            }
+        }
+
+        Ok(n)
+    }
+
+    /// Map an error of the reader of a frame.
+    pub(crate) fn read_error(e: io::Error) -> Error {
+        Error {
+            code: Error::INVALID_REQUEST,
+            message: e.to_string(),
+            data: None,
+        }
+    }
+
+    /// Accept a `Transfer-Encoding` header only if its value is `chunked`.
+    fn is_chunked(value: &str) -> Result<bool, Error> {
+        match value.trim() {
+            v if v.eq_ignore_ascii_case("chunked") => Ok(true),
+            v => Err(Error {
+                code: Error::INVALID_REQUEST,
+                message: format!("the transfer encoding {} is not supported", v),
+                data: Some(Value::String(v.to_string())),
+            }),
+        }
+    }
+
+    fn chunked_with_content_length() -> Error {
+        Error {
+            code: Error::INVALID_REQUEST,
+            message: "the provided request header has both a content-length and a chunked \
+                transfer-encoding"
+                .to_string(),
+            data: None,
+        }
+    }
+
+    /// Read a chunked body into the buffer, returning the number of consumed bytes.
+    ///
+    /// Chunk extensions and trailers are skipped; the body is capped to `max_len` bytes.
+    fn read_chunked<R>(reader: &mut R, buf: &mut Vec<u8>, max_len: usize) -> Result<usize, Error>
+    where
+        R: Read,
+    {
+        let invalid = |message: String, line: &str| Error {
+            code: Error::INVALID_REQUEST,
+            message,
+            data: Some(Value::String(line.to_string())),
+        };
+
+        let mut n = 0;
+        let mut line = Vec::new();
+        buf.clear();
+        loop {
+            n += read_chunk_line(reader, &mut line)?;
+            let size_line = header_line(&line)?;
+            let size = size_line.split(';').next().unwrap_or_default().trim();
+            let size = Some(size)
+                .filter(|s| !s.is_empty() && s.bytes().all(|b| b.is_ascii_hexdigit()))
+                .and_then(|s| usize::from_str_radix(s, 16).ok())
+                .ok_or_else(|| invalid("the chunk size is invalid".to_string(), size_line))?;
+            if size == 0 {
+                break;
+            }
+            if size > max_len - buf.len() {
+                return Err(Error {
+                    code: Error::INVALID_REQUEST,
+                    message: format!("the chunked body exceeds the limit of {} bytes", max_len),
+                    data: Some(Value::from(max_len)),
+                });
+            }
+
+            let start = buf.len();
+            buf.resize(start + size, 0);
+            reader.read_exact(&mut buf[start..]).map_err(read_error)?;
+            n += size;
+
+            n += read_chunk_line(reader, &mut line)?;
+            let end = header_line(&line)?;
+            if !end.trim().is_empty() {
+                return Err(invalid(
+                    "the chunk is not terminated by a line break".to_string(),
+                    end,
+                ));
+            }
+        }
+
+        loop {
+            n += read_chunk_line(reader, &mut line)?;
+            if header_line(&line)?.trim().is_empty() {
+                break;
+            }
+        }
+
+        Ok(n)
+    }
+
+    /// Decode the body of a frame according to its `Content-Encoding`, returning `None` if the
+    /// body is not encoded.
+    #[cfg_attr(not(feature = "compression"), allow(unused_variables))]
+    fn decode_content(
+        encoding: &str,
+        body: &[u8],
+        max_len: usize,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        match encoding {
+            e if e.eq_ignore_ascii_case("identity") => Ok(None),
+            #[cfg(feature = "compression")]
+            e if e.eq_ignore_ascii_case("gzip") => super::gzip::decompress(body, max_len).map(Some),
+            _ => Err(Error {
+                code: Error::INVALID_REQUEST,
+                message: format!("the content encoding {} is not supported", encoding),
+                data: Some(Value::String(encoding.to_string())),
+            }),
+        }
+    }
+
+    /// Read a line into the buffer, without its `\n`, returning the number of consumed bytes.
+    // The header is consumed byte by byte so no data past the frame is taken from the reader.
+    #[allow(clippy::unbuffered_bytes)]
+    fn read_line<R>(reader: &mut R, line: &mut Vec<u8>) -> Result<usize, Error>
+    where
+        R: Read,
+    {
+        line.clear();
+        for b in reader.bytes() {
+            let b = b.map_err(|e| Error {
+                code: Error::INVALID_REQUEST,
+                message: e.to_string(),
+                data: None,
+            })?;
+            if b == b'\n' {
+                break;
+            }
+            line.push(b);
+        }
+        Ok(line.len() + 1)
+    }
+
+    /// Read a line of a chunked body, failing if the stream ends before its `\n`.
+    #[allow(clippy::unbuffered_bytes)]
+    fn read_chunk_line<R>(reader: &mut R, line: &mut Vec<u8>) -> Result<usize, Error>
+    where
+        R: Read,
+    {
+        line.clear();
+        for b in reader.bytes() {
+            let b = b.map_err(read_error)?;
+            if b == b'\n' {
+                return Ok(line.len() + 1);
+            }
+            line.push(b);
+        }
+        Err(read_error(io::ErrorKind::UnexpectedEof.into()))
+    }
+
+    fn header_line(line: &[u8]) -> Result<&str, Error> {
+        core::str::from_utf8(line).map_err(|e| Error {
+            code: Error::INVALID_REQUEST,
+            message: e.to_string(),
+            data: None,
+        })
+    }
+
+    /// Sink counting the bytes written to it.
+    #[derive(Default)]
+    pub struct Counter(pub usize);
+
+    impl io::Write for Counter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0 += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Adapter writing the output of a JSON serializer to a formatter.
+    ///
+    /// The serializer writes whole characters in each call, so every slice is valid UTF-8.
+    pub struct FmtWriter<'a, W: ?Sized>(pub &'a mut W);
+
+    impl<W> io::Write for FmtWriter<'_, W>
+    where
+        W: fmt::Write + ?Sized,
+    {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let s = core::str::from_utf8(buf)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            self.0.write_str(s).map_err(io::Error::other)?;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Serialize the value and write its frame as two slices, header and body, with a single
+    /// vectored write if the writer accepts it.
+    ///
+    /// The body is serialized into the buffer, cleared first. Partial writes are retried until
+    /// the frame is complete, so a frame written behind a lock is never interleaved with another
+    /// one. Returns the size of the frame.
+    pub(crate) fn write_vectored<T, W>(
+        value: &T,
+        mut writer: W,
+        body: &mut Vec<u8>,
+    ) -> Result<usize, Error>
+    where
+        T: Serialize,
+        W: Write,
+    {
+        body.clear();
+        serde_json::to_writer(&mut *body, value).map_err(|e| Error {
+            code: Error::PARSE_ERROR,
+            message: e.to_string(),
+            data: None,
+        })?;
+        // The header with the 20 digits of the largest length fits in 40 bytes
+        let mut header = [0u8; 40];
+        let mut cursor = &mut header[..];
+        write!(cursor, "Content-Length: {}\r\n\r\n", body.len())
+            .map_err(|e| write_error(e, value))?;
+        let header_len = 40 - cursor.len();
+
+        let mut slices = [
+            io::IoSlice::new(&header[..header_len]),
+            io::IoSlice::new(body),
+        ];
+        let mut pending = &mut slices[..];
+        while !pending.is_empty() {
+            match writer.write_vectored(pending) {
+                Ok(0) => return Err(write_error(io::ErrorKind::WriteZero.into(), value)),
+                Ok(n) => io::IoSlice::advance_slices(&mut pending, n),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => (),
+                Err(e) => return Err(write_error(e, value)),
+            }
+        }
+        writer.flush().map_err(|e| write_error(e, value))?;
+        Ok(header_len + body.len())
+    }
+
+    fn write_error<T>(e: io::Error, value: &T) -> Error
+    where
+        T: Serialize,
+    {
+        Error {
+            code: Error::PARSE_ERROR,
+            message: e.to_string(),
+            data: serde_json::to_value(value).ok(),
+        }
+    }
+
+    /// Map a transport failure into an internal error, keeping the error kind as data.
+    pub fn io_error(e: io::Error) -> Error {
+        Error {
+            code: Error::INTERNAL_ERROR,
+            message: e.to_string(),
+            data: Some(Value::String(format!("{:?}", e.kind()))),
+        }
+    }
+
+    /// Read messages until EOF, writing each one as transformed by the closure.
+    ///
+    /// Every message is flushed once written. Stops at the first parse or write failure.
+    pub fn pipe_messages<R, W, F>(reader: R, mut writer: W, mut transform: F) -> Result<(), Error>
+    where
+        R: Read,
+        W: io::Write,
+        F: FnMut(crate::Message) -> crate::Message,
+    {
+        for message in crate::MessageIterator::new(reader) {
+            let message = transform(message?);
+            writer
+                .write_all(message.to_string().as_bytes())
+                .and_then(|_| writer.flush())
+                .map_err(|e| Error {
+                    code: Error::PARSE_ERROR,
+                    message: e.to_string(),
+                    data: None,
+                })?;
+        }
+        Ok(())
+    }
+
+    /// Read the next frame of the reader, returning `None` if it is at EOF before the first byte.
+    pub fn try_get_content_from_reader<R>(
+        mut reader: R,
+    ) -> Result<Option<(usize, usize, String)>, Error>
+    where
+        R: Read,
+    {
+        let mut first = [0u8; 1];
+        loop {
+            match reader.read(&mut first) {
+                Ok(0) => return Ok(None),
+                Ok(_) => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    return Err(Error {
+                        code: Error::INVALID_REQUEST,
+                        message: e.to_string(),
+                        data: None,
+                    })
+                }
+            }
+        }
+
+        read_frame(first.as_slice().chain(reader)).map(Some)
+    }
+
+    #[test]
+    fn test_get_content_from_reader_huge_length() {
+        for length in [usize::MAX.to_string(), (1usize << 40).to_string()] {
+            let bytes = alloc::format!("Content-Length: {}\r\n\r\n{{}}", length);
+            let err = get_content_from_reader(bytes.as_bytes()).unwrap_err();
+            assert_eq!(err.code, Error::INVALID_REQUEST);
+        }
+    }
+
+    #[test]
+    fn test_write_vectored() {
+        use crate::{test_helpers::Trickle, Message, Notification, Request};
+
+        let request = Request::new("foo").with_id(1);
+        let mut writer = Trickle::new(7);
+        let n = request.try_to_writer_vectored(&mut writer).unwrap();
+        assert_eq!(n, request.to_string().len());
+        assert_eq!(writer.written(), request.to_string().as_bytes());
+        assert_eq!(writer.slices_per_write()[0], 2);
+        assert!(writer.slices_per_write().iter().all(|&c| c <= 2));
+
+        // The body buffer is reused across writes
+        let mut buf = Vec::new();
+        let mut writer = Trickle::new(7);
+        for message in [
+            Message::from(request.clone()),
+            Message::from(Notification::new("a")),
+        ] {
+            message
+                .try_to_writer_vectored_buffered(&mut writer, &mut buf)
+                .unwrap();
+            assert_eq!(buf, message.to_compact_json().unwrap().as_bytes());
+        }
+        let expected = request.to_string() + &Notification::new("a").to_string();
+        assert_eq!(writer.written(), expected.as_bytes());
+
+        // Writers without vectored support take one slice per call
+        let notification = Notification::new("bar");
+        let mut written = Vec::new();
+        let n = Message::from(notification.clone())
+            .try_to_writer_vectored(&mut written)
+            .unwrap();
+        assert_eq!(n, written.len());
+        assert_eq!(written, notification.to_string().as_bytes());
+
+        let err = request
+            .try_to_writer_vectored(&mut [0u8; 8][..])
+            .unwrap_err();
+        assert_eq!(err.code, Error::PARSE_ERROR);
+    }
+
+    #[test]
+    fn test_get_buffer_from_reader() {
+        let bytes = "Foo: HTTP/1.1\r\nContent-Length: 5\r\n\r\nHelloEXTRA";
+        let (n, contents) = get_content_from_reader(bytes.as_bytes()).unwrap();
+        assert_eq!(n, 41);
+        assert_eq!(contents.as_bytes(), b"Hello");
+    }
+
+    #[test]
+    fn test_get_content_from_reader_into_matches() {
+        let inputs = [
+            "Foo: HTTP/1.1\r\nContent-Length: 5\r\n\r\nHelloEXTRA",
+            "Content-Length: 5\r\nContent-Length: 10\r\n\r\nHelloEXTRA",
+            "Content-Length: 5\r\n\r\nHello",
+            "Content-Length: 10\r\n\r\nHello",
+            "Content-Length: x\r\n\r\nHello",
+            "Foo\r\n\r\n",
+            "",
+        ];
+
+        let mut buf = b"stale".to_vec();
+        for input in inputs {
+            let expected = get_content_from_reader(input.as_bytes());
+            let actual = get_content_from_reader_into(input.as_bytes(), &mut buf);
+            match (expected, actual) {
+                (Ok((n, contents)), Ok((m, body))) => {
+                    assert_eq!(n, m);
+                    assert_eq!(contents, body);
+                }
+                (Err(e), Err(f)) => {
+                    assert_eq!((e.code, e.message, e.data), (f.code, f.message, f.data))
+                }
+                (expected, actual) => panic!("{:?} != {:?}", expected, actual),
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_buffer_from_reader_duplicate() {
+        let bytes = "Content-Length: 5\r\nContent-Length: 10\r\n\r\nHelloEXTRA";
+        let err = get_content_from_reader(bytes.as_bytes()).unwrap_err();
+        assert_eq!(err.code, Error::INVALID_REQUEST);
+    }
+
+    #[test]
+    fn test_json_error_location_in_reader() {
+        use crate::{Message, Request};
+
+        let json = r#"{"jsonrpc":"2.0","id":1,"method":"foo",}"#;
+        let framed = frame(json);
+        let errors = [
+            Request::try_from_reader(framed.as_bytes()).unwrap_err(),
+            Message::try_from_reader_buffered(framed.as_bytes(), &mut Vec::new()).unwrap_err(),
+        ];
+        for err in errors {
+            let data = err.data.unwrap();
+            assert_eq!(data["byte_offset"], json.len() - 1);
+            assert_eq!(data["header_length"], framed.len() - json.len());
+        }
+    }
+
+    #[test]
+    fn test_get_content_from_reader_encoding() {
+        let bytes = "Content-Encoding: identity\r\nContent-Length: 5\r\n\r\nHello";
+        assert_eq!(
+            get_content_from_reader(bytes.as_bytes()).unwrap().1,
+            "Hello"
+        );
+
+        let bytes = "Content-Length: 5\r\nContent-Encoding: br\r\n\r\nHello";
+        let err = get_content_from_reader(bytes.as_bytes()).unwrap_err();
+        assert_eq!(err.code, Error::INVALID_REQUEST);
+        assert_eq!(err.data, Some(Value::from("br")));
+    }
+
+    #[test]
+    fn test_get_content_from_reader_chunked() {
+        use crate::{test_helpers::Trickle, Message};
+
+        let first = "Transfer-Encoding: chunked\r\n\r\n\
+            11\r\n{\"jsonrpc\":\"2.0\",\r\n\
+            1c;name=value\r\n\"method\":\"foo\",\"params\":[1]}\r\n\
+            0\r\nExpires: never\r\nX-Trailer: 1\r\n\r\n";
+        let second = "Content-Length: 5\r\n\r\nHello";
+        let bytes = format!("{}{}", first, second);
+
+        let (n, contents) = get_content_from_reader(bytes.as_bytes()).unwrap();
+        assert_eq!(n, first.len());
+        assert_eq!(contents, r#"{"jsonrpc":"2.0","method":"foo","params":[1]}"#);
+
+        let mut reader = Trickle::new(3).with_input(&bytes);
+        let (n, message) = Message::try_from_reader(&mut reader).unwrap();
+        assert_eq!(n, first.len());
+        assert_eq!(message.as_notification().unwrap().method, "foo");
+        assert_eq!(get_content_from_reader(&mut reader).unwrap().1, "Hello");
+
+        for bytes in [
+            "Transfer-Encoding: chunked\r\n\r\nzz\r\nHello\r\n0\r\n\r\n",
+            "Transfer-Encoding: chunked\r\n\r\n+5\r\nHello\r\n0\r\n\r\n",
+            "Transfer-Encoding: chunked\r\n\r\n3\r\nHello\r\n0\r\n\r\n",
+            "Transfer-Encoding: chunked\r\n\r\n5\r\nHel",
+            "Transfer-Encoding: gzip\r\n\r\n5\r\nHello\r\n0\r\n\r\n",
+            "Transfer-Encoding: chunked\r\nContent-Length: 5\r\n\r\nHello",
+            "Content-Length: 5\r\nTransfer-Encoding: chunked\r\n\r\nHello",
+        ] {
+            let err = get_content_from_reader(bytes.as_bytes()).unwrap_err();
+            assert_eq!(err.code, Error::INVALID_REQUEST, "{}", bytes);
+        }
+
+        let eof = std::io::Error::from(std::io::ErrorKind::UnexpectedEof).to_string();
+        for bytes in [
+            "Transfer-Encoding: chunked\r\n\r\n5\r\nHello\r\n0",
+            "Transfer-Encoding: chunked\r\n\r\n5\r\nHello\r\n0\r\n",
+            "Transfer-Encoding: chunked\r\n\r\n5\r\nHello\r\n0\r\nExpires: never",
+            "Transfer-Encoding: chunked\r\n\r\n5\r\nHello",
+            "Transfer-Encoding: chunked\r\n\r\n5\r\nHello\r",
+            "Transfer-Encoding: chunked\r\n\r\n5",
+        ] {
+            let err = get_content_from_reader(bytes.as_bytes()).unwrap_err();
+            assert_eq!(err.code, Error::INVALID_REQUEST, "{}", bytes);
+            assert_eq!(err.message, eof, "{}", bytes);
+        }
+
+        let bytes = "Transfer-Encoding: chunked\r\n\r\n5\r\nHello\r\n5\r\nWorld\r\n0\r\n\r\n";
+        let mut buf = Vec::new();
+        let err = get_content_from_reader_limited(bytes.as_bytes(), &mut buf, 8).unwrap_err();
+        assert_eq!(err.data, Some(Value::from(8)));
+        let (_, contents) =
+            get_content_from_reader_limited(bytes.as_bytes(), &mut buf, 10).unwrap();
+        assert_eq!(contents, "HelloWorld");
+    }
+
+    #[test]
+    fn test_try_get_content_from_reader_eof() {
+        let bytes = "Content-Length: 5\r\n\r\nHello";
+        let mut reader = bytes.as_bytes();
+        let (n, _, contents) = try_get_content_from_reader(&mut reader).unwrap().unwrap();
+        assert_eq!(n, bytes.len());
+        assert_eq!(contents, "Hello");
+        assert!(try_get_content_from_reader(&mut reader).unwrap().is_none());
+    }
+}
+
+#[cfg(feature = "bytes")]
+mod buf {
+    use super::*;
+    use bytes::{Buf, BytesMut};
+    use core::fmt::Write;
+
+    /// Append the frame of the value to the buffer, returning the number of bytes appended.
+    pub(crate) fn encode_to<T>(value: &T, buf: &mut BytesMut) -> Result<usize, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let json = to_json_compact(value)?;
+        let start = buf.len();
+        buf.reserve(frame_len(json.len()));
+        write!(buf, "Content-Length: {}\r\n\r\n", json.len()).map_err(|e| Error {
+            code: Error::PARSE_ERROR,
+            message: e.to_string(),
+            data: None,
+        })?;
+        buf.extend_from_slice(json.as_bytes());
+        Ok(buf.len() - start)
+    }
+
+    /// Parse the frame at the start of the buffer, advancing past it.
+    ///
+    /// Returns `None`, leaving the buffer untouched, if the frame is not complete. A frame with an
+    /// invalid body is consumed; an invalid header, or one declaring a frame longer than
+    /// [`MAX_FRAME_LEN`], is not, as its end is unknown.
+    pub(crate) fn decode_from<T, F>(buf: &mut BytesMut, parse: F) -> Result<Option<T>, Error>
+    where
+        F: FnOnce(&str) -> Result<T, Error>,
+    {
+        let Some((header_len, length)) = frame_bounds(buf)? else {
+            return Ok(None);
+        };
+        let frame_len = checked_frame_len(header_len, length, MAX_FRAME_LEN)?;
+        if buf.len() < frame_len {
+            return Ok(None);
+        }
+        let parsed = parse_body(&buf[header_len..frame_len], header_len, parse);
+        buf.advance(frame_len);
+        parsed.map(Some)
+    }
+}
+
+#[cfg(feature = "bytes")]
+pub(crate) use buf::{decode_from, encode_to};
+
+#[cfg(feature = "compression")]
+mod gzip {
+    use super::*;
+    use alloc::vec::Vec;
+    use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+    use std::io::{Read, Write};
+
+    /// Frame the provided JSON body gzipped, with `Content-Length` and `Content-Encoding` headers.
+    ///
+    /// The length is the size of the compressed body.
+    pub fn frame_gzip(body: &str) -> Result<Vec<u8>, Error> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        let compressed = encoder
+            .write_all(body.as_bytes())
+            .and_then(|_| encoder.finish())
+            .map_err(|e| Error {
+                code: Error::PARSE_ERROR,
+                message: e.to_string(),
+                data: None,
+            })?;
+
+        let mut frame = format!(
+            "Content-Length: {}\r\nContent-Encoding: gzip\r\n\r\n",
+            compressed.len()
+        )
+        .into_bytes();
+        frame.extend_from_slice(&compressed);
+        Ok(frame)
+    }
+
+    /// Decompress the gzipped body, failing if it exceeds the provided length.
+    pub(super) fn decompress(body: &[u8], max_len: usize) -> Result<Vec<u8>, Error> {
+        let mut decompressed = Vec::new();
+        GzDecoder::new(body)
+            .take(max_len as u64 + 1)
+            .read_to_end(&mut decompressed)
+            .map_err(|e| Error {
+                code: Error::INVALID_REQUEST,
+                message: e.to_string(),
+                data: None,
+            })?;
+        if decompressed.len() > max_len {
+            return Err(Error {
+                code: Error::INVALID_REQUEST,
+                message: format!(
+                    "the decompressed body exceeds the limit of {} bytes",
+                    max_len
+                ),
+                data: Some(Value::from(max_len)),
+            });
+        }
+        Ok(decompressed)
+    }
+
+    #[test]
+    fn test_gzip_round_trip() {
+        use crate::{Message, Notification};
+
+        let compressible = Value::from(alloc::vec!["completion item"; 1000]);
+        let mut seed = 7u64;
+        let incompressible: String = (0..4000)
+            .map(|_| {
+                seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+                char::from(b'!' + (seed >> 58) as u8)
+            })
+            .collect();
+
+        for params in [compressible, serde_json::json!([incompressible])] {
+            let message = Message::from(Notification::new("foo").with_params_value(params));
+            let mut frame = Vec::new();
+            let n = message.try_to_writer_gzip(&mut frame).unwrap();
+            assert_eq!(n, frame.len());
+
+            let (read, parsed) = Message::try_from_reader(frame.as_slice()).unwrap();
+            assert_eq!(read, frame.len());
+            assert_eq!(
+                parsed.to_compact_json().unwrap(),
+                message.to_compact_json().unwrap()
+            );
+        }
+
+        let body = serde_json::to_string(&alloc::vec!["completion item"; 1000]).unwrap();
+        assert!(frame_gzip(&body).unwrap().len() * 10 < body.len());
+    }
+
+    #[test]
+    fn test_gzip_limit() {
+        let body = format!("[{}]", " ".repeat(1 << 20));
+        let frame = frame_gzip(&body).unwrap();
+
+        let mut buf = Vec::new();
+        let err = get_content_from_reader_limited(frame.as_slice(), &mut buf, 1024).unwrap_err();
+        assert_eq!(err.code, Error::INVALID_REQUEST);
+        assert_eq!(err.data, Some(Value::from(1024)));
+
+        let (n, contents) =
+            get_content_from_reader_limited(frame.as_slice(), &mut buf, body.len()).unwrap();
+        assert_eq!(n, frame.len());
+        assert_eq!(contents, body);
+
+        let mut corrupt = frame.clone();
+        let last = corrupt.len() - 20;
+        corrupt[last] ^= 0xff;
+        let err = get_content_from_reader(corrupt.as_slice()).unwrap_err();
+        assert_eq!(err.code, Error::INVALID_REQUEST);
+    }
+
+    #[test]
+    fn test_gzip_malformed_json() {
+        use crate::{Message, Request};
+
+        let body = format!(
+            r#"{{"jsonrpc":"2.0","method":"{}" "id":1}}"#,
+            "a".repeat(1000)
+        );
+        let frame = frame_gzip(&body).unwrap();
+        let header_len = frame.windows(4).position(|w| w == b"\r\n\r\n").unwrap() + 4;
+        assert!(body.len() > frame.len());
+
+        let err = Message::try_from_reader(frame.as_slice()).unwrap_err();
+        assert_eq!(err.code, Error::PARSE_ERROR);
+        let data = err.data.unwrap();
+        assert_eq!(data["header_length"], header_len);
+        assert_eq!(data["byte_offset"], body.find(r#" "id""#).unwrap() + 1);
+
+        let mut buf = Vec::new();
+        let err = Request::try_from_reader_buffered(frame.as_slice(), &mut buf).unwrap_err();
+        assert_eq!(err.data.unwrap()["header_length"], header_len);
     }
 }
@@ -0,0 +1,82 @@
+use super::Request;
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+use serde_json::Value;
+
+/// Namespace of the request IDs generated by one side of a bidirectional connection.
+///
+/// When both peers send requests over the same transport, each picks its IDs independently. If
+/// the client sends a request with ID `1` while the server sends its own request with ID `1`, the
+/// response of either can be matched with the wrong request. Giving each direction a distinct
+/// prefix, such as `"c"` for the client and `"s"` for the server, keeps the IDs disjoint.
+///
+/// IDs are strings of the form `{prefix}-{n}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdSpace {
+    prefix: String,
+    next: u64,
+}
+
+impl IdSpace {
+    /// Create a namespace with the provided prefix, starting at `0`.
+    pub fn new<P>(prefix: P) -> Self
+    where
+        P: ToString,
+    {
+        Self {
+            prefix: prefix.to_string(),
+            next: 0,
+        }
+    }
+
+    /// Prefix of the namespace
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Generate the next ID of the namespace.
+    pub fn next_id(&mut self) -> Value {
+        let id = namespaced_id(&self.prefix, self.next);
+        self.next += 1;
+        id
+    }
+
+    /// Assign the next ID of the namespace to the request.
+    pub fn assign(&mut self, request: Request) -> Request {
+        Request {
+            id: self.next_id(),
+            ..request
+        }
+    }
+
+    /// Return `true` if the ID was generated in this namespace.
+    pub fn contains(&self, id: &Value) -> bool {
+        id.as_str()
+            .and_then(|id| id.strip_prefix(self.prefix.as_str()))
+            .and_then(|n| n.strip_prefix('-'))
+            .is_some_and(|n| n.parse::<u64>().is_ok())
+    }
+}
+
+pub(crate) fn namespaced_id(prefix: &str, n: u64) -> Value {
+    Value::String(format!("{}-{}", prefix, n))
+}
+
+#[test]
+fn test_id_space() {
+    let mut client = IdSpace::new("c");
+    let mut server = IdSpace::new("s");
+
+    let request = client.assign(Request::new("initialize"));
+    assert_eq!(request.id, Value::from("c-0"));
+    assert_eq!(server.next_id(), Value::from("s-0"));
+    assert_eq!(client.next_id(), Value::from("c-1"));
+
+    assert!(client.contains(&request.id));
+    assert!(!server.contains(&request.id));
+    assert!(!client.contains(&Value::from(0)));
+    assert!(!client.contains(&Value::from("c-x")));
+    assert!(!IdSpace::new("").contains(&Value::from("c-0")));
+}
@@ -4,15 +4,59 @@
 
 extern crate alloc;
 
+mod batch;
+#[cfg(feature = "std")]
+mod connection;
+mod decoder;
 mod error;
-mod helpers;
+mod extensions;
+pub mod helpers;
+mod id_space;
+#[cfg(feature = "lsp")]
+pub mod lsp;
 mod message;
+#[cfg(feature = "std")]
+mod middleware;
 mod notification;
+#[cfg(feature = "std")]
+mod pending;
+pub mod prelude;
+mod preserved;
+#[cfg(feature = "std")]
+mod reader;
+mod redacted;
 mod request;
 mod response;
+mod serialized;
+mod service;
+mod telemetry;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_helpers;
+#[cfg(all(feature = "std", unix))]
+mod unix;
+mod version;
 
-pub use error::Error;
+pub use batch::{Batch, BatchBuilder, BatchHandle, BatchMap, BatchResults};
+#[cfg(feature = "std")]
+pub use connection::{Connection, SetReadTimeout, TcpOptions};
+pub use decoder::{ConcatenatedDecoder, ContentLengthDecoder, LengthMismatch, ReadOptions};
+pub use error::{Error, ErrorCode};
+pub use extensions::{Extensions, MessageEnvelope};
+pub use id_space::IdSpace;
 pub use message::Message;
+#[cfg(feature = "std")]
+pub use middleware::{LatencyLog, Middleware, RequireParam};
 pub use notification::Notification;
+#[cfg(feature = "std")]
+pub use pending::PendingRequestsTimed;
+pub use preserved::Preserved;
+#[cfg(feature = "std")]
+pub use reader::{ConnectionReader, MessageIterator};
+pub use redacted::{RedactedRequest, RedactedResponse};
 pub use request::Request;
-pub use response::Response;
+pub use response::{Outcome, Response};
+pub use serialized::Serialized;
+pub use service::Service;
+#[cfg(all(feature = "std", unix))]
+pub use unix::{serve_unix, UnixServeOptions, UnixServer};
+pub use version::JsonRpcVersion;
@@ -0,0 +1,132 @@
+//! Lifecycle messages of the Language Server Protocol.
+//!
+//! Only the method names are modeled; the parameters of `initialize` are provided by the caller.
+
+use super::{Error, Notification, Request};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Method of the `initialize` request
+pub const INITIALIZE: &str = "initialize";
+/// Method of the `initialized` notification
+pub const INITIALIZED: &str = "initialized";
+/// Method of the `shutdown` request
+pub const SHUTDOWN: &str = "shutdown";
+/// Method of the `exit` notification
+pub const EXIT: &str = "exit";
+/// Method of the `$/cancelRequest` notification
+pub const CANCEL_REQUEST: &str = "$/cancelRequest";
+/// Method of the `$/setTrace` notification
+pub const SET_TRACE: &str = "$/setTrace";
+
+impl Request {
+    /// Create an `initialize` request with the provided parameters
+    pub fn initialize<P>(params: P) -> Result<Self, Error>
+    where
+        P: Serialize,
+    {
+        Request::new(INITIALIZE).with_params(params)
+    }
+
+    /// Create a `shutdown` request
+    pub fn shutdown() -> Self {
+        Request::new(SHUTDOWN)
+    }
+
+    /// Return `true` if the request is an `initialize` request
+    pub fn is_initialize(&self) -> bool {
+        self.method == INITIALIZE
+    }
+
+    /// Return `true` if the request is a `shutdown` request
+    pub fn is_shutdown(&self) -> bool {
+        self.method == SHUTDOWN
+    }
+}
+
+impl Notification {
+    /// Create an `initialized` notification
+    pub fn initialized() -> Self {
+        Notification::new(INITIALIZED).with_params_value(json!({}))
+    }
+
+    /// Create an `exit` notification
+    pub fn exit() -> Self {
+        Notification::new(EXIT)
+    }
+
+    /// Create a `$/cancelRequest` notification for the request with the provided ID
+    pub fn cancel_request<I>(id: I) -> Self
+    where
+        I: Into<Value>,
+    {
+        Notification::new(CANCEL_REQUEST).with_params_value(json!({ "id": id.into() }))
+    }
+
+    /// Create a `$/setTrace` notification with the provided trace value, such as `"verbose"`
+    pub fn set_trace<V>(value: V) -> Self
+    where
+        V: AsRef<str>,
+    {
+        Notification::new(SET_TRACE).with_params_value(json!({ "value": value.as_ref() }))
+    }
+
+    /// Return `true` if the notification is an `initialized` notification
+    pub fn is_initialized(&self) -> bool {
+        self.method == INITIALIZED
+    }
+
+    /// Return `true` if the notification is an `exit` notification
+    pub fn is_exit(&self) -> bool {
+        self.method == EXIT
+    }
+
+    /// Return `true` if the notification is a `$/cancelRequest` notification
+    pub fn is_cancel_request(&self) -> bool {
+        self.method == CANCEL_REQUEST
+    }
+
+    /// Return `true` if the notification is a `$/setTrace` notification
+    pub fn is_set_trace(&self) -> bool {
+        self.method == SET_TRACE
+    }
+}
+
+#[test]
+fn test_lsp_methods() {
+    let initialize = Request::initialize(json!({"processId": null})).unwrap();
+    assert_eq!(initialize.method, "initialize");
+    assert_eq!(Request::shutdown().method, "shutdown");
+    assert_eq!(Notification::initialized().method, "initialized");
+    assert_eq!(Notification::exit().method, "exit");
+    assert_eq!(Notification::set_trace("off").method, "$/setTrace");
+
+    let cancel = Notification::cancel_request(4);
+    assert_eq!(cancel.method, "$/cancelRequest");
+    assert_eq!(cancel.params, Some(json!({"id": 4})));
+}
+
+#[test]
+fn test_lsp_predicates() {
+    let request = Request::parse_json(r#"{"jsonrpc":"2.0","id":1,"method":"shutdown"}"#).unwrap();
+    assert!(request.is_shutdown());
+    assert!(!request.is_initialize());
+
+    let request =
+        Request::parse_json(r#"{"jsonrpc":"2.0","id":0,"method":"initialize","params":{}}"#)
+            .unwrap();
+    assert!(request.is_initialize());
+
+    let notification = Notification::parse_json(r#"{"jsonrpc":"2.0","method":"exit"}"#).unwrap();
+    assert!(notification.is_exit());
+    assert!(!notification.is_initialized());
+
+    let json = r#"{"jsonrpc":"2.0","method":"$/cancelRequest","params":{"id":1}}"#;
+    assert!(Notification::parse_json(json).unwrap().is_cancel_request());
+
+    let json = r#"{"jsonrpc":"2.0","method":"$/setTrace","params":{"value":"off"}}"#;
+    assert!(Notification::parse_json(json).unwrap().is_set_trace());
+
+    let json = r#"{"jsonrpc":"2.0","method":"initialized","params":{}}"#;
+    assert!(Notification::parse_json(json).unwrap().is_initialized());
+}
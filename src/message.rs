@@ -1,5 +1,11 @@
-use super::{helpers, Error, Notification, Request, Response};
-use alloc::string::ToString;
+use super::{
+    decoder, helpers, telemetry, Error, LengthMismatch, Notification, ReadOptions, Request,
+    Response,
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
 use core::{fmt, str::FromStr};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -19,17 +25,295 @@ impl Message {
     /// Parse a string into the message, returning the message and the remaining string
     pub fn parse(s: &str) -> Result<(Self, &str), Error> {
         let (message, remainder) = helpers::get_content_length(s)?;
-        let message = Message::parse_json(message)?;
+        let header_len = s.len() - remainder.len() - message.len();
+        let message = Message::parse_json(message).map_err(|e| helpers::in_frame(e, header_len))?;
         Ok((message, remainder))
     }
 
+    /// Parse a message as [`Message::parse`], with the provided options.
+    ///
+    /// With [`ReadOptions::tolerate_length_mismatch`], a frame whose declared length does not
+    /// delimit valid JSON is recovered from the end of the JSON value of its body, and the
+    /// remainder starts after that value. The discrepancy is returned, and logged as a warning.
+    pub fn parse_with<'a>(
+        s: &'a str,
+        options: &ReadOptions,
+    ) -> Result<(Self, &'a str, Option<LengthMismatch>), Error> {
+        let err = match Self::parse(s) {
+            Ok((message, remainder)) => return Ok((message, remainder, None)),
+            Err(err) if options.tolerate_length_mismatch => err,
+            Err(err) => return Err(err),
+        };
+        let b = s.as_bytes();
+        let Ok(Some((header_len, declared))) = helpers::frame_bounds(b) else {
+            return Err(err);
+        };
+        let limit = b
+            .len()
+            .min(header_len.saturating_add(decoder::scan_limit(declared)));
+        let recovered = match decoder::JsonBoundary::default().scan(&b[header_len..limit]) {
+            Ok(Some(actual)) => decoder::recover_frame(b, header_len, declared, actual),
+            _ => None,
+        };
+        match recovered {
+            Some((message, mismatch)) => {
+                let remainder = &s[header_len + mismatch.actual..];
+                Ok((message, remainder, Some(mismatch)))
+            }
+            None => Err(err),
+        }
+    }
+
+    /// Append the framed message to the buffer, returning the number of bytes appended
+    #[cfg(feature = "bytes")]
+    pub fn encode_to(&self, buf: &mut bytes::BytesMut) -> Result<usize, Error> {
+        match self {
+            Message::Request(r) => r.encode_to(buf),
+            Message::Notification(n) => n.encode_to(buf),
+            Message::Response(r) => r.encode_to(buf),
+        }
+    }
+
+    /// Parse the message framed at the start of the buffer, advancing past its frame.
+    ///
+    /// Returns `None`, leaving the buffer untouched, if the frame is not complete yet. A frame
+    /// with an invalid body is consumed, but not one with an invalid header or one declaring more
+    /// than [`helpers::MAX_FRAME_LEN`] bytes.
+    #[cfg(feature = "bytes")]
+    pub fn decode_from(buf: &mut bytes::BytesMut) -> Result<Option<Self>, Error> {
+        helpers::decode_from(buf, Message::parse_json)
+    }
+
+    /// Parse the first message of a header-less stream of concatenated JSON values, returning
+    /// it with the remaining string.
+    ///
+    /// Returns `None` if the first value is not complete yet. A top-level scalar is rejected with
+    /// [`Error::INVALID_REQUEST`].
+    pub fn parse_concatenated(s: &str) -> Result<Option<(Self, &str)>, Error> {
+        match decoder::JsonBoundary::default().scan(s.as_bytes())? {
+            Some(end) => {
+                let (json, remainder) = s.split_at(end);
+                Message::parse_json(json).map(|message| Some((message, remainder)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Version of the protocol of the message.
+    ///
+    /// This is `"2.0"` for messages built by this crate; a parsed message keeps the version sent
+    /// by the peer, such as the `"1.0"` of a response parsed with the `v1-compat` feature.
+    pub fn jsonrpc_version(&self) -> &str {
+        match self {
+            Message::Request(r) => r.jsonrpc_version(),
+            Message::Notification(n) => n.jsonrpc_version(),
+            Message::Response(r) => r.jsonrpc_version(),
+        }
+    }
+
+    /// Return the request if the message is one
+    pub fn as_request(&self) -> Option<&Request> {
+        match self {
+            Message::Request(m) => Some(m),
+            _ => None,
+        }
+    }
+
+    /// Return the request if the message is one, mutably
+    pub fn as_request_mut(&mut self) -> Option<&mut Request> {
+        match self {
+            Message::Request(m) => Some(m),
+            _ => None,
+        }
+    }
+
+    /// Return the notification if the message is one
+    pub fn as_notification(&self) -> Option<&Notification> {
+        match self {
+            Message::Notification(m) => Some(m),
+            _ => None,
+        }
+    }
+
+    /// Return the notification if the message is one, mutably
+    pub fn as_notification_mut(&mut self) -> Option<&mut Notification> {
+        match self {
+            Message::Notification(m) => Some(m),
+            _ => None,
+        }
+    }
+
+    /// Return the response if the message is one
+    pub fn as_response(&self) -> Option<&Response> {
+        match self {
+            Message::Response(m) => Some(m),
+            _ => None,
+        }
+    }
+
+    /// Return the response if the message is one, mutably
+    pub fn as_response_mut(&mut self) -> Option<&mut Response> {
+        match self {
+            Message::Response(m) => Some(m),
+            _ => None,
+        }
+    }
+
+    /// Return the error if the message is an error response
+    pub fn extract_error(&self) -> Option<&Error> {
+        match self {
+            Message::Response(r) => r.error(),
+            _ => None,
+        }
+    }
+
+    /// Return the result if the message is a success response
+    pub fn extract_result(&self) -> Option<&Value> {
+        match self {
+            Message::Response(r) => r.result(),
+            _ => None,
+        }
+    }
+
+    /// Replace the ID of a request or response, leaving notifications unchanged
+    pub fn rewrite_id(self, new_id: Value) -> Self {
+        match self {
+            Message::Request(r) => Message::Request(Request { id: new_id, ..r }),
+            Message::Notification(n) => Message::Notification(n),
+            Message::Response(r) => Message::Response(Response { id: new_id, ..r }),
+        }
+    }
+
+    /// Replace the method of a request or notification, leaving responses unchanged
+    pub fn rewrite_method<M>(self, new_method: M) -> Self
+    where
+        M: ToString,
+    {
+        match self {
+            Message::Request(r) => Message::Request(Request {
+                method: new_method.to_string(),
+                ..r
+            }),
+            Message::Notification(n) => Message::Notification(Notification {
+                method: new_method.to_string(),
+                ..n
+            }),
+            Message::Response(r) => Message::Response(r),
+        }
+    }
+
+    /// Remove the params of a request or notification, or replace the result of a success
+    /// response with `null`, to log the message without its payload.
+    pub fn strip_params(self) -> Self {
+        match self {
+            Message::Request(r) => Message::Request(Request { params: None, ..r }),
+            Message::Notification(n) => Message::Notification(Notification { params: None, ..n }),
+            Message::Response(r) => Message::Response(r.map_result(|_| Value::Null)),
+        }
+    }
+
+    /// Replace the params of a request or notification, or the result of a success response,
+    /// with the placeholder. Messages without a payload are unchanged.
+    pub fn redact_params(self, placeholder: Value) -> Self {
+        match self {
+            Message::Request(r) => Message::Request(r.map_params(|p| p.map(|_| placeholder))),
+            Message::Notification(n) => {
+                Message::Notification(n.map_params(|p| p.map(|_| placeholder)))
+            }
+            Message::Response(r) => Message::Response(r.map_result(|_| placeholder)),
+        }
+    }
+
+    /// Serialize the message to canonical JSON.
+    ///
+    /// The output is deterministic: object keys are sorted at every level, including inside
+    /// arguments and results, with no whitespace. Numbers are written in the ECMAScript form of
+    /// their value, as in RFC 8785, so `1` and `1.0` canonicalize identically; integers keep
+    /// their digits.
+    pub fn to_json_canonical(&self) -> Result<String, Error> {
+        match self {
+            Message::Request(r) => r.to_json_canonical(),
+            Message::Notification(n) => n.to_json_canonical(),
+            Message::Response(r) => r.to_json_canonical(),
+        }
+    }
+
+    /// Bytes of the canonical JSON of the message, to be fed into a hasher
+    pub fn canonical_hash(&self) -> Result<Vec<u8>, Error> {
+        self.to_json_canonical().map(String::into_bytes)
+    }
+
+    /// Approximate length of the JSON serialization of the message, to pre-size buffers.
+    ///
+    /// This is a hint computed without serializing: it is not exact, and not a bound. Use
+    /// [`Message::json_len`] for the exact length.
+    pub fn serialized_len_hint(&self) -> usize {
+        match self {
+            Message::Request(r) => r.serialized_len_hint(),
+            Message::Notification(n) => n.serialized_len_hint(),
+            Message::Response(r) => r.serialized_len_hint(),
+        }
+    }
+
+    /// Length of the JSON serialization of the message, without the frame header
+    pub fn json_len(&self) -> Result<usize, Error> {
+        match self {
+            Message::Request(r) => r.json_len(),
+            Message::Notification(n) => n.json_len(),
+            Message::Response(r) => r.json_len(),
+        }
+    }
+
+    /// Length of the frame of the message, header included.
+    ///
+    /// Equals the length of its `Display` output, computed without building the frame.
+    pub fn encoded_len(&self) -> Result<usize, Error> {
+        self.json_len().map(helpers::frame_len)
+    }
+
+    /// Serialize the message to compact JSON, without the frame header
+    pub fn to_compact_json(&self) -> Result<String, Error> {
+        match self {
+            Message::Request(r) => r.to_compact_json(),
+            Message::Notification(n) => n.to_compact_json(),
+            Message::Response(r) => r.to_compact_json(),
+        }
+    }
+
+    /// Serialize the message to indented JSON, without the frame header
+    pub fn to_pretty_json(&self) -> Result<String, Error> {
+        match self {
+            Message::Request(r) => r.to_pretty_json(),
+            Message::Notification(n) => n.to_pretty_json(),
+            Message::Response(r) => r.to_pretty_json(),
+        }
+    }
+
+    /// Serialize the message to JSON, escaping every non-ASCII character as `\uXXXX`
+    pub fn to_json_ascii(&self) -> Result<String, Error> {
+        match self {
+            Message::Request(r) => r.to_json_ascii(),
+            Message::Notification(n) => n.to_json_ascii(),
+            Message::Response(r) => r.to_json_ascii(),
+        }
+    }
+
+    /// Serialize the message to a frame with an ASCII-only body.
+    ///
+    /// The `Content-Length` header is computed over the escaped body.
+    pub fn to_string_ascii(&self) -> Result<String, Error> {
+        match self {
+            Message::Request(r) => r.to_string_ascii(),
+            Message::Notification(n) => n.to_string_ascii(),
+            Message::Response(r) => r.to_string_ascii(),
+        }
+    }
+
     /// Parse a message from the provided JSON
     pub fn parse_json(json: &str) -> Result<Self, Error> {
-        let value: Value = serde_json::from_str(json).map_err(|e| Error {
-            code: Error::INVALID_REQUEST,
-            message: e.to_string(),
-            data: Some(Value::String(json.to_string())),
-        })?;
+        let value: Value = serde_json::from_str(json)
+            .map_err(|e| helpers::json_error(e, json))
+            .inspect_err(|e| telemetry::parse_failed(e, json))?;
         if value.get("method").is_some() && value.get("id").is_some() {
             Request::parse_json(json).map(Self::Request)
         } else if value.get("method").is_some() {
@@ -121,6 +405,480 @@ impl TryFrom<Message> for Response {
     }
 }
 
+/// Borrow the request of the message.
+///
+/// Unlike the owned conversion, the error on mismatch carries no data.
+impl<'a> TryFrom<&'a Message> for &'a Request {
+    type Error = Error;
+
+    fn try_from(value: &'a Message) -> Result<Self, Self::Error> {
+        match value {
+            Message::Request(request) => Ok(request),
+            _ => Err(Error {
+                code: Error::INVALID_REQUEST,
+                message: "the provided message is not a request".to_string(),
+                data: None,
+            }),
+        }
+    }
+}
+
+/// Borrow the notification of the message.
+///
+/// Unlike the owned conversion, the error on mismatch carries no data.
+impl<'a> TryFrom<&'a Message> for &'a Notification {
+    type Error = Error;
+
+    fn try_from(value: &'a Message) -> Result<Self, Self::Error> {
+        match value {
+            Message::Notification(notification) => Ok(notification),
+            _ => Err(Error {
+                code: Error::INVALID_REQUEST,
+                message: "the provided message is not a notification".to_string(),
+                data: None,
+            }),
+        }
+    }
+}
+
+/// Borrow the response of the message.
+///
+/// Unlike the owned conversion, the error on mismatch carries no data.
+impl<'a> TryFrom<&'a Message> for &'a Response {
+    type Error = Error;
+
+    fn try_from(value: &'a Message) -> Result<Self, Self::Error> {
+        match value {
+            Message::Response(response) => Ok(response),
+            _ => Err(Error {
+                code: Error::INVALID_REQUEST,
+                message: "the provided message is not a response".to_string(),
+                data: None,
+            }),
+        }
+    }
+}
+
+#[test]
+fn test_message_as() {
+    let mut message = Message::from(Request::new("foo").with_id(1));
+    assert_eq!(message.as_request().unwrap().method, "foo");
+    assert!(message.as_notification().is_none());
+    assert!(message.as_response().is_none());
+
+    message.as_request_mut().unwrap().method = "bar".to_string();
+    assert_eq!(message.as_request().unwrap().method, "bar");
+    assert!(message.as_response_mut().is_none());
+
+    let mut message = Message::from(Notification::new("foo"));
+    assert!(message.as_notification().is_some());
+    message.as_notification_mut().unwrap().params = Some(Value::from(alloc::vec![1]));
+    assert!(message.as_request().is_none());
+
+    let message = Message::from(Response::ok(1, true));
+    assert_eq!(message.as_response().unwrap().id, Value::from(1));
+}
+
+#[test]
+fn test_extract_error_and_result() {
+    let err = Error {
+        code: Error::INTERNAL_ERROR,
+        message: "foo".to_string(),
+        data: None,
+    };
+    let message = Message::from(Response::err(1, err));
+    assert_eq!(message.extract_error().unwrap().code, Error::INTERNAL_ERROR);
+    assert!(message.extract_result().is_none());
+
+    let message = Message::from(Response::ok(1, true));
+    assert!(message.extract_error().is_none());
+    assert_eq!(message.extract_result(), Some(&Value::Bool(true)));
+
+    let message = Message::from(Request::new("foo"));
+    assert!(message.extract_error().is_none());
+    assert!(message.extract_result().is_none());
+}
+
+#[test]
+fn test_rewrite_id_and_method() {
+    let message = Message::from(Request::new("foo").with_id(1))
+        .rewrite_id(Value::from("upstream-1"))
+        .rewrite_method("bar");
+    match message {
+        Message::Request(r) => {
+            assert_eq!(r.id, Value::from("upstream-1"));
+            assert_eq!(r.method, "bar");
+        }
+        m => panic!("unexpected message {:?}", m),
+    }
+
+    let message = Message::from(Response::ok("upstream-1", true))
+        .rewrite_id(Value::from(1))
+        .rewrite_method("bar");
+    match message {
+        Message::Response(r) => assert_eq!(r.id, Value::from(1)),
+        m => panic!("unexpected message {:?}", m),
+    }
+
+    let message = Message::from(Notification::new("foo"))
+        .rewrite_id(Value::from(1))
+        .rewrite_method("bar");
+    let expected = Notification::new("bar").to_string();
+    assert_eq!(message.to_string(), expected);
+}
+
+#[test]
+fn test_strip_and_redact_params() {
+    let request = Message::from(Request::new("open").with_params(["contents"]).unwrap());
+    let stripped = request.clone().strip_params();
+    assert!(!stripped.to_string().contains("contents"));
+    assert!(matches!(
+        stripped,
+        Message::Request(Request { params: None, .. })
+    ));
+
+    let redacted = request.redact_params(Value::from("<redacted>"));
+    match redacted {
+        Message::Request(r) => assert_eq!(r.params, Some(Value::from("<redacted>"))),
+        m => panic!("unexpected message {:?}", m),
+    }
+
+    let notification = Message::from(Notification::new("exit"));
+    match notification.redact_params(Value::from("<redacted>")) {
+        Message::Notification(n) => assert!(n.params.is_none()),
+        m => panic!("unexpected message {:?}", m),
+    }
+
+    let response = Message::from(Response::ok(1, "contents")).strip_params();
+    assert_eq!(response.extract_result(), Some(&Value::Null));
+
+    let err = Error {
+        code: Error::INTERNAL_ERROR,
+        message: "foo".to_string(),
+        data: None,
+    };
+    let response = Message::from(Response::err(1, err)).strip_params();
+    assert!(response.extract_result().is_none());
+    assert!(response.extract_error().is_some());
+}
+
+#[test]
+fn test_to_json_canonical() {
+    #[derive(Serialize)]
+    struct Params {
+        zeta: f64,
+        alpha: Value,
+    }
+
+    let from_struct = Request::new("foo")
+        .with_id(1)
+        .with_params(Params {
+            zeta: 1.5,
+            alpha: serde_json::json!({"b": [1, {"d": 2, "c": 3}], "a": "\u{e9}"}),
+        })
+        .unwrap();
+    let mut params = serde_json::Map::new();
+    params.insert(
+        "alpha".to_string(),
+        serde_json::json!({"a": "\u{e9}", "b": [1, {"c": 3, "d": 2}]}),
+    );
+    params.insert("zeta".to_string(), Value::from(1.5));
+    let from_map = Request::new("foo")
+        .with_id(1)
+        .with_params_value(Value::Object(params));
+
+    let canonical = Message::from(from_struct).to_json_canonical().unwrap();
+    assert_eq!(canonical, from_map.to_json_canonical().unwrap());
+    assert_eq!(
+        canonical,
+        concat!(
+            r#"{"id":1,"jsonrpc":"2.0","method":"foo","#,
+            r#""params":{"alpha":{"a":"é","b":[1,{"c":3,"d":2}]},"zeta":1.5}}"#
+        )
+    );
+    assert_eq!(from_map.canonical_hash().unwrap(), canonical.as_bytes());
+
+    let parsed = Request::parse_json(&canonical).unwrap();
+    assert_eq!(parsed.to_json_canonical().unwrap(), canonical);
+}
+
+#[test]
+fn test_serialized_len_hint() {
+    use alloc::vec;
+
+    let messages = vec![
+        Message::from(Request::new("foo").with_id(1)),
+        Message::from(
+            Request::new("textDocument/completion")
+                .with_id_string("3f2a")
+                .with_params_value(serde_json::json!({
+                    "textDocument": {"uri": "file:///src/main.rs"},
+                    "position": {"line": 120, "character": 17},
+                    "context": {"triggerKind": 1, "items": [1, 2, 3, null, true]},
+                })),
+        ),
+        Message::from(Notification::new("exit")),
+        Message::from(Notification::new("log").with_params_value(Value::from(vec!["x"; 50]))),
+        Message::from(Response::ok(1, "caf\u{e9}")),
+        Message::from(Response::ok(
+            2,
+            serde_json::json!({"items": vec!["completion"; 100]}),
+        )),
+        Message::from(Response::err(
+            2,
+            Error {
+                code: Error::INTERNAL_ERROR,
+                message: "foo".to_string(),
+                data: Some(Value::from(12345)),
+            },
+        )),
+    ];
+
+    for message in messages {
+        let hint = message.serialized_len_hint();
+        let len = message.json_len().unwrap();
+        assert!(
+            len <= hint * 3 / 2 && hint <= len * 3 / 2,
+            "{} {}",
+            hint,
+            len
+        );
+    }
+}
+
+#[test]
+fn test_encoded_len() {
+    use alloc::vec;
+
+    let mut messages = vec![
+        Message::from(Request::new("foo").with_id(1)),
+        Message::from(
+            Request::new("\u{1f680}")
+                .with_params(["\u{6f22}\u{5b57}"])
+                .unwrap(),
+        ),
+        Message::from(Notification::new("exit")),
+        Message::from(Response::ok(1, "caf\u{e9}")),
+        Message::from(Response::err(
+            2,
+            Error {
+                code: Error::INTERNAL_ERROR,
+                message: "foo".to_string(),
+                data: None,
+            },
+        )),
+    ];
+
+    // Bodies around the length where the header gains a digit
+    for n in 0..120 {
+        messages.push(Message::from(Notification::new("x".repeat(n))));
+    }
+
+    for message in messages {
+        let frame = message.to_string();
+        let (body, _) = helpers::get_content_length(&frame).unwrap();
+        assert_eq!(message.encoded_len().unwrap(), frame.len());
+        assert_eq!(message.json_len().unwrap(), body.len());
+    }
+}
+
+#[test]
+fn test_to_string_ascii_round_trip() {
+    let params = serde_json::json!(["\u{1f680} launch", "\u{6f22}\u{5b57}"]);
+    let message = Message::from(Request::new("foo").with_params_value(params.clone()));
+
+    let framed = message.to_string_ascii().unwrap();
+    assert!(framed.is_ascii());
+    assert!(framed.contains(r"\ud83d\ude80"));
+
+    let (parsed, remainder) = Message::parse(&framed).unwrap();
+    assert!(remainder.is_empty());
+    match parsed {
+        Message::Request(r) => assert_eq!(r.params, Some(params)),
+        m => panic!("unexpected message {:?}", m),
+    }
+}
+
+#[test]
+fn test_compact_and_pretty_json() {
+    let request = Request::new("foo").with_id(1).with_params([1]).unwrap();
+    let message = Message::from(request.clone());
+
+    let compact = message.to_compact_json().unwrap();
+    assert_eq!(
+        compact,
+        r#"{"jsonrpc":"2.0","id":1,"method":"foo","params":[1]}"#
+    );
+    assert_eq!(request.to_compact_json().unwrap(), compact);
+    assert!(request.to_string().ends_with(&compact));
+
+    let pretty = message.to_pretty_json().unwrap();
+    assert!(pretty.contains("\n  \"method\": \"foo\""));
+    assert_eq!(
+        serde_json::from_str::<Value>(&pretty).unwrap(),
+        serde_json::from_str::<Value>(&compact).unwrap()
+    );
+
+    let error = Error {
+        code: Error::INTERNAL_ERROR,
+        message: "foo".to_string(),
+        data: None,
+    };
+    assert_eq!(
+        error.to_compact_json().unwrap(),
+        r#"{"code":-32603,"message":"foo","data":null}"#
+    );
+}
+
+#[test]
+fn test_parse_concatenated() {
+    let stream = r#"{"jsonrpc":"2.0","method":"}"} {"jsonrpc":"2.0","id":1,"result":"{"}"#;
+    let (first, rest) = Message::parse_concatenated(stream).unwrap().unwrap();
+    assert_eq!(first.as_notification().unwrap().method, "}");
+    let (second, rest) = Message::parse_concatenated(rest).unwrap().unwrap();
+    assert_eq!(
+        second.as_response().unwrap().result(),
+        Some(&Value::from("{"))
+    );
+    assert!(rest.is_empty());
+
+    assert!(Message::parse_concatenated(r#"{"jsonrpc":"2.0","#)
+        .unwrap()
+        .is_none());
+    assert!(Message::parse_concatenated("  ").unwrap().is_none());
+    for scalar in ["42", "\"foo\"", "null"] {
+        let err = Message::parse_concatenated(scalar).unwrap_err();
+        assert_eq!(err.code, Error::INVALID_REQUEST);
+    }
+}
+
+#[test]
+fn test_parse_with_length_mismatch() {
+    let body = Request::new("foo").with_id(1).to_compact_json().unwrap();
+    let next = Notification::new("bar").to_string();
+    let tolerant = ReadOptions {
+        tolerate_length_mismatch: true,
+    };
+
+    for declared in [body.len() - 3, body.len() + 3] {
+        let stream = alloc::format!("Content-Length: {}\r\n\r\n{}{}", declared, body, next);
+        assert!(Message::parse(&stream).is_err());
+        assert!(Message::parse_with(&stream, &ReadOptions::default()).is_err());
+
+        let (message, remainder, mismatch) = Message::parse_with(&stream, &tolerant).unwrap();
+        assert_eq!(message.as_request().unwrap().method, "foo");
+        assert_eq!(
+            mismatch,
+            Some(LengthMismatch {
+                declared,
+                actual: body.len()
+            })
+        );
+        assert_eq!(remainder, next);
+        let (message, remainder, mismatch) = Message::parse_with(remainder, &tolerant).unwrap();
+        assert!(message.as_notification().is_some());
+        assert!(remainder.is_empty());
+        assert!(mismatch.is_none());
+    }
+
+    let invalid = "Content-Length: 3\r\n\r\n{x}";
+    assert!(Message::parse_with(invalid, &tolerant).is_err());
+}
+
+#[test]
+fn test_message_jsonrpc_version() {
+    let messages: [Message; 3] = [
+        Request::new("foo").into(),
+        Notification::new("bar").into(),
+        Response::ok(0, true).into(),
+    ];
+    for message in &messages {
+        assert_eq!(message.jsonrpc_version(), "2.0");
+        let (parsed, _) = Message::parse(&message.to_string()).unwrap();
+        assert_eq!(parsed.jsonrpc_version(), "2.0");
+    }
+
+    let message: Message = Request::new("foo").set_jsonrpc_version("1.0").into();
+    assert_eq!(message.jsonrpc_version(), "1.0");
+}
+
+#[test]
+fn test_try_from_borrowed_message() {
+    let message = Message::from(Request::new("foo").with_id(1));
+    let request: &Request = (&message).try_into().unwrap();
+    assert_eq!(request.method, "foo");
+
+    let err = <&Response>::try_from(&message).unwrap_err();
+    assert_eq!(err.code, Error::INVALID_REQUEST);
+    assert!(err.data.is_none());
+    assert!(<&Notification>::try_from(&message).is_err());
+
+    let message = Message::from(Response::ok(1, true));
+    assert!(<&Response>::try_from(&message).is_ok());
+    assert!(<&Request>::try_from(&message).is_err());
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn test_bytes_codec() {
+    use bytes::BytesMut;
+
+    let request: Message = Request::new("foo").with_id(1).into();
+    let notification: Message = Notification::new("bar").into();
+    let frame = request.to_string();
+
+    let mut buf = BytesMut::new();
+    buf.extend_from_slice(&frame.as_bytes()[..10]);
+    assert!(Message::decode_from(&mut buf).unwrap().is_none());
+    buf.extend_from_slice(&frame.as_bytes()[10..frame.len() - 1]);
+    assert!(Message::decode_from(&mut buf).unwrap().is_none());
+    assert_eq!(buf.len(), frame.len() - 1);
+
+    buf.extend_from_slice(&frame.as_bytes()[frame.len() - 1..]);
+    let decoded = Message::decode_from(&mut buf).unwrap().unwrap();
+    assert_eq!(decoded.as_request().unwrap().method, "foo");
+    assert!(buf.is_empty());
+
+    let mut buf = BytesMut::new();
+    assert_eq!(request.encode_to(&mut buf).unwrap(), frame.len());
+    notification.encode_to(&mut buf).unwrap();
+    Response::ok(1, true).encode_to(&mut buf).unwrap();
+    buf.extend_from_slice(b"Content-Len");
+    assert!(Message::decode_from(&mut buf)
+        .unwrap()
+        .unwrap()
+        .as_request()
+        .is_some());
+    assert_eq!(
+        Notification::decode_from(&mut buf).unwrap().unwrap().method,
+        "bar"
+    );
+    assert!(Message::decode_from(&mut buf)
+        .unwrap()
+        .unwrap()
+        .as_response()
+        .is_some());
+    assert!(Message::decode_from(&mut buf).unwrap().is_none());
+    assert_eq!(&buf[..], b"Content-Len");
+
+    let mut buf = BytesMut::from("Content-Length: 3\r\n\r\n{]}Content-Length: 2\r\n\r\n{}");
+    assert_eq!(
+        Message::decode_from(&mut buf).unwrap_err().code,
+        Error::PARSE_ERROR
+    );
+    assert_eq!(&buf[..], b"Content-Length: 2\r\n\r\n{}");
+
+    let mut buf = BytesMut::from("Foo\r\n\r\n");
+    assert!(Request::decode_from(&mut buf).is_err());
+    assert_eq!(buf.len(), 7);
+
+    let mut buf = BytesMut::from("Content-Length: 18446744073709551615\r\n\r\n{}");
+    let err = Message::decode_from(&mut buf).unwrap_err();
+    assert_eq!(err.code, Error::INVALID_REQUEST);
+    assert_eq!(err.data, Some(helpers::MAX_FRAME_LEN.into()));
+    assert_eq!(buf.len(), 42);
+}
+
 #[cfg(feature = "std")]
 mod io {
     use super::*;
@@ -134,23 +892,177 @@ mod io {
         where
             R: Read,
         {
-            let (n, contents) = helpers::get_content_from_reader(reader)?;
-            let message = Message::parse_json(&contents)?;
+            let span = telemetry::IoSpan::read();
+            let (n, header_len, contents) = helpers::read_frame(reader)?;
+            let message =
+                Message::parse_json(&contents).map_err(|e| helpers::in_frame(e, header_len))?;
+            span.finish(&message, n);
+            Ok((n, message))
+        }
+
+        /// Read exactly `n` messages from a reader.
+        ///
+        /// Fails with the error of the first read that fails, such as the end of the stream
+        /// before the `n`th message.
+        pub fn try_read_n<R>(mut reader: R, n: usize) -> Result<Vec<Self>, Error>
+        where
+            R: Read,
+        {
+            (0..n)
+                .map(|_| Self::try_from_reader(&mut reader).map(|(_, message)| message))
+                .collect()
+        }
+
+        /// Read and discard `n` messages from a reader, such as a protocol handshake.
+        ///
+        /// The bodies are not parsed. Returns the number of consumed bytes.
+        pub fn try_skip_n<R>(mut reader: R, n: usize) -> Result<usize, Error>
+        where
+            R: Read,
+        {
+            (0..n).try_fold(0, |total, _| {
+                helpers::get_content_from_reader(&mut reader).map(|(len, _)| total + len)
+            })
+        }
+
+        /// Read the messages of a reader until EOF.
+        ///
+        /// The reader must end at a frame boundary; an EOF inside a frame, or a malformed frame,
+        /// fails the whole read.
+        pub fn read_all<R>(reader: R) -> Result<Vec<Self>, Error>
+        where
+            R: Read,
+        {
+            crate::MessageIterator::new(reader).collect()
+        }
+
+        /// Read the messages of a reader until EOF, with the options.
+        ///
+        /// With [`ReadOptions::tolerate_length_mismatch`], the frames are read as by
+        /// [`MessageIterator::with_options`](crate::MessageIterator::with_options).
+        pub fn read_all_with<R>(reader: R, options: &ReadOptions) -> Result<Vec<Self>, Error>
+        where
+            R: Read,
+        {
+            crate::MessageIterator::with_options(reader, *options).collect()
+        }
+
+        /// Read a message from a TCP stream, borrowing it for the next read.
+        ///
+        /// The stream is read up to the end of the frame only, so the following messages stay in
+        /// it. Returns the number of consumed bytes and the message.
+        ///
+        /// ```no_run
+        /// use jsonrpc_base::Message;
+        /// use std::net::TcpListener;
+        ///
+        /// let listener = TcpListener::bind("127.0.0.1:9257").unwrap();
+        /// let (mut stream, _) = listener.accept().unwrap();
+        /// while let Ok((_, message)) = Message::try_from_tcp_stream(&mut stream) {
+        ///     println!("{:?}", message);
+        /// }
+        /// ```
+        pub fn try_from_tcp_stream(
+            stream: &mut std::net::TcpStream,
+        ) -> Result<(usize, Self), Error> {
+            Self::try_from_reader(stream)
+        }
+
+        /// Read a message from a reader, reusing the provided buffer for the frame.
+        ///
+        /// Returns the number of consumed bytes and the message.
+        pub fn try_from_reader_buffered<R>(
+            reader: R,
+            buf: &mut Vec<u8>,
+        ) -> Result<(usize, Self), Error>
+        where
+            R: Read,
+        {
+            let span = telemetry::IoSpan::read();
+            let (n, header_len, contents) =
+                helpers::read_frame_into(reader, buf, helpers::MAX_DECOMPRESSED_LEN)?;
+            let message =
+                Message::parse_json(contents).map_err(|e| helpers::in_frame(e, header_len))?;
+            span.finish(&message, n);
             Ok((n, message))
         }
 
+        /// Write the frame of the message to a writer with a vectored write of its header and
+        /// body, and return the number of bytes written.
+        ///
+        /// Partial writes are retried until the frame is complete, so frames written behind a
+        /// lock are never interleaved.
+        pub fn try_to_writer_vectored<W>(&self, writer: W) -> Result<usize, Error>
+        where
+            W: Write,
+        {
+            self.try_to_writer_vectored_buffered(writer, &mut Vec::new())
+        }
+
+        /// Write the frame of the message as [`Message::try_to_writer_vectored`], serializing its
+        /// body into the provided buffer.
+        ///
+        /// The buffer is cleared first, and can be reused across writes.
+        pub fn try_to_writer_vectored_buffered<W>(
+            &self,
+            writer: W,
+            buf: &mut Vec<u8>,
+        ) -> Result<usize, Error>
+        where
+            W: Write,
+        {
+            match self {
+                Message::Request(r) => r.try_to_writer_vectored_buffered(writer, buf),
+                Message::Notification(n) => n.try_to_writer_vectored_buffered(writer, buf),
+                Message::Response(r) => r.try_to_writer_vectored_buffered(writer, buf),
+            }
+        }
+
         /// Write a message to a writer and return the number of bytes written.
         pub fn try_to_writer<W>(&self, mut writer: W) -> Result<usize, Error>
         where
             W: Write,
         {
+            let span = telemetry::IoSpan::write();
             writer
                 .write(self.to_string().as_bytes())
                 .map_err(|e| Error {
                     code: Error::PARSE_ERROR,
                     message: e.to_string(),
-                    data: serde_json::to_value(&self).ok(),
+                    data: serde_json::to_value(self).ok(),
                 })
+                .inspect(|n| span.finish(self, *n))
+        }
+
+        /// Read messages from the reader until EOF, writing each one as transformed by the
+        /// closure, as in [`helpers::pipe_messages`].
+        pub fn pipe_transform<R, W, F>(reader: R, writer: W, transform: F) -> Result<(), Error>
+        where
+            R: Read,
+            W: Write,
+            F: FnMut(Message) -> Message,
+        {
+            helpers::pipe_messages(reader, writer, transform)
+        }
+
+        /// Write the message gzipped, as framed by [`helpers::frame_gzip`], and return the number
+        /// of bytes written.
+        #[cfg(feature = "compression")]
+        pub fn try_to_writer_gzip<W>(&self, mut writer: W) -> Result<usize, Error>
+        where
+            W: Write,
+        {
+            let span = telemetry::IoSpan::write();
+            let frame = helpers::frame_gzip(&self.to_compact_json()?)?;
+            writer
+                .write_all(&frame)
+                .map_err(|e| Error {
+                    code: Error::PARSE_ERROR,
+                    message: e.to_string(),
+                    data: None,
+                })
+                .map(|_| frame.len())
+                .inspect(|n| span.finish(self, *n))
         }
     }
 
@@ -163,4 +1075,120 @@ mod io {
         let (consumed, _message) = Message::try_from_reader(input.as_bytes()).unwrap();
         assert_eq!(consumed, input.len());
     }
+
+    #[test]
+    fn test_try_read_and_skip_n() {
+        let mut stream = Request::new("foo").with_id(1).to_string();
+        let handshake = stream.len();
+        stream.push_str(&Notification::new("bar").to_string());
+        stream.push_str(&Response::ok(1, true).to_string());
+
+        let mut reader = stream.as_bytes();
+        assert_eq!(Message::try_skip_n(&mut reader, 1).unwrap(), handshake);
+        let messages = Message::try_read_n(&mut reader, 2).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].as_notification().is_some());
+        assert!(messages[1].as_response().is_some());
+        assert!(reader.is_empty());
+
+        assert!(Message::try_read_n(stream.as_bytes(), 4).is_err());
+        assert!(Message::try_skip_n(stream.as_bytes(), 4).is_err());
+        assert!(Message::try_read_n(&b""[..], 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_read_all() {
+        use std::io::Cursor;
+
+        let mut stream = Request::new("foo").with_id(1).to_string();
+        stream.push_str(&Notification::new("bar").to_string());
+        stream.push_str(&Response::ok(1, true).to_string());
+
+        let messages = Message::read_all(Cursor::new(stream.as_bytes())).unwrap();
+        assert_eq!(messages.len(), 3);
+        assert!(messages[0].as_request().is_some());
+        assert!(messages[1].as_notification().is_some());
+        assert!(messages[2].as_response().is_some());
+
+        assert!(Message::read_all(Cursor::new(b"")).unwrap().is_empty());
+        let last = Response::ok(1, true).to_string().len();
+        let boundary = Cursor::new(&stream.as_bytes()[..stream.len() - last]);
+        assert_eq!(Message::read_all(boundary).unwrap().len(), 2);
+        // Inside the body and inside the header of the last frame
+        for end in [stream.len() - 1, stream.len() - 55] {
+            let truncated = Cursor::new(&stream.as_bytes()[..end]);
+            assert!(Message::read_all(truncated).is_err());
+        }
+    }
+
+    #[test]
+    fn test_try_from_tcp_stream() {
+        use std::net::{TcpListener, TcpStream};
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (mut stream, _) = listener.accept().unwrap();
+
+        let first = Request::new("first").with_id(1).to_string();
+        let second = Notification::new("second").to_string();
+        client.write_all(first.as_bytes()).unwrap();
+        client.write_all(second.as_bytes()).unwrap();
+
+        let (n, message) = Message::try_from_tcp_stream(&mut stream).unwrap();
+        assert_eq!(n, first.len());
+        assert_eq!(message.as_request().unwrap().method, "first");
+        let (_, message) = Message::try_from_tcp_stream(&mut stream).unwrap();
+        assert_eq!(message.as_notification().unwrap().method, "second");
+    }
+
+    #[test]
+    fn test_try_from_reader_buffered() {
+        let mut stream = Request::new("foo").with_id(1).to_string();
+        stream.push_str(&Notification::new("bar").to_string());
+        stream.push_str(&Response::ok(1, true).to_string());
+
+        let mut reader = stream.as_bytes();
+        let mut buf = Vec::new();
+        let mut consumed = 0;
+        for _ in 0..3 {
+            let (n, message) = Message::try_from_reader_buffered(&mut reader, &mut buf).unwrap();
+            let (m, expected) = Message::try_from_reader(&stream.as_bytes()[consumed..]).unwrap();
+            assert_eq!(n, m);
+            assert_eq!(message.to_string(), expected.to_string());
+            consumed += n;
+        }
+        assert!(reader.is_empty());
+        assert_eq!(consumed, stream.len());
+
+        let (_, request) = Request::try_from_reader_buffered(stream.as_bytes(), &mut buf).unwrap();
+        assert_eq!(request.method, "foo");
+    }
+
+    #[test]
+    fn test_pipe_transform() {
+        let mut stream = Request::new("foo").with_id(1).to_string();
+        stream.push_str(&Notification::new("bar").to_string());
+
+        let mut output = Vec::new();
+        Message::pipe_transform(stream.as_bytes(), &mut output, |m| {
+            m.rewrite_method("proxied")
+        })
+        .unwrap();
+
+        let mut reader = output.as_slice();
+        let (_, request) = Request::try_from_reader(&mut reader).unwrap();
+        assert_eq!(
+            (request.method.as_str(), request.id),
+            ("proxied", Value::from(1))
+        );
+        let (_, notification) = Notification::try_from_reader(&mut reader).unwrap();
+        assert_eq!(notification.method, "proxied");
+        assert!(reader.is_empty());
+
+        stream.push_str("Content-Length: 2\r\n\r\n{]");
+        let mut output = Vec::new();
+        let err = Message::pipe_transform(stream.as_bytes(), &mut output, |m| m).unwrap_err();
+        assert_eq!(err.code, Error::PARSE_ERROR);
+        assert_eq!(output, stream.as_bytes()[..output.len()]);
+    }
 }
@@ -0,0 +1,313 @@
+use super::{Error, Message, Response};
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// Interception point around the messages received and sent by a
+/// [`Connection`](crate::Connection).
+///
+/// Middleware is stacked with [`Connection::with_middleware`](crate::Connection::with_middleware).
+/// Incoming messages run through the stack first-to-last before dispatch, and outgoing messages
+/// run last-to-first before being written, so the first middleware is the outermost.
+pub trait Middleware {
+    /// Inspect or replace a message received by the connection.
+    ///
+    /// Every message read from the peer runs through the hook as it is received, whether it is
+    /// dispatched by [`Connection::serve`](crate::Connection::serve), returned by
+    /// [`Connection::next_incoming`](crate::Connection::next_incoming), or answers a request of
+    /// [`Connection::send_request`](crate::Connection::send_request).
+    ///
+    /// Returning a response short-circuits the rest of the stack and the message: the response
+    /// is sent in reply to a request, and dropped for notifications and responses.
+    fn on_incoming(&mut self, message: Message) -> Result<Message, Box<Response>> {
+        Ok(message)
+    }
+
+    /// Inspect or replace a message sent by the connection.
+    ///
+    /// The ID of a request must be kept, or its response will not be matched.
+    fn on_outgoing(&mut self, message: Message) -> Message {
+        message
+    }
+}
+
+/// Stack of middleware, boxed to be stored by a connection.
+pub(crate) type Stack = alloc::vec::Vec<Box<dyn Middleware + Send>>;
+
+/// Run the message through the incoming hooks of the stack, first-to-last.
+pub(crate) fn incoming(stack: &mut Stack, message: Message) -> Result<Message, Box<Response>> {
+    stack
+        .iter_mut()
+        .try_fold(message, |message, m| m.on_incoming(message))
+}
+
+/// Run the message through the outgoing hooks of the stack, last-to-first.
+pub(crate) fn outgoing(stack: &mut Stack, message: Message) -> Message {
+    stack
+        .iter_mut()
+        .rev()
+        .fold(message, |message, m| m.on_outgoing(message))
+}
+
+/// Middleware rejecting the requests and notifications without a named param, such as an
+/// authentication token.
+///
+/// The params must be an object containing the member; requests without it are answered with an
+/// error of code `-32001`, or the one set with [`RequireParam::with_code`].
+#[derive(Debug, Clone)]
+pub struct RequireParam {
+    name: String,
+    code: i32,
+}
+
+impl RequireParam {
+    /// Require the param with the provided name.
+    pub fn new<N>(name: N) -> Self
+    where
+        N: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            code: -32001,
+        }
+    }
+
+    /// Replace the code of the error of rejected requests
+    pub fn with_code(mut self, code: i32) -> Self {
+        self.code = code;
+        self
+    }
+
+    fn has_param(&self, params: Option<&Value>) -> bool {
+        params.is_some_and(|p| p.get(&self.name).is_some())
+    }
+
+    fn missing(&self, id: Value) -> Response {
+        Response::err(
+            id,
+            Error {
+                code: self.code,
+                message: format!("the param {} is required", self.name),
+                data: Some(Value::String(self.name.clone())),
+            },
+        )
+    }
+}
+
+impl Middleware for RequireParam {
+    fn on_incoming(&mut self, message: Message) -> Result<Message, Box<Response>> {
+        match &message {
+            Message::Request(r) if !self.has_param(r.params.as_ref()) => {
+                Err(Box::new(self.missing(r.id.clone())))
+            }
+            Message::Notification(n) if !self.has_param(n.params.as_ref()) => {
+                Err(Box::new(self.missing(Value::Null)))
+            }
+            _ => Ok(message),
+        }
+    }
+}
+
+/// Middleware reporting the method, ID, and latency of every request answered by the
+/// connection.
+///
+/// The latency runs from the request leaving the incoming stack to its response entering the
+/// outgoing stack.
+pub struct LatencyLog<F> {
+    pending: HashMap<String, (String, Instant)>,
+    sink: F,
+}
+
+impl<F> LatencyLog<F>
+where
+    F: FnMut(&str, &Value, Duration),
+{
+    /// Report every answered request to the sink, as its method, ID, and latency.
+    pub fn new(sink: F) -> Self {
+        Self {
+            pending: HashMap::new(),
+            sink,
+        }
+    }
+}
+
+impl<F> Middleware for LatencyLog<F>
+where
+    F: FnMut(&str, &Value, Duration),
+{
+    fn on_incoming(&mut self, message: Message) -> Result<Message, Box<Response>> {
+        if let Message::Request(r) = &message {
+            self.pending
+                .insert(r.id.to_string(), (r.method.clone(), Instant::now()));
+        }
+        Ok(message)
+    }
+
+    fn on_outgoing(&mut self, message: Message) -> Message {
+        if let Message::Response(r) = &message {
+            if let Some((method, started)) = self.pending.remove(&r.id.to_string()) {
+                (self.sink)(&method, &r.id, started.elapsed());
+            }
+        }
+        message
+    }
+}
+
+#[test]
+fn test_middleware_order() {
+    use super::{
+        test_helpers::{pipe, MockClient},
+        Connection, Request,
+    };
+    use alloc::vec::Vec;
+    use std::{
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    struct Record(&'static str, Arc<Mutex<Vec<String>>>);
+
+    impl Middleware for Record {
+        fn on_incoming(&mut self, message: Message) -> Result<Message, Box<Response>> {
+            self.1.lock().unwrap().push(format!("in {}", self.0));
+            Ok(message.rewrite_method(self.0))
+        }
+
+        fn on_outgoing(&mut self, message: Message) -> Message {
+            self.1.lock().unwrap().push(format!("out {}", self.0));
+            message
+        }
+    }
+
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let (client, server) = pipe();
+    let served = {
+        let log = log.clone();
+        thread::spawn(move || {
+            Connection::new(&server, &server)
+                .with_middleware(Record("a", log.clone()))
+                .with_middleware(Record("b", log))
+                .serve(&|request: Request| Response::ok(request.id, request.method))
+        })
+    };
+
+    let mut client = MockClient::new(client);
+    let response = client.call("foo", ());
+    assert_eq!(response.result(), Some(&Value::from("b")));
+    client.close();
+    served.join().unwrap().unwrap();
+    assert_eq!(*log.lock().unwrap(), ["in a", "in b", "out b", "out a"]);
+}
+
+#[test]
+fn test_middleware_short_circuit() {
+    use super::{
+        test_helpers::{pipe, MockClient},
+        Connection, Request,
+    };
+    use alloc::vec::Vec;
+    use std::{
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    let latencies = Arc::new(Mutex::new(Vec::new()));
+    let sink = latencies.clone();
+    let called = Mutex::new(Vec::new());
+    let (client, server) = pipe();
+
+    thread::scope(|scope| {
+        let served = scope.spawn(|| {
+            Connection::new(&server, &server)
+                .with_middleware(RequireParam::new("token"))
+                .with_middleware(LatencyLog::new(move |method: &str, id: &Value, _| {
+                    sink.lock().unwrap().push((method.to_string(), id.clone()));
+                }))
+                .serve(&|request: Request| {
+                    called.lock().unwrap().push(request.id.clone());
+                    Response::ok(request.id, true)
+                })
+        });
+
+        let mut client = MockClient::new(client);
+        let first = client.call("open", serde_json::json!({"token": "s3cr3t"}));
+        let second = client.call("open", ());
+        client.notify("exit", ());
+        client.close();
+        served.join().unwrap().unwrap();
+
+        assert_eq!(first.result(), Some(&Value::Bool(true)));
+        assert_eq!(second.id, Value::from(2));
+        assert_eq!(second.error().unwrap().code, -32001);
+    });
+
+    assert_eq!(*called.lock().unwrap(), [Value::from(1)]);
+    assert_eq!(
+        *latencies.lock().unwrap(),
+        [("open".to_string(), Value::from(1))]
+    );
+}
+
+#[test]
+fn test_middleware_client_paths() {
+    use super::{test_helpers::pipe, Connection, Notification, Request};
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread,
+    };
+
+    struct DropNoise(Arc<AtomicUsize>);
+
+    impl Middleware for DropNoise {
+        fn on_incoming(&mut self, message: Message) -> Result<Message, Box<Response>> {
+            match &message {
+                Message::Notification(n) if n.method == "noise" => {
+                    Err(Box::new(Response::ok(Value::Null, ())))
+                }
+                Message::Response(_) => {
+                    self.0.fetch_add(1, Ordering::Relaxed);
+                    Ok(message)
+                }
+                _ => Ok(message),
+            }
+        }
+    }
+
+    let (client, server) = pipe();
+    let peer = thread::spawn(move || {
+        Message::try_from_reader(&server).unwrap();
+        let messages: [Message; 4] = [
+            Notification::new("noise").into(),
+            Notification::new("progress").into(),
+            Response::ok(1, "done").into(),
+            Notification::new("noise").into(),
+        ];
+        for message in messages {
+            message.try_to_writer(&server).unwrap();
+        }
+    });
+
+    let responses = Arc::new(AtomicUsize::new(0));
+    let mut connection =
+        Connection::new(&client, &client).with_middleware(DropNoise(responses.clone()));
+    let result = connection.send_request(Request::new("work").with_id(1));
+    assert_eq!(result.unwrap(), Value::from("done"));
+    assert_eq!(responses.load(Ordering::Relaxed), 1);
+    peer.join().unwrap();
+
+    match connection.next_incoming().unwrap() {
+        Some(Message::Notification(n)) => assert_eq!(n.method, "progress"),
+        m => panic!("unexpected message {:?}", m),
+    }
+    assert!(connection.next_incoming().unwrap().is_none());
+}
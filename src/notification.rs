@@ -1,14 +1,18 @@
-use super::{helpers, Error};
-use alloc::string::{String, ToString};
+use super::{helpers, telemetry, Error, JsonRpcVersion, Preserved};
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+    vec::Vec,
+};
 use core::{fmt, str::FromStr};
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
 
 /// JSON-RPC notification
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Notification {
     /// Protocol header
-    pub jsonrpc: String,
+    pub jsonrpc: JsonRpcVersion,
     /// Method name
     pub method: String,
 
@@ -24,13 +28,60 @@ impl Notification {
         M: ToString,
     {
         Notification {
-            jsonrpc: "2.0".to_string(),
+            jsonrpc: JsonRpcVersion::default(),
             method: method.to_string(),
             params: None,
         }
     }
 
-    /// Replace the methods arguments with the provided value
+    /// Create a new notification with the provided method and arguments.
+    ///
+    /// Fails as [`Notification::with_params`].
+    pub fn with<M, P>(method: M, params: P) -> Result<Self, Error>
+    where
+        M: ToString,
+        P: Serialize,
+    {
+        Self::new(method).with_params(params)
+    }
+
+    /// Create a new notification with the provided method and parsed arguments.
+    ///
+    /// The value is not validated, as in [`Notification::with_params_value`].
+    pub fn with_value<M>(method: M, params: Value) -> Self
+    where
+        M: ToString,
+    {
+        Self::new(method).with_params_value(params)
+    }
+
+    /// Compare the method and params with another notification, ignoring the protocol version.
+    pub fn eq_ignore_version(&self, other: &Notification) -> bool {
+        self.method == other.method && self.params == other.params
+    }
+
+    /// Version of the protocol of the notification
+    #[inline]
+    pub fn jsonrpc_version(&self) -> &str {
+        &self.jsonrpc
+    }
+
+    /// Replace the version of the protocol.
+    ///
+    /// The only version compliant with the spec is `"2.0"`, set by the constructors; other
+    /// versions are meant for bridges to JSON-RPC 1.0 peers.
+    pub fn set_jsonrpc_version<V>(mut self, version: V) -> Self
+    where
+        V: ToString,
+    {
+        self.jsonrpc = JsonRpcVersion::from(version.to_string());
+        self
+    }
+
+    /// Replace the methods arguments with the provided value.
+    ///
+    /// Arrays and objects are accepted, and `null` removes the arguments. Any other value fails
+    /// with [`Error::INVALID_PARAMS`], as the specification forbids scalar arguments.
     pub fn with_params<P>(self, params: P) -> Result<Self, Error>
     where
         P: Serialize,
@@ -41,29 +92,285 @@ impl Notification {
                 message: e.to_string(),
                 data: None,
             })
-            .map(|params| self.with_params_value(params))
+            .and_then(|params| self.try_with_params_value(params))
+    }
+
+    /// Replace the methods arguments with the provided value, as in [`Notification::with_params`].
+    ///
+    /// Intended for constant arguments in tests and examples.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value cannot be serialized or is not a valid argument.
+    #[track_caller]
+    pub fn with_params_or_panic<P>(self, params: P) -> Self
+    where
+        P: Serialize,
+    {
+        match self.with_params(params) {
+            Ok(s) => s,
+            Err(e) => panic!("invalid params: {}", e),
+        }
+    }
+
+    /// Replace the methods arguments with the parsed value, validated as in
+    /// [`Notification::with_params`].
+    pub fn try_with_params_value(mut self, params: Value) -> Result<Self, Error> {
+        self.params = helpers::check_params(params)?;
+        Ok(self)
     }
 
-    /// Replace the methods arguments with the parsed value
+    /// Replace the methods arguments with the parsed value.
+    ///
+    /// The value is not validated; prefer [`Notification::try_with_params_value`].
     pub fn with_params_value(mut self, params: Value) -> Self {
         self.params = Some(params);
         self
     }
 
+    /// Method arguments as an array, or an empty slice if they are absent or an object
+    pub fn params_or_empty_array(&self) -> &[Value] {
+        match &self.params {
+            Some(Value::Array(params)) => params,
+            _ => &[],
+        }
+    }
+
+    /// Method arguments as an object, or an empty map if they are absent or an array.
+    ///
+    /// The empty map is returned as owned, as it cannot be borrowed from the notification; it does
+    /// not allocate.
+    pub fn params_or_empty_object(&self) -> Cow<'_, Map<String, Value>> {
+        match &self.params {
+            Some(Value::Object(params)) => Cow::Borrowed(params),
+            _ => Cow::Owned(Map::new()),
+        }
+    }
+
+    /// Deserialize the method arguments into the provided type.
+    ///
+    /// Absent arguments are deserialized from `null`. On failure, the error data contains the
+    /// method and reason.
+    pub fn params_as<T>(&self) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let params = self.params.clone().unwrap_or(Value::Null);
+        serde_json::from_value(params).map_err(|e| Error {
+            code: Error::INVALID_PARAMS,
+            message: e.to_string(),
+            data: Some(serde_json::json!({
+                "method": self.method,
+                "reason": e.to_string(),
+            })),
+        })
+    }
+
+    /// Deserialize the method arguments into the provided type, or return its default value if
+    /// they are absent or `null`.
+    ///
+    /// Fails with [`Error::INVALID_PARAMS`] if the arguments are of a different type.
+    pub fn params_or_default<T>(&self) -> Result<T, Error>
+    where
+        T: DeserializeOwned + Default,
+    {
+        match self.params {
+            None | Some(Value::Null) => Ok(T::default()),
+            Some(_) => self.params_as(),
+        }
+    }
+
+    /// Deserialize the method arguments into the provided type.
+    ///
+    /// Fails with [`Error::INVALID_PARAMS`] if the arguments are absent or of a different type.
+    pub fn params_as_typed<P>(&self) -> Result<P, Error>
+    where
+        P: DeserializeOwned,
+    {
+        match self.params {
+            Some(_) => self.params_as(),
+            None => Err(self.missing_params()),
+        }
+    }
+
+    /// Consume the notification, deserializing its method arguments into the provided type.
+    ///
+    /// Fails with [`Error::INVALID_PARAMS`] if the arguments are absent or of a different type.
+    pub fn try_into_notification_call<P>(self) -> Result<P, Error>
+    where
+        P: DeserializeOwned,
+    {
+        let Some(params) = self.params else {
+            return Err(self.missing_params());
+        };
+
+        serde_json::from_value(params).map_err(|e| Error {
+            code: Error::INVALID_PARAMS,
+            message: e.to_string(),
+            data: Some(serde_json::json!({
+                "method": self.method,
+                "reason": e.to_string(),
+            })),
+        })
+    }
+
+    fn missing_params(&self) -> Error {
+        Error {
+            code: Error::INVALID_PARAMS,
+            message: "the provided notification has no params".to_string(),
+            data: Some(serde_json::json!({
+                "method": self.method,
+            })),
+        }
+    }
+
+    /// Transform the method arguments with the provided function
+    pub fn map_params<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(Option<Value>) -> Option<Value>,
+    {
+        self.params = f(self.params);
+        self
+    }
+
+    /// Transform the method name with the provided function
+    pub fn map_method<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(String) -> String,
+    {
+        self.method = f(self.method);
+        self
+    }
+
     /// Parse a message into the notification
     pub fn parse(s: &str) -> Result<(Self, &str), Error> {
         let (message, remainder) = helpers::get_content_length(s)?;
-        let notification = Notification::parse_json(message)?;
+        let header_len = s.len() - remainder.len() - message.len();
+        let notification =
+            Notification::parse_json(message).map_err(|e| helpers::in_frame(e, header_len))?;
         Ok((notification, remainder))
     }
 
-    /// Parse a notification from the provided JSON
+    /// Append the framed notification to the buffer, returning the number of bytes appended
+    #[cfg(feature = "bytes")]
+    pub fn encode_to(&self, buf: &mut bytes::BytesMut) -> Result<usize, Error> {
+        helpers::encode_to(self, buf)
+    }
+
+    /// Parse the notification framed at the start of the buffer, advancing past its frame.
+    ///
+    /// Returns `None`, leaving the buffer untouched, if the frame is not complete yet. A frame
+    /// with an invalid body is consumed, but not one with an invalid header or one declaring more
+    /// than [`helpers::MAX_FRAME_LEN`] bytes.
+    #[cfg(feature = "bytes")]
+    pub fn decode_from(buf: &mut bytes::BytesMut) -> Result<Option<Self>, Error> {
+        helpers::decode_from(buf, Notification::parse_json)
+    }
+
+    /// Serialize the notification to canonical JSON.
+    ///
+    /// The output is deterministic: object keys are sorted at every level, including inside
+    /// arguments and results, with no whitespace. Numbers are written in the ECMAScript form of
+    /// their value, as in RFC 8785, so `1` and `1.0` canonicalize identically; integers keep
+    /// their digits.
+    pub fn to_json_canonical(&self) -> Result<String, Error> {
+        helpers::to_json_canonical(self)
+    }
+
+    /// Bytes of the canonical JSON of the notification, to be fed into a hasher
+    pub fn canonical_hash(&self) -> Result<Vec<u8>, Error> {
+        self.to_json_canonical().map(String::into_bytes)
+    }
+
+    /// Approximate length of the JSON serialization of the notification, to pre-size buffers.
+    ///
+    /// This is a hint computed without serializing: it is not exact, and not a bound. Use
+    /// [`Notification::json_len`] for the exact length.
+    pub fn serialized_len_hint(&self) -> usize {
+        "{}".len()
+            + helpers::str_member_len("jsonrpc", &self.jsonrpc)
+            + helpers::str_member_len("method", &self.method)
+            + helpers::member_len_hint("params", self.params.as_ref())
+    }
+
+    /// Length of the JSON serialization of the notification, without the frame header
+    pub fn json_len(&self) -> Result<usize, Error> {
+        helpers::json_len(self)
+    }
+
+    /// Length of the frame of the notification, header included.
+    ///
+    /// Equals the length of its `Display` output, computed without building the frame.
+    pub fn encoded_len(&self) -> Result<usize, Error> {
+        self.json_len().map(helpers::frame_len)
+    }
+
+    /// Serialize the notification to compact JSON, without the frame header
+    pub fn to_compact_json(&self) -> Result<String, Error> {
+        helpers::to_json_compact(self)
+    }
+
+    /// Serialize the notification to indented JSON, without the frame header
+    pub fn to_pretty_json(&self) -> Result<String, Error> {
+        helpers::to_json_pretty(self)
+    }
+
+    /// Serialize the notification to its frame, as bytes.
+    ///
+    /// The frame can be written as is to the socket of every subscriber of a broadcast, without
+    /// serializing the notification again for each of them.
+    pub fn to_framed_bytes(&self) -> Result<Vec<u8>, Error> {
+        self.to_compact_json()
+            .map(|json| helpers::frame(&json).into_bytes())
+    }
+
+    /// Serialize the notification to JSON, escaping every non-ASCII character as `\uXXXX`
+    pub fn to_json_ascii(&self) -> Result<String, Error> {
+        helpers::to_json_ascii(self)
+    }
+
+    /// Serialize the notification to a frame with an ASCII-only body.
+    ///
+    /// The `Content-Length` header is computed over the escaped body.
+    pub fn to_string_ascii(&self) -> Result<String, Error> {
+        self.to_json_ascii().map(|m| helpers::frame(&m))
+    }
+
+    /// Serialize the notifications into a single buffer of consecutive frames.
+    ///
+    /// The buffer can be written as is to any number of connections.
+    pub fn serialize_many(notifications: &[Self]) -> Result<Vec<u8>, Error> {
+        let mut buffer = Vec::new();
+        for notification in notifications {
+            let json = serde_json::to_string(notification).map_err(|e| Error {
+                code: Error::PARSE_ERROR,
+                message: e.to_string(),
+                data: serde_json::to_value(notification).ok(),
+            })?;
+            buffer.extend_from_slice(helpers::frame(&json).as_bytes());
+        }
+        Ok(buffer)
+    }
+
+    /// Parse a notification from the provided JSON.
+    ///
+    /// An object with an `id` is a request, and fails with [`Error::INVALID_REQUEST`].
     pub fn parse_json(json: &str) -> Result<Self, Error> {
-        serde_json::from_str(json).map_err(|e| Error {
-            code: Error::INVALID_REQUEST,
-            message: e.to_string(),
-            data: Some(Value::String(json.to_string())),
-        })
+        helpers::parse_json_without(json, "id", "the provided notification contains an id")
+            .inspect(telemetry::parsed_notification)
+            .inspect_err(|e| telemetry::parse_failed(e, json))
+    }
+
+    /// Parse a notification from the provided JSON, keeping its unknown top-level members
+    pub fn parse_json_preserving(json: &str) -> Result<Preserved<Self>, Error> {
+        Preserved::parse_json(json)
+    }
+
+    /// Parse a notification from the provided JSON, rejecting objects that contain an `id`.
+    ///
+    /// Equivalent to [`Notification::parse_json`], which rejects them too.
+    pub fn parse_json_strict(json: &str) -> Result<Self, Error> {
+        Self::parse_json(json)
     }
 }
 
@@ -77,10 +384,122 @@ impl FromStr for Notification {
 
 impl fmt::Display for Notification {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        serde_json::to_string(&self)
-            .map_err(|_| fmt::Error)
-            .and_then(|m| write!(f, "Content-Length: {}\r\n\r\n{}", m.len(), m))
+        helpers::write_framed(f, self)
+    }
+}
+
+#[test]
+fn test_parse_json_strict_rejects_id() {
+    let json = r#"{"jsonrpc":"2.0","id":1,"method":"foo"}"#;
+    let err = Notification::parse_json(json).unwrap_err();
+    assert_eq!(err.code, Error::INVALID_REQUEST);
+    let err = Notification::parse_json_strict(json).unwrap_err();
+    assert_eq!(err.code, Error::INVALID_REQUEST);
+    assert!(Notification::parse(&super::Request::new("foo").to_string()).is_err());
+
+    let json = r#"{"jsonrpc":"2.0","method":"foo"}"#;
+    let notification = Notification::parse_json_strict(json).unwrap();
+    assert_eq!(notification.method, "foo");
+}
+
+#[test]
+fn test_constructors_with_params() {
+    use super::Request;
+    use serde_json::json;
+
+    let notification = Notification::with("foo", ["bar"]).unwrap();
+    assert_eq!(notification.method, "foo");
+    assert_eq!(notification.params, Some(json!(["bar"])));
+    let value = Notification::with_value("foo", json!(["bar"]));
+    assert!(value.eq_ignore_version(&notification));
+    assert_eq!(
+        Notification::with("foo", 1).unwrap_err().code,
+        Error::INVALID_PARAMS
+    );
+
+    let request = Request::with("foo", json!({"bar": 1})).unwrap();
+    assert_eq!(request.params, Some(json!({"bar": 1})));
+    let value = Request::with_value("foo", json!({"bar": 1}));
+    assert_eq!(value.method, request.method);
+    assert_eq!(value.params, request.params);
+    assert!(Request::with("foo", "bar").is_err());
+}
+
+#[test]
+fn test_notification_map_params_and_method() {
+    let notification = Notification::new("foo")
+        .map_method(|m| alloc::format!("v2.{}", m))
+        .map_params(|p| {
+            assert!(p.is_none());
+            Some(serde_json::json!([1]))
+        });
+    assert_eq!(notification.method, "v2.foo");
+    assert_eq!(notification.params, Some(serde_json::json!([1])));
+}
+
+#[test]
+fn test_notification_params_as() {
+    #[derive(Debug, Deserialize)]
+    struct Params {
+        foo: u64,
     }
+
+    let notification = Notification::new("bar");
+    let err = notification.params_as::<Params>().unwrap_err();
+    assert_eq!(err.code, Error::INVALID_PARAMS);
+    let data = err.data.unwrap();
+    assert_eq!(data["method"], "bar");
+    assert!(data["reason"].is_string());
+
+    let notification = notification.with_params_value(serde_json::json!({"foo": 1}));
+    assert_eq!(notification.params_as::<Params>().unwrap().foo, 1);
+}
+
+#[test]
+fn test_notification_params_or_default() {
+    let notification = Notification::new("progress");
+    assert!(notification
+        .params_or_default::<Vec<u8>>()
+        .unwrap()
+        .is_empty());
+
+    let notification = notification.with_params([1]).unwrap();
+    assert_eq!(notification.params_or_default::<Vec<u8>>().unwrap(), [1]);
+
+    let notification = notification.with_params_value(serde_json::json!({"a": 1}));
+    let err = notification.params_or_default::<Vec<u8>>().unwrap_err();
+    assert_eq!(err.code, Error::INVALID_PARAMS);
+}
+
+#[test]
+fn test_notification_params_or_empty() {
+    let notification = Notification::new("progress");
+    assert!(notification.params_or_empty_array().is_empty());
+    assert!(notification.params_or_empty_object().is_empty());
+
+    let notification = notification.with_params_value(serde_json::json!({"done": 1}));
+    assert!(notification.params_or_empty_array().is_empty());
+    assert_eq!(notification.params_or_empty_object()["done"], 1);
+}
+
+#[test]
+fn test_try_into_notification_call() {
+    let notification = Notification::new("progress").with_params([50]).unwrap();
+    assert_eq!(notification.params_as_typed::<[u8; 1]>().unwrap(), [50]);
+    assert_eq!(
+        notification
+            .try_into_notification_call::<[u8; 1]>()
+            .unwrap(),
+        [50]
+    );
+
+    let notification = Notification::new("progress");
+    let err = notification.params_as_typed::<[u8; 1]>().unwrap_err();
+    assert_eq!(err.code, Error::INVALID_PARAMS);
+    let err = notification
+        .try_into_notification_call::<[u8; 1]>()
+        .unwrap_err();
+    assert_eq!(err.code, Error::INVALID_PARAMS);
 }
 
 #[cfg(feature = "std")]
@@ -96,23 +515,177 @@ mod io {
         where
             R: Read,
         {
-            let (n, contents) = helpers::get_content_from_reader(reader)?;
-            let notification = Notification::parse_json(&contents)?;
+            let span = telemetry::IoSpan::read();
+            let (n, header_len, contents) = helpers::read_frame(reader)?;
+            let notification = Notification::parse_json(&contents)
+                .map_err(|e| helpers::in_frame(e, header_len))?;
+            span.finish(&notification, n);
             Ok((n, notification))
         }
 
+        /// Read a notification from a reader, reusing the provided buffer for the frame.
+        ///
+        /// Returns the number of consumed bytes and the notification.
+        pub fn try_from_reader_buffered<R>(
+            reader: R,
+            buf: &mut Vec<u8>,
+        ) -> Result<(usize, Self), Error>
+        where
+            R: Read,
+        {
+            let span = telemetry::IoSpan::read();
+            let (n, header_len, contents) =
+                helpers::read_frame_into(reader, buf, helpers::MAX_DECOMPRESSED_LEN)?;
+            let notification =
+                Notification::parse_json(contents).map_err(|e| helpers::in_frame(e, header_len))?;
+            span.finish(&notification, n);
+            Ok((n, notification))
+        }
+
+        /// Serialize the notifications once and write the frames to every writer.
+        ///
+        /// Returns the number of bytes written to each writer. Stops at the first writer that
+        /// fails.
+        pub fn broadcast_to<I>(notifications: &[Self], writers: I) -> Result<usize, Error>
+        where
+            I: IntoIterator,
+            I::Item: Write,
+        {
+            let buffer = Self::serialize_many(notifications)?;
+            for mut writer in writers {
+                writer.write_all(&buffer).map_err(|e| Error {
+                    code: Error::PARSE_ERROR,
+                    message: e.to_string(),
+                    data: serde_json::to_value(notifications).ok(),
+                })?;
+            }
+            Ok(buffer.len())
+        }
+
+        /// Frame the notification once and write it to every writer.
+        ///
+        /// Returns the number of bytes written to each writer, in order; a failing writer does
+        /// not stop the others.
+        pub fn broadcast<I>(&self, writers: I) -> Vec<Result<usize, Error>>
+        where
+            I: IntoIterator,
+            I::Item: Write,
+        {
+            let frame = self.to_framed_bytes();
+            writers
+                .into_iter()
+                .map(|mut writer| {
+                    let frame = frame.as_ref().map_err(Clone::clone)?;
+                    writer
+                        .write_all(frame)
+                        .map(|_| frame.len())
+                        .map_err(|e| Error {
+                            code: Error::PARSE_ERROR,
+                            message: e.to_string(),
+                            data: serde_json::to_value(self).ok(),
+                        })
+                })
+                .collect()
+        }
+
+        /// Write the frame of the notification to a writer with a vectored write of its header and
+        /// body, and return the number of bytes written.
+        ///
+        /// Partial writes are retried until the frame is complete, so frames written behind a
+        /// lock are never interleaved.
+        pub fn try_to_writer_vectored<W>(&self, writer: W) -> Result<usize, Error>
+        where
+            W: Write,
+        {
+            self.try_to_writer_vectored_buffered(writer, &mut Vec::new())
+        }
+
+        /// Write the frame of the notification as [`Notification::try_to_writer_vectored`],
+        /// serializing its body into the provided buffer.
+        ///
+        /// The buffer is cleared first, and can be reused across writes.
+        pub fn try_to_writer_vectored_buffered<W>(
+            &self,
+            writer: W,
+            buf: &mut Vec<u8>,
+        ) -> Result<usize, Error>
+        where
+            W: Write,
+        {
+            let span = telemetry::IoSpan::write();
+            helpers::write_vectored(self, writer, buf).inspect(|n| span.finish(self, *n))
+        }
+
         /// Write a notification to a writer and return the number of bytes written.
         pub fn try_to_writer<W>(&self, mut writer: W) -> Result<usize, Error>
         where
             W: Write,
         {
+            let span = telemetry::IoSpan::write();
             writer
                 .write(self.to_string().as_bytes())
                 .map_err(|e| Error {
                     code: Error::PARSE_ERROR,
                     message: e.to_string(),
-                    data: serde_json::to_value(&self).ok(),
+                    data: serde_json::to_value(self).ok(),
                 })
+                .inspect(|n| span.finish(self, *n))
         }
     }
+
+    #[test]
+    fn test_broadcast_to() {
+        let notifications = [
+            Notification::new("window/showMessage")
+                .with_params(["hello"])
+                .unwrap(),
+            Notification::new("exit"),
+        ];
+        let expected: String = notifications.iter().map(|n| n.to_string()).collect();
+
+        let mut clients = [Vec::new(), Vec::new(), Vec::new()];
+        let n = Notification::broadcast_to(&notifications, clients.iter_mut()).unwrap();
+        assert_eq!(n, expected.len());
+        for client in &clients {
+            assert_eq!(client, expected.as_bytes());
+        }
+
+        let mut reader = clients[0].as_slice();
+        let (_, first) = Notification::try_from_reader(&mut reader).unwrap();
+        let (_, second) = Notification::try_from_reader(&mut reader).unwrap();
+        assert_eq!(first.method, "window/showMessage");
+        assert_eq!(second.method, "exit");
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn test_broadcast() {
+        struct Closed;
+
+        impl Write for Closed {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::BrokenPipe.into())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let notification = Notification::new("price/update").with_params_value(Value::from(42));
+        let frame = notification.to_framed_bytes().unwrap();
+        assert_eq!(frame, notification.to_string().into_bytes());
+
+        let mut first = Vec::new();
+        let mut last = Vec::new();
+        let writers: [Box<dyn Write>; 3] =
+            [Box::new(&mut first), Box::new(Closed), Box::new(&mut last)];
+        let results = notification.broadcast(writers);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap(), &frame.len());
+        assert_eq!(results[1].as_ref().unwrap_err().code, Error::PARSE_ERROR);
+        assert_eq!(results[2].as_ref().unwrap(), &frame.len());
+        assert_eq!(first, frame);
+        assert_eq!(last, frame);
+    }
 }
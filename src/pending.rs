@@ -0,0 +1,144 @@
+use super::Response;
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use serde_json::Value;
+use std::time::Instant;
+
+/// Correlation map of in-flight requests with deadline-based eviction.
+///
+/// The map never reads a clock on its own: the caller provides the current instant to
+/// [`PendingRequestsTimed::expire`], so it can be driven by any runtime or by plain threads.
+#[derive(Debug, Clone)]
+pub struct PendingRequestsTimed<T> {
+    entries: BTreeMap<String, Pending<T>>,
+}
+
+#[derive(Debug, Clone)]
+struct Pending<T> {
+    id: Value,
+    context: T,
+    deadline: Instant,
+}
+
+impl<T> Default for PendingRequestsTimed<T> {
+    fn default() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> PendingRequestsTimed<T> {
+    /// Create a new empty map
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Track a request ID with its context until the provided deadline.
+    ///
+    /// Returns the context previously associated with the ID, if any.
+    pub fn insert<I>(&mut self, id: I, context: T, deadline: Instant) -> Option<T>
+    where
+        I: Into<Value>,
+    {
+        let id = id.into();
+        self.entries
+            .insert(
+                id.to_string(),
+                Pending {
+                    id,
+                    context,
+                    deadline,
+                },
+            )
+            .map(|p| p.context)
+    }
+
+    /// Remove the entry that originated the response, returning its context.
+    ///
+    /// Returns `None` if the ID is unknown, which includes entries that were already expired.
+    pub fn complete(&mut self, response: &Response) -> Option<T> {
+        self.entries
+            .remove(&response.id.to_string())
+            .map(|p| p.context)
+    }
+
+    /// Stop tracking the provided ID, returning its context.
+    pub fn remove(&mut self, id: &Value) -> Option<T> {
+        self.entries.remove(&id.to_string()).map(|p| p.context)
+    }
+
+    /// Evict all the entries with a deadline at or before `now`, returning their IDs and contexts.
+    pub fn expire(&mut self, now: Instant) -> Vec<(Value, T)> {
+        let mut expired = Vec::new();
+        self.expire_with(now, |id, context| expired.push((id, context)));
+        expired
+    }
+
+    /// Evict all the entries with a deadline at or before `now`, calling `f` for each of them.
+    pub fn expire_with<F>(&mut self, now: Instant, mut f: F)
+    where
+        F: FnMut(Value, T),
+    {
+        let keys: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|(_, p)| p.deadline <= now)
+            .map(|(k, _)| k.clone())
+            .collect();
+
+        for key in keys {
+            if let Some(p) = self.entries.remove(&key) {
+                f(p.id, p.context);
+            }
+        }
+    }
+
+    /// Number of in-flight requests
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if there are no in-flight requests
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[test]
+fn test_pending_complete_racing_expire() {
+    use std::time::Duration;
+
+    let now = Instant::now();
+    let mut pending = PendingRequestsTimed::new();
+    pending.insert(1, "first", now + Duration::from_secs(1));
+    pending.insert("2", "second", now + Duration::from_secs(1));
+
+    assert_eq!(pending.complete(&Response::ok(1, true)), Some("first"));
+
+    let expired = pending.expire(now + Duration::from_secs(1));
+    assert_eq!(expired, vec![(Value::from("2"), "second")]);
+    assert_eq!(pending.complete(&Response::ok("2", true)), None);
+    assert!(pending.is_empty());
+}
+
+#[test]
+fn test_pending_reuse_expired_id() {
+    use std::time::Duration;
+
+    let now = Instant::now();
+    let mut pending = PendingRequestsTimed::new();
+    pending.insert(1, "first", now);
+
+    let mut expired = Vec::new();
+    pending.expire_with(now, |id, context| expired.push((id, context)));
+    assert_eq!(expired, vec![(Value::from(1), "first")]);
+
+    assert_eq!(
+        pending.insert(1, "second", now + Duration::from_secs(1)),
+        None
+    );
+    assert!(pending.expire(now).is_empty());
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending.remove(&Value::from("1")), None);
+    assert_eq!(pending.complete(&Response::ok(1, true)), Some("second"));
+}
@@ -0,0 +1,14 @@
+//! Commonly used types and traits, imported with `use jsonrpc_base::prelude::*`.
+
+pub use crate::{Error, Message, Notification, Request, Response};
+pub use core::{fmt::Display, str::FromStr};
+pub use serde::{Deserialize, Serialize};
+
+#[test]
+fn test_prelude() {
+    use alloc::string::ToString;
+
+    let frame = Request::new("foo").with_id(1).to_string();
+    let message = Message::from_str(&frame).unwrap();
+    assert_eq!(message.to_string(), frame);
+}
@@ -0,0 +1,150 @@
+use super::{helpers, Error, Notification, Request, Response};
+use alloc::string::String;
+use core::fmt;
+use serde::{de::DeserializeOwned, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+/// Message with the unknown top-level members of the object it was parsed from.
+///
+/// The members are attached again when the message is serialized, so extension fields such as
+/// `traceparent` survive a parse and re-serialize. Members named as a field of the message are
+/// never part of `extra`; the comparison is case-sensitive, as in the parsers.
+#[derive(Debug, Clone)]
+pub struct Preserved<T> {
+    /// Parsed message
+    pub message: T,
+    /// Unknown top-level members
+    pub extra: Map<String, Value>,
+}
+
+/// Top-level members of a message type.
+pub trait Fields {
+    /// Names of the members known by the message type
+    const FIELDS: &'static [&'static str];
+
+    /// Members whose presence makes the object another message type, with the reason of the
+    /// rejection
+    const REJECTED: &'static [(&'static str, &'static str)] = &[];
+}
+
+impl Fields for Request {
+    #[cfg(not(feature = "meta"))]
+    const FIELDS: &'static [&'static str] = &["jsonrpc", "id", "method", "params"];
+    #[cfg(feature = "meta")]
+    const FIELDS: &'static [&'static str] = &["jsonrpc", "id", "method", "params", "meta"];
+}
+
+impl Fields for Notification {
+    const FIELDS: &'static [&'static str] = &["jsonrpc", "method", "params"];
+    const REJECTED: &'static [(&'static str, &'static str)] =
+        &[("id", "the provided notification contains an id")];
+}
+
+impl Fields for Response {
+    #[cfg(not(feature = "meta"))]
+    const FIELDS: &'static [&'static str] = &["jsonrpc", "result", "error", "id"];
+    #[cfg(feature = "meta")]
+    const FIELDS: &'static [&'static str] = &["jsonrpc", "result", "error", "id", "meta"];
+    const REJECTED: &'static [(&'static str, &'static str)] =
+        &[("method", "the provided response contains a method")];
+}
+
+impl<T> Preserved<T>
+where
+    T: Fields + DeserializeOwned,
+{
+    /// Parse a message from the provided JSON, keeping its unknown top-level members.
+    ///
+    /// A member the message type does not allow, such as the `id` of a notification or the
+    /// `method` of a response, fails with [`Error::INVALID_REQUEST`], as with its own parser.
+    pub fn parse_json(json: &str) -> Result<Self, Error> {
+        let invalid = |e| helpers::json_error(e, json);
+        for (member, reason) in T::REJECTED {
+            helpers::reject_member(json, member, reason)?;
+        }
+
+        let object: Map<String, Value> = serde_json::from_str(json).map_err(invalid)?;
+        let (known, extra): (Map<String, Value>, _) = object
+            .into_iter()
+            .partition(|(k, _)| T::FIELDS.contains(&k.as_str()));
+        let message = serde_json::from_value(Value::Object(known)).map_err(invalid)?;
+
+        Ok(Self { message, extra })
+    }
+}
+
+impl<T> Serialize for Preserved<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut value = serde_json::to_value(&self.message).map_err(serde::ser::Error::custom)?;
+        if let Value::Object(object) = &mut value {
+            for (k, v) in &self.extra {
+                if !object.contains_key(k) {
+                    object.insert(k.clone(), v.clone());
+                }
+            }
+        }
+        value.serialize(serializer)
+    }
+}
+
+impl<T> fmt::Display for Preserved<T>
+where
+    T: Serialize,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        helpers::write_framed(f, self)
+    }
+}
+
+#[test]
+fn test_preserved_round_trip() {
+    use alloc::string::ToString;
+
+    let json = r#"{"jsonrpc":"2.0","id":1,"method":"foo","traceparent":"00-abc-01"}"#;
+    let preserved = Request::parse_json_preserving(json).unwrap();
+    assert_eq!(preserved.message.method, "foo");
+    assert_eq!(preserved.extra.len(), 1);
+    assert_eq!(preserved.extra["traceparent"], "00-abc-01");
+
+    let framed = preserved.to_string();
+    let (body, _) = helpers::get_content_length(&framed).unwrap();
+    let reparsed: Value = serde_json::from_str(body).unwrap();
+    assert_eq!(reparsed, serde_json::from_str::<Value>(json).unwrap());
+}
+
+#[test]
+fn test_preserved_rejected_members() {
+    let request = r#"{"jsonrpc":"2.0","id":1,"method":"foo"}"#;
+    let err = Notification::parse_json_preserving(request).unwrap_err();
+    assert_eq!(err.code, Error::INVALID_REQUEST);
+    assert_eq!(
+        err.data,
+        Notification::parse_json(request).unwrap_err().data
+    );
+
+    let response = r#"{"jsonrpc":"2.0","id":1,"result":true,"method":"foo"}"#;
+    let err = Response::parse_json_preserving(response).unwrap_err();
+    assert_eq!(err.code, Error::INVALID_REQUEST);
+    assert_eq!(err.data, Response::parse_json(response).unwrap_err().data);
+}
+
+#[test]
+fn test_preserved_case_insensitive_collision() {
+    let json = r#"{"jsonrpc":"2.0","method":"foo","Method":"bar","PARAMS":[1]}"#;
+    let preserved = Notification::parse_json_preserving(json).unwrap();
+    assert_eq!(preserved.message.method, "foo");
+    assert!(preserved.message.params.is_none());
+    assert!(!preserved.extra.contains_key("method"));
+    assert_eq!(preserved.extra["Method"], "bar");
+    assert_eq!(preserved.extra["PARAMS"], serde_json::json!([1]));
+
+    let value = serde_json::to_value(&preserved).unwrap();
+    assert_eq!(value["method"], "foo");
+    assert_eq!(value["Method"], "bar");
+}
@@ -0,0 +1,415 @@
+use super::{
+    decoder::{self, JsonBoundary},
+    helpers, telemetry, Error, LengthMismatch, Message, ReadOptions,
+};
+use alloc::{collections::VecDeque, vec::Vec};
+use std::io::{self, Read};
+
+/// Reader counting the bytes consumed from the wrapped reader.
+///
+/// The offset locates a malformed frame in the stream when reporting diagnostics.
+#[derive(Debug)]
+pub struct ConnectionReader<R> {
+    inner: R,
+    offset: u64,
+}
+
+impl<R> ConnectionReader<R>
+where
+    R: Read,
+{
+    /// Wrap the provided reader, starting at offset `0`.
+    pub fn new(inner: R) -> Self {
+        Self { inner, offset: 0 }
+    }
+
+    /// Total number of bytes read from the wrapped reader
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Reference to the wrapped reader
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Return the wrapped reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Iterate over the messages of the reader.
+    pub fn into_message_iter(self) -> MessageIterator<Self> {
+        MessageIterator::new(self)
+    }
+}
+
+impl<R> Read for ConnectionReader<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.offset += n as u64;
+        Ok(n)
+    }
+}
+
+/// Reader replaying the bytes put back into it before those of the wrapped reader.
+///
+/// With recording, the bytes read for the current frame are kept, to be put back if its read
+/// times out.
+#[derive(Debug)]
+pub(crate) struct Rewind<R> {
+    inner: R,
+    replay: VecDeque<u8>,
+    recorded: Option<Vec<u8>>,
+    timed_out: bool,
+}
+
+impl<R> Rewind<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        Self {
+            inner,
+            replay: VecDeque::new(),
+            recorded: None,
+            timed_out: false,
+        }
+    }
+
+    /// Record the bytes read for each frame.
+    pub(crate) fn with_recording(mut self) -> Self {
+        self.recorded = Some(Vec::new());
+        self
+    }
+
+    /// Start the read of a frame, discarding the bytes recorded for the previous one.
+    pub(crate) fn start_frame(&mut self) {
+        if let Some(recorded) = &mut self.recorded {
+            recorded.clear();
+        }
+        self.timed_out = false;
+    }
+
+    /// Check if the last read of the wrapped reader failed with `WouldBlock` or `TimedOut`
+    pub(crate) fn read_timed_out(&self) -> bool {
+        self.timed_out
+    }
+
+    /// Put back the bytes recorded for the current frame, so its read starts over.
+    pub(crate) fn rewind_frame(&mut self) {
+        if let Some(recorded) = self.recorded.take() {
+            self.unread(&recorded);
+            self.recorded = Some(Vec::new());
+        }
+    }
+
+    pub(crate) fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Return the wrapped reader, dropping the bytes put back.
+    pub(crate) fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Check if the reader is at EOF, putting its next byte back otherwise.
+    pub(crate) fn at_eof(&mut self) -> io::Result<bool>
+    where
+        R: Read,
+    {
+        let mut byte = 0;
+        loop {
+            match self.read(core::slice::from_mut(&mut byte)) {
+                Ok(0) => return Ok(true),
+                Ok(_) => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        self.replay.push_front(byte);
+        Ok(false)
+    }
+
+    /// Put the bytes back, to be read before the others.
+    pub(crate) fn unread(&mut self, bytes: &[u8]) {
+        for b in bytes.iter().rev() {
+            self.replay.push_front(*b);
+        }
+    }
+}
+
+impl<R> Read for Rewind<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = match self.replay.is_empty() {
+            true => self.inner.read(buf).inspect_err(|e| {
+                self.timed_out = matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                )
+            })?,
+            false => self.replay.read(buf)?,
+        };
+        if let Some(recorded) = &mut self.recorded {
+            recorded.extend_from_slice(&buf[..n]);
+        }
+        Ok(n)
+    }
+}
+
+/// Read the next message with the options, returning `None` at EOF.
+///
+/// A frame recovered from a length mismatch is returned with it. The bytes read past the end of
+/// the frame are put back into the reader.
+pub(crate) fn read_message<R>(
+    reader: &mut Rewind<R>,
+    options: &ReadOptions,
+) -> Result<Option<(Message, Option<LengthMismatch>)>, Error>
+where
+    R: Read,
+{
+    let span = telemetry::IoSpan::read();
+    if !options.tolerate_length_mismatch {
+        let Some((n, header_len, contents)) = helpers::try_get_content_from_reader(reader)? else {
+            return Ok(None);
+        };
+        let message =
+            Message::parse_json(&contents).map_err(|e| helpers::in_frame(e, header_len))?;
+        span.finish(&message, n);
+        return Ok(Some((message, None)));
+    }
+
+    if reader.at_eof().map_err(helpers::read_error)? {
+        return Ok(None);
+    }
+    let mut buf = Vec::new();
+    let header = helpers::read_header(&mut *reader, &mut buf)
+        .inspect_err(|e| telemetry::parse_failed(e, ""))?;
+    let header_len = header.len;
+    let (n, message, mismatch) = match header.plain_length() {
+        Some(declared) => read_body_tolerant(reader, header_len, declared)?,
+        None => {
+            let max_len = helpers::MAX_DECOMPRESSED_LEN;
+            let n = helpers::read_body(&mut *reader, &mut buf, header, max_len)
+                .inspect_err(|e| telemetry::parse_failed(e, ""))?;
+            let message = helpers::parse_body(&buf, header_len, Message::parse_json)?;
+            (n, message, None)
+        }
+    };
+    span.finish(&message, n);
+    Ok(Some((message, mismatch)))
+}
+
+/// Read a body of the declared length, recovering it with the actual length of its value.
+///
+/// The body is read a byte at a time while the end of its value is scanned, up to
+/// [`decoder::scan_limit`]. Returns the number of bytes read for the frame, header included.
+fn read_body_tolerant<R>(
+    reader: &mut Rewind<R>,
+    header_len: usize,
+    declared: usize,
+) -> Result<(usize, Message, Option<LengthMismatch>), Error>
+where
+    R: Read,
+{
+    let limit = decoder::scan_limit(declared);
+    let mut body = Vec::new();
+    let mut boundary = JsonBoundary::default();
+    let mut byte = 0;
+    let end = loop {
+        if body.len() >= limit {
+            break None;
+        }
+        match reader.read(core::slice::from_mut(&mut byte)) {
+            Ok(0) => break None,
+            Ok(_) => body.push(byte),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(helpers::read_error(e)),
+        }
+        match boundary.scan(&body) {
+            Ok(None) => (),
+            Ok(Some(end)) => break Some(end),
+            Err(_) => break None,
+        }
+    };
+
+    // The strict read consumes the declared length
+    if body.len() < declared {
+        let missing = declared - body.len();
+        let read = (&mut *reader)
+            .take(missing as u64)
+            .read_to_end(&mut body)
+            .map_err(helpers::read_error)?;
+        if read < missing {
+            return Err(helpers::read_error(io::ErrorKind::UnexpectedEof.into()));
+        }
+    }
+
+    let read = match helpers::parse_body(&body[..declared], header_len, Message::parse_json) {
+        Ok(message) => Ok((message, None)),
+        Err(e) => end
+            .and_then(|actual| decoder::recover_frame(&body, 0, declared, actual))
+            .map(|(message, mismatch)| (message, Some(mismatch)))
+            .ok_or(e),
+    };
+    let body_len = match &read {
+        Ok((_, Some(mismatch))) => mismatch.actual,
+        _ => declared,
+    };
+    reader.unread(&body[body_len..]);
+    read.map(|(message, mismatch)| (header_len + body_len, message, mismatch))
+}
+
+/// Iterator over the messages read from a reader, until EOF.
+///
+/// A malformed frame yields an error; iteration can continue with the next frame.
+#[derive(Debug)]
+pub struct MessageIterator<R> {
+    reader: Rewind<R>,
+    options: ReadOptions,
+    mismatch: Option<LengthMismatch>,
+}
+
+impl<R> MessageIterator<R>
+where
+    R: Read,
+{
+    /// Iterate over the messages of the provided reader.
+    pub fn new(reader: R) -> Self {
+        Self::with_options(reader, ReadOptions::default())
+    }
+
+    /// Iterate over the messages of the provided reader with the options.
+    ///
+    /// With [`ReadOptions::tolerate_length_mismatch`], the bodies are read a byte at a time while
+    /// the end of their value is scanned, so wrap an unbuffered reader in a
+    /// [`BufReader`](std::io::BufReader). The bytes read past a recovered frame are kept for the
+    /// next one.
+    pub fn with_options(reader: R, options: ReadOptions) -> Self {
+        Self {
+            reader: Rewind::new(reader),
+            options,
+            mismatch: None,
+        }
+    }
+
+    /// Take the length mismatch of the last message, if its frame was recovered
+    pub fn take_mismatch(&mut self) -> Option<LengthMismatch> {
+        self.mismatch.take()
+    }
+
+    /// Reference to the reader
+    pub fn get_ref(&self) -> &R {
+        self.reader.get_ref()
+    }
+
+    /// Return the reader.
+    ///
+    /// The bytes read past the last frame recovered from a length mismatch are dropped.
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+}
+
+impl<R> Iterator for MessageIterator<R>
+where
+    R: Read,
+{
+    type Item = Result<Message, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.mismatch = None;
+        let read = read_message(&mut self.reader, &self.options).transpose()?;
+        Some(read.map(|(message, mismatch)| {
+            self.mismatch = mismatch;
+            message
+        }))
+    }
+}
+
+#[test]
+fn test_connection_reader_offset() {
+    use super::{Notification, Request};
+
+    let first = Request::new("foo").with_id(1).to_string();
+    let malformed = "Content-Length: 3\r\n\r\n{x}";
+    let last = Notification::new("bar").to_string();
+    let stream = [first.as_str(), malformed, last.as_str()].concat();
+
+    let mut messages = ConnectionReader::new(stream.as_bytes()).into_message_iter();
+    assert!(matches!(messages.next(), Some(Ok(Message::Request(_)))));
+    assert_eq!(messages.get_ref().offset(), first.len() as u64);
+
+    let err = messages.next().unwrap().unwrap_err();
+    assert_eq!(err.code, Error::PARSE_ERROR);
+    assert_eq!(
+        messages.get_ref().offset(),
+        (first.len() + malformed.len()) as u64
+    );
+
+    assert!(matches!(
+        messages.next(),
+        Some(Ok(Message::Notification(_)))
+    ));
+    assert!(messages.next().is_none());
+    assert_eq!(messages.into_inner().offset(), stream.len() as u64);
+}
+
+#[test]
+fn test_message_iterator_length_mismatch() {
+    use super::{Notification, Request, Response};
+    use alloc::format;
+
+    let body = Request::new("foo").with_id(1).to_compact_json().unwrap();
+    let next = Notification::new("bar").to_string();
+    let tolerant = ReadOptions {
+        tolerate_length_mismatch: true,
+    };
+
+    for declared in [body.len() - 3, body.len() + 3] {
+        let stream = format!("Content-Length: {}\r\n\r\n{}{}", declared, body, next);
+        assert!(Message::read_all(stream.as_bytes()).is_err());
+
+        let mut messages = MessageIterator::with_options(stream.as_bytes(), tolerant);
+        let message = messages.next().unwrap().unwrap();
+        assert_eq!(message.as_request().unwrap().method, "foo");
+        assert_eq!(
+            messages.take_mismatch(),
+            Some(LengthMismatch {
+                declared,
+                actual: body.len()
+            })
+        );
+        assert!(matches!(
+            messages.next(),
+            Some(Ok(Message::Notification(_)))
+        ));
+        assert!(messages.take_mismatch().is_none());
+        assert!(messages.next().is_none());
+    }
+
+    // A value that never closes fails as in strict mode, without losing the next frames
+    let mut stream = format!("Content-Length: 5\r\n\r\n{{\"a\":{}", next);
+    stream.push_str(&Response::ok(1, true).to_string());
+    let mut messages =
+        MessageIterator::with_options(io::BufReader::new(stream.as_bytes()), tolerant);
+    assert_eq!(
+        messages.next().unwrap().unwrap_err().code,
+        Error::PARSE_ERROR
+    );
+    let messages = messages.collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(messages.len(), 2);
+    assert!(messages[0].as_notification().is_some());
+    assert!(messages[1].as_response().is_some());
+
+    let stream = [next.as_str(), next.as_str()].concat();
+    let messages = Message::read_all_with(stream.as_bytes(), &tolerant).unwrap();
+    assert_eq!(messages.len(), 2);
+}
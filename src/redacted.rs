@@ -0,0 +1,118 @@
+use super::{helpers, Outcome, Request, Response};
+use core::fmt;
+use serde_json::Value;
+
+/// View of a request that hides its params, returned by [`Request::redacted`].
+///
+/// Displayed as `{method: foo, id: 1, params: <redacted len=N>}`, where `N` is the length in
+/// bytes of the JSON of the params. Absent params are displayed as `params: none`.
+#[derive(Clone, Copy)]
+pub struct RedactedRequest<'a>(&'a Request);
+
+/// View of a response that hides its result, returned by [`Response::redacted`].
+///
+/// A success is displayed as `{id: 1, result: <redacted len=N>}`, where `N` is the length in
+/// bytes of the JSON of the result. An error is displayed with its code and message, as
+/// `{id: 1, error: -32601 method not found}`; its data is omitted.
+#[derive(Clone, Copy)]
+pub struct RedactedResponse<'a>(&'a Response);
+
+impl Request {
+    /// View of the request that displays its method and ID, but not its params.
+    pub fn redacted(&self) -> RedactedRequest<'_> {
+        RedactedRequest(self)
+    }
+}
+
+impl Response {
+    /// View of the response that displays its ID and error, but not its result.
+    pub fn redacted(&self) -> RedactedResponse<'_> {
+        RedactedResponse(self)
+    }
+}
+
+fn fmt_redacted(f: &mut fmt::Formatter, value: &Value) -> fmt::Result {
+    let len = helpers::json_len(value).map_err(|_| fmt::Error)?;
+    write!(f, "<redacted len={}>", len)
+}
+
+impl fmt::Display for RedactedRequest<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{{method: {}, id: {}, params: ",
+            self.0.method, self.0.id
+        )?;
+        match &self.0.params {
+            Some(params) => fmt_redacted(f, params)?,
+            None => f.write_str("none")?,
+        }
+        f.write_str("}")
+    }
+}
+
+impl fmt::Debug for RedactedRequest<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl fmt::Display for RedactedResponse<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{id: {}, ", self.0.id)?;
+        match &self.0.outcome {
+            Outcome::Success(result) => {
+                f.write_str("result: ")?;
+                fmt_redacted(f, result)?;
+            }
+            Outcome::Failure(error) => write!(f, "error: {} {}", error.code, error.message)?,
+        }
+        f.write_str("}")
+    }
+}
+
+impl fmt::Debug for RedactedResponse<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[test]
+fn test_redacted() {
+    use super::Error;
+    use alloc::{format, string::ToString};
+
+    let request = Request::new("login")
+        .with_id(1)
+        .with_params(["hunter2"])
+        .unwrap();
+    let redacted = request.redacted().to_string();
+    assert_eq!(
+        redacted,
+        "{method: login, id: 1, params: <redacted len=11>}"
+    );
+    assert_eq!(format!("{:?}", request.redacted()), redacted);
+
+    let request = Request::new("ping").with_id_string("a");
+    assert_eq!(
+        request.redacted().to_string(),
+        r#"{method: ping, id: "a", params: none}"#
+    );
+
+    let response = Response::ok(1, "secret token");
+    assert_eq!(
+        response.redacted().to_string(),
+        "{id: 1, result: <redacted len=14>}"
+    );
+
+    let error = Error {
+        code: Error::METHOD_NOT_FOUND,
+        message: "method not found".to_string(),
+        data: Some(Value::from("secret")),
+    };
+    let response = Response::err(1, error);
+    assert_eq!(
+        response.redacted().to_string(),
+        "{id: 1, error: -32601 method not found}"
+    );
+}
@@ -1,14 +1,18 @@
-use super::{helpers, Error};
-use alloc::string::{String, ToString};
+use super::{helpers, id_space, telemetry, Error, IdSpace, JsonRpcVersion, Preserved};
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+    vec::Vec,
+};
 use core::{fmt, str::FromStr};
-use serde::{Deserialize, Serialize};
-use serde_json::{Number, Value};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
 
 /// JSON-RPC request
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Request {
     /// Protocol header
-    pub jsonrpc: String,
+    pub jsonrpc: JsonRpcVersion,
     /// Request ID
     pub id: Value,
     /// Method name
@@ -17,6 +21,11 @@ pub struct Request {
     /// Optional method arguments
     #[serde(skip_serializing_if = "Option::is_none")]
     pub params: Option<Value>,
+
+    /// Optional non-standard metadata, such as deadlines or trace IDs
+    #[cfg(feature = "meta")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meta: Option<Value>,
 }
 
 impl Request {
@@ -35,9 +44,154 @@ impl Request {
 
         Request {
             id,
-            jsonrpc: "2.0".to_string(),
+            jsonrpc: JsonRpcVersion::default(),
             method: method.to_string(),
             params: None,
+            #[cfg(feature = "meta")]
+            meta: None,
+        }
+    }
+
+    /// Create a new request with the provided method and arguments, with an ID generated as in
+    /// [`Request::new`].
+    ///
+    /// Fails as [`Request::with_params`].
+    pub fn with<M, P>(method: M, params: P) -> Result<Self, Error>
+    where
+        M: ToString,
+        P: Serialize,
+    {
+        Self::new(method).with_params(params)
+    }
+
+    /// Create a new request with the provided method and parsed arguments.
+    ///
+    /// The value is not validated, as in [`Request::with_params_value`].
+    pub fn with_value<M>(method: M, params: Value) -> Self
+    where
+        M: ToString,
+    {
+        Self::new(method).with_params_value(params)
+    }
+
+    /// Clone the request with a newly generated random ID, to retry it.
+    ///
+    /// The protocol version, params and metadata are kept, so deadlines and trace IDs follow the
+    /// retry. If the original request is tracked in a `PendingRequestsTimed`, its ID should be
+    /// dropped with `PendingRequestsTimed::remove` and the reissued request inserted in its place.
+    #[cfg(feature = "uuid")]
+    pub fn reissue(&self) -> Request {
+        Request {
+            id: Value::String(uuid::Uuid::new_v4().to_string()),
+            ..self.clone()
+        }
+    }
+
+    /// Clone the request with the next ID of the provided namespace, to retry it.
+    ///
+    /// The protocol version, params and metadata are kept, as with `reissue`; the ID is taken
+    /// from [`IdSpace::next_id`], so it is available without the feature `uuid`.
+    pub fn reissue_in(&self, space: &mut IdSpace) -> Request {
+        space.assign(self.clone())
+    }
+
+    /// Compare the ID, method and params with another request, ignoring the protocol version.
+    pub fn eq_ignore_version(&self, other: &Request) -> bool {
+        self.id == other.id && self.method == other.method && self.params == other.params
+    }
+
+    /// Version of the protocol of the request
+    #[inline]
+    pub fn jsonrpc_version(&self) -> &str {
+        &self.jsonrpc
+    }
+
+    /// Replace the version of the protocol.
+    ///
+    /// The only version compliant with the spec is `"2.0"`, set by the constructors; other
+    /// versions are meant for bridges to JSON-RPC 1.0 peers.
+    pub fn set_jsonrpc_version<V>(mut self, version: V) -> Self
+    where
+        V: ToString,
+    {
+        self.jsonrpc = JsonRpcVersion::from(version.to_string());
+        self
+    }
+
+    /// Method name
+    #[inline]
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// ID of the request
+    #[inline]
+    pub fn id(&self) -> &Value {
+        &self.id
+    }
+
+    /// ID of the request, as a borrowed JSON value
+    #[inline]
+    pub fn id_value(&self) -> &Value {
+        &self.id
+    }
+
+    /// Replace the ID of the request with the provided value
+    pub fn set_id<I>(mut self, id: I) -> Self
+    where
+        I: Into<Value>,
+    {
+        self.id = id.into();
+        self
+    }
+
+    /// ID of the request if it is a number representable as `u64`
+    pub fn id_as_u64(&self) -> Option<u64> {
+        self.id.as_u64()
+    }
+
+    /// ID of the request if it is a number representable as `i64`
+    pub fn id_as_i64(&self) -> Option<i64> {
+        self.id.as_i64()
+    }
+
+    /// ID of the request if it is a number, without converting it to a primitive.
+    ///
+    /// With the `arbitrary-precision` feature, the number keeps the exact digits of the JSON.
+    pub fn id_as_raw_number(&self) -> Option<&Number> {
+        match &self.id {
+            Value::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// ID of the request if it is a string
+    pub fn id_as_str(&self) -> Option<&str> {
+        self.id.as_str()
+    }
+
+    /// Method arguments, if any
+    #[inline]
+    pub fn params(&self) -> Option<&Value> {
+        self.params.as_ref()
+    }
+
+    /// Method arguments as an array, or an empty slice if they are absent or an object
+    pub fn params_or_empty_array(&self) -> &[Value] {
+        match &self.params {
+            Some(Value::Array(params)) => params,
+            _ => &[],
+        }
+    }
+
+    /// Method arguments as an object, or an empty map if they are absent or an array.
+    ///
+    /// The empty map is returned as owned, as it cannot be borrowed from the request; it does not
+    /// allocate.
+    pub fn params_or_empty_object(&self) -> Cow<'_, Map<String, Value>> {
+        match &self.params {
+            Some(Value::Object(params)) => Cow::Borrowed(params),
+            _ => Cow::Owned(Map::new()),
         }
     }
 
@@ -50,6 +204,18 @@ impl Request {
         self
     }
 
+    /// Replace the method ID with `{prefix}-{n}`.
+    ///
+    /// See [`IdSpace`](crate::IdSpace) for keeping the IDs of both directions of a connection
+    /// disjoint.
+    pub fn with_namespaced_id<P>(mut self, prefix: P, n: u64) -> Self
+    where
+        P: AsRef<str>,
+    {
+        self.id = id_space::namespaced_id(prefix.as_ref(), n);
+        self
+    }
+
     /// Replace the method ID with the provided string
     pub fn with_id_string<I>(mut self, id: I) -> Self
     where
@@ -59,7 +225,10 @@ impl Request {
         self
     }
 
-    /// Replace the methods arguments with the provided value
+    /// Replace the methods arguments with the provided value.
+    ///
+    /// Arrays and objects are accepted, and `null` removes the arguments. Any other value fails
+    /// with [`Error::INVALID_PARAMS`], as the specification forbids scalar arguments.
     pub fn with_params<P>(self, params: P) -> Result<Self, Error>
     where
         P: Serialize,
@@ -70,36 +239,332 @@ impl Request {
                 message: e.to_string(),
                 data: None,
             })
-            .map(|params| self.with_params_value(params))
+            .and_then(|params| self.try_with_params_value(params))
     }
 
-    /// Replace the methods arguments with the parsed value
+    /// Replace the methods arguments with the provided value, as in [`Request::with_params`].
+    ///
+    /// Intended for constant arguments in tests and examples.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value cannot be serialized or is not a valid argument.
+    #[track_caller]
+    pub fn with_params_or_panic<P>(self, params: P) -> Self
+    where
+        P: Serialize,
+    {
+        match self.with_params(params) {
+            Ok(s) => s,
+            Err(e) => panic!("invalid params: {}", e),
+        }
+    }
+
+    /// Replace the methods arguments with the parsed value, validated as in
+    /// [`Request::with_params`].
+    pub fn try_with_params_value(mut self, params: Value) -> Result<Self, Error> {
+        self.params = helpers::check_params(params)?;
+        Ok(self)
+    }
+
+    /// Replace the methods arguments with the parsed value.
+    ///
+    /// The value is not validated; prefer [`Request::try_with_params_value`].
     pub fn with_params_value(mut self, params: Value) -> Self {
         self.params = Some(params);
         self
     }
 
+    /// Optional non-standard metadata of the request
+    #[cfg(feature = "meta")]
+    pub fn meta(&self) -> Option<&Value> {
+        self.meta.as_ref()
+    }
+
+    /// Replace the non-standard metadata with the provided value
+    #[cfg(feature = "meta")]
+    pub fn with_meta(mut self, meta: Value) -> Self {
+        self.meta = Some(meta);
+        self
+    }
+
+    /// Deserialize the method arguments into the provided type.
+    ///
+    /// Absent arguments are deserialized from `null`. On failure, the error data contains the
+    /// method, ID, and reason.
+    pub fn params_as<T>(&self) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let params = self.params.clone().unwrap_or(Value::Null);
+        serde_json::from_value(params).map_err(|e| Error {
+            code: Error::INVALID_PARAMS,
+            message: e.to_string(),
+            data: Some(serde_json::json!({
+                "method": self.method,
+                "id": self.id,
+                "reason": e.to_string(),
+            })),
+        })
+    }
+
+    /// Deserialize the method arguments into the provided type, or return its default value if
+    /// they are absent or `null`.
+    ///
+    /// Fails with [`Error::INVALID_PARAMS`] if the arguments are of a different type.
+    pub fn params_or_default<T>(&self) -> Result<T, Error>
+    where
+        T: DeserializeOwned + Default,
+    {
+        match self.params {
+            None | Some(Value::Null) => Ok(T::default()),
+            Some(_) => self.params_as(),
+        }
+    }
+
+    /// Deserialize the method arguments into the provided type.
+    ///
+    /// Fails with [`Error::INVALID_PARAMS`] if the arguments are absent or of a different type.
+    pub fn params_as_typed<P>(&self) -> Result<P, Error>
+    where
+        P: DeserializeOwned,
+    {
+        match self.params {
+            Some(_) => self.params_as(),
+            None => Err(self.missing_params()),
+        }
+    }
+
+    /// Deserialize the positional method arguments into the provided tuple.
+    ///
+    /// Unlike [`Request::params_as`], the arguments must be an array, of the arity of the tuple.
+    /// Fails with [`Error::INVALID_PARAMS`] otherwise; the error data contains the method, the
+    /// `expected` and `actual` arity, and the reason.
+    pub fn positional_as<T>(&self) -> Result<T, Error>
+    where
+        T: DeserializeOwned,
+    {
+        let params = match &self.params {
+            Some(Value::Array(params)) => params,
+            _ => {
+                return Err(Error {
+                    code: Error::INVALID_PARAMS,
+                    message: "the params must be an array of positional arguments".to_string(),
+                    data: Some(serde_json::json!({
+                        "method": self.method,
+                        "id": self.id,
+                        "expected": "array",
+                        "actual": self.params,
+                    })),
+                })
+            }
+        };
+
+        helpers::positional_params(params).map_err(|(e, expected)| Error {
+            code: Error::INVALID_PARAMS,
+            message: e.to_string(),
+            data: Some(serde_json::json!({
+                "method": self.method,
+                "id": self.id,
+                "expected": expected,
+                "actual": params.len(),
+                "reason": e.to_string(),
+            })),
+        })
+    }
+
+    /// Split the request into its ID and method arguments deserialized into the provided type.
+    ///
+    /// Fails with [`Error::INVALID_PARAMS`] if the arguments are absent or of a different type.
+    pub fn try_into_call<P>(self) -> Result<(Value, P), Error>
+    where
+        P: DeserializeOwned,
+    {
+        let Some(params) = self.params else {
+            return Err(self.missing_params());
+        };
+
+        match serde_json::from_value(params) {
+            Ok(params) => Ok((self.id, params)),
+            Err(e) => Err(Error {
+                code: Error::INVALID_PARAMS,
+                message: e.to_string(),
+                data: Some(serde_json::json!({
+                    "method": self.method,
+                    "id": self.id,
+                    "reason": e.to_string(),
+                })),
+            }),
+        }
+    }
+
+    fn missing_params(&self) -> Error {
+        Error {
+            code: Error::INVALID_PARAMS,
+            message: "the provided request has no params".to_string(),
+            data: Some(serde_json::json!({
+                "method": self.method,
+                "id": self.id,
+            })),
+        }
+    }
+
+    /// Transform the method arguments with the provided function
+    pub fn map_params<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(Option<Value>) -> Option<Value>,
+    {
+        self.params = f(self.params);
+        self
+    }
+
+    /// Transform the method name with the provided function
+    pub fn map_method<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(String) -> String,
+    {
+        self.method = f(self.method);
+        self
+    }
+
     /// Split the request into its ID and message
+    ///
+    /// Superseded by [`Request::prepare_ref`], which neither clones the ID nor builds the frame
+    /// until it is written.
     pub fn prepare(&self) -> (Value, String) {
         let id = self.id.clone();
         let message = self.to_string();
         (id, message)
     }
 
+    /// Borrow the ID of the request with its frame, serialized only when displayed.
+    ///
+    /// Writing the frame with `write!` produces the same bytes as [`Request::prepare`]; with the
+    /// feature `std`, they are serialized straight into the writer.
+    pub fn prepare_ref(&self) -> (&Value, impl fmt::Display + '_) {
+        (&self.id, self)
+    }
+
+    /// Split the request into its ID, method and params, without cloning them.
+    ///
+    /// Supersedes cloning the fields of a request that is dropped afterwards.
+    pub fn into_parts(self) -> (Value, String, Option<Value>) {
+        (self.id, self.method, self.params)
+    }
+
     /// Parse a message into the request, returning the remainder string
     pub fn parse(s: &str) -> Result<(Self, &str), Error> {
         let (message, remainder) = helpers::get_content_length(s)?;
-        let request = Request::parse_json(message)?;
+        let header_len = s.len() - remainder.len() - message.len();
+        let request = Request::parse_json(message).map_err(|e| helpers::in_frame(e, header_len))?;
         Ok((request, remainder))
     }
 
+    /// Append the framed request to the buffer, returning the number of bytes appended
+    #[cfg(feature = "bytes")]
+    pub fn encode_to(&self, buf: &mut bytes::BytesMut) -> Result<usize, Error> {
+        helpers::encode_to(self, buf)
+    }
+
+    /// Parse the request framed at the start of the buffer, advancing past its frame.
+    ///
+    /// Returns `None`, leaving the buffer untouched, if the frame is not complete yet. A frame
+    /// with an invalid body is consumed, but not one with an invalid header or one declaring more
+    /// than [`helpers::MAX_FRAME_LEN`] bytes.
+    #[cfg(feature = "bytes")]
+    pub fn decode_from(buf: &mut bytes::BytesMut) -> Result<Option<Self>, Error> {
+        helpers::decode_from(buf, Request::parse_json)
+    }
+
+    /// Serialize the request to canonical JSON.
+    ///
+    /// The output is deterministic: object keys are sorted at every level, including inside
+    /// arguments and results, with no whitespace. Numbers are written in the ECMAScript form of
+    /// their value, as in RFC 8785, so `1` and `1.0` canonicalize identically; integers keep
+    /// their digits.
+    pub fn to_json_canonical(&self) -> Result<String, Error> {
+        helpers::to_json_canonical(self)
+    }
+
+    /// Bytes of the canonical JSON of the request, to be fed into a hasher
+    pub fn canonical_hash(&self) -> Result<Vec<u8>, Error> {
+        self.to_json_canonical().map(String::into_bytes)
+    }
+
+    /// Deterministic bytes of the request, for deduplication and caching.
+    ///
+    /// Requests differing only in key order or whitespace canonicalize identically. The bytes are
+    /// the canonical JSON of [`Request::to_json_canonical`]; they are meant as a hash or cache key,
+    /// not for transmission, for which the `Display` frame preserves the original key order.
+    pub fn canonicalize(&self) -> Result<Vec<u8>, Error> {
+        self.canonical_hash()
+    }
+
+    /// Approximate length of the JSON serialization of the request, to pre-size buffers.
+    ///
+    /// This is a hint computed without serializing: it is not exact, and not a bound. Use
+    /// [`Request::json_len`] for the exact length.
+    pub fn serialized_len_hint(&self) -> usize {
+        let hint = "{}".len()
+            + helpers::str_member_len("jsonrpc", &self.jsonrpc)
+            + helpers::member_len_hint("id", Some(&self.id))
+            + helpers::str_member_len("method", &self.method)
+            + helpers::member_len_hint("params", self.params.as_ref());
+        #[cfg(feature = "meta")]
+        let hint = hint + helpers::member_len_hint("meta", self.meta.as_ref());
+        hint
+    }
+
+    /// Length of the JSON serialization of the request, without the frame header
+    pub fn json_len(&self) -> Result<usize, Error> {
+        helpers::json_len(self)
+    }
+
+    /// Length of the frame of the request, header included.
+    ///
+    /// Equals the length of its `Display` output, computed without building the frame.
+    pub fn encoded_len(&self) -> Result<usize, Error> {
+        self.json_len().map(helpers::frame_len)
+    }
+
+    /// Serialize the request to compact JSON, without the frame header
+    pub fn to_compact_json(&self) -> Result<String, Error> {
+        helpers::to_json_compact(self)
+    }
+
+    /// Serialize the request to indented JSON, without the frame header
+    pub fn to_pretty_json(&self) -> Result<String, Error> {
+        helpers::to_json_pretty(self)
+    }
+
+    /// Serialize the request to JSON, escaping every non-ASCII character as `\uXXXX`
+    pub fn to_json_ascii(&self) -> Result<String, Error> {
+        helpers::to_json_ascii(self)
+    }
+
+    /// Serialize the request to a frame with an ASCII-only body.
+    ///
+    /// The `Content-Length` header is computed over the escaped body.
+    pub fn to_string_ascii(&self) -> Result<String, Error> {
+        self.to_json_ascii().map(|m| helpers::frame(&m))
+    }
+
     /// Parse a request from the provided JSON
     pub fn parse_json(json: &str) -> Result<Self, Error> {
-        serde_json::from_str(json).map_err(|e| Error {
-            code: Error::INVALID_REQUEST,
-            message: e.to_string(),
-            data: Some(Value::String(json.to_string())),
-        })
+        serde_json::from_str(json)
+            .map_err(|e| helpers::json_error(e, json))
+            .inspect(telemetry::parsed_request)
+            .inspect_err(|e| telemetry::parse_failed(e, json))
+    }
+
+    /// Parse a request from the provided JSON, keeping its unknown top-level members
+    pub fn parse_json_preserving(json: &str) -> Result<Preserved<Self>, Error> {
+        Preserved::parse_json(json)
+    }
+
+    /// Parse a request from the provided JSON, rejecting the non-standard `meta` member
+    pub fn parse_json_strict(json: &str) -> Result<Self, Error> {
+        helpers::parse_json_without(json, "meta", "the provided request contains metadata")
     }
 }
 
@@ -113,10 +578,387 @@ impl FromStr for Request {
 
 impl fmt::Display for Request {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        serde_json::to_string(&self)
-            .map_err(|_| fmt::Error)
-            .and_then(|m| write!(f, "Content-Length: {}\r\n\r\n{}", m.len(), m))
+        helpers::write_framed(f, self)
+    }
+}
+
+#[test]
+fn test_request_map_params_and_method() {
+    let request = Request::new("foo")
+        .map_method(|m| alloc::format!("v2.{}", m))
+        .map_params(|p| {
+            assert!(p.is_none());
+            Some(serde_json::json!([1]))
+        });
+    assert_eq!(request.method, "v2.foo");
+    assert_eq!(request.params, Some(serde_json::json!([1])));
+}
+
+#[test]
+fn test_request_prepare_ref() {
+    use alloc::string::ToString;
+    use core::fmt::Write;
+
+    let request = Request::new("foo")
+        .with_id(1)
+        .with_params_value(serde_json::json!([1, 2]));
+    let (id, message) = request.prepare();
+
+    let (id_ref, frame) = request.prepare_ref();
+    assert_eq!(id_ref, &id);
+    let mut written = String::new();
+    write!(written, "{}", frame).unwrap();
+    assert_eq!(written, message);
+
+    let (id, method, params) = request.clone().into_parts();
+    let rebuilt = Request {
+        id,
+        method,
+        params,
+        ..request.clone()
+    };
+    assert_eq!(rebuilt.to_string(), message);
+}
+
+#[test]
+fn test_reissue() {
+    let mut ids = IdSpace::new("c");
+    let request = ids.assign(Request::new("foo").with_params(["bar"]).unwrap());
+    let reissued = request.reissue_in(&mut ids);
+    assert_ne!(request.id, reissued.id);
+    assert_eq!(reissued.id, Value::from("c-1"));
+    assert_eq!(request.jsonrpc, reissued.jsonrpc);
+    assert_eq!(request.method, reissued.method);
+    assert_eq!(request.params, reissued.params);
+
+    #[cfg(feature = "uuid")]
+    {
+        let reissued = request.reissue();
+        assert_ne!(request.id, reissued.id);
+        assert_ne!(reissued.id, request.reissue().id);
+        assert_eq!(request.method, reissued.method);
+        assert_eq!(request.params, reissued.params);
+    }
+
+    #[cfg(feature = "meta")]
+    {
+        let request = request.with_meta(serde_json::json!({"trace": "abc"}));
+        assert_eq!(request.reissue_in(&mut ids).meta(), request.meta());
+    }
+}
+
+#[test]
+fn test_request_id_value() {
+    let request = Request::new("foo").set_id("abc");
+    assert_eq!(request.id_value(), request.id());
+    assert_eq!(request.id_value(), "abc");
+    let request = request.set_id(Value::Null);
+    assert!(request.id_value().is_null());
+}
+
+#[test]
+fn test_request_id_as() {
+    let request = Request::new("foo").with_id(7);
+    assert_eq!(request.id_as_u64(), Some(7));
+    assert_eq!(request.id_as_i64(), Some(7));
+    assert_eq!(request.id_as_str(), None);
+
+    let request = request.with_id(-1);
+    assert_eq!(request.id_as_u64(), None);
+    assert_eq!(request.id_as_i64(), Some(-1));
+
+    let request = request.with_id_string("abc");
+    assert_eq!(request.id_as_u64(), None);
+    assert_eq!(request.id_as_str(), Some("abc"));
+}
+
+#[cfg(feature = "arbitrary-precision")]
+#[test]
+fn test_request_arbitrary_precision() {
+    use super::Message;
+
+    let big = "1234567890123456789012345678901234567890";
+    let json = alloc::format!(
+        r#"{{"jsonrpc":"2.0","id":{big},"method":"transfer","params":{{"amount":{big}}}}}"#
+    );
+    let request = Request::parse_json(&json).unwrap();
+    assert_eq!(request.id_as_raw_number().unwrap().to_string(), big);
+    assert_eq!(request.id_as_u64(), None);
+    assert_eq!(request.params_or_empty_object()["amount"].to_string(), big);
+    assert_eq!(request.to_compact_json().unwrap(), json);
+    assert!(request.to_json_canonical().unwrap().contains(big));
+
+    let framed = request.to_string();
+    let (message, _) = Message::parse(&framed).unwrap();
+    assert_eq!(message.to_string(), framed);
+
+    let id: Number = big.parse().unwrap();
+    let request = Request::new("transfer")
+        .with_id(id)
+        .with_params_value(request.params.unwrap());
+    assert_eq!(request.to_compact_json().unwrap(), json);
+}
+
+#[test]
+fn test_jsonrpc_version() {
+    use super::{Notification, Response};
+
+    let request = Request::new("foo").with_id(1);
+    assert_eq!(request.jsonrpc_version(), "2.0");
+    let request = request.set_jsonrpc_version("1.0");
+    assert_eq!(request.jsonrpc_version(), "1.0");
+    assert!(request.to_string().contains(r#""jsonrpc":"1.0""#));
+
+    let notification = Notification::new("foo").set_jsonrpc_version(String::from("1.0"));
+    assert_eq!(notification.jsonrpc_version(), "1.0");
+    let response = Response::ok(1, true).set_jsonrpc_version("1.0");
+    assert_eq!(response.jsonrpc_version(), "1.0");
+}
+
+#[test]
+fn test_eq_ignore_version() {
+    use super::{Error, Notification, Response};
+
+    let request = Request::new("foo").with_id(1);
+    assert!(request.eq_ignore_version(&request.clone().set_jsonrpc_version("1.0")));
+    assert!(!request.eq_ignore_version(&request.clone().with_id(2)));
+    assert!(!request.eq_ignore_version(&request.clone().with_params(["bar"]).unwrap()));
+
+    let notification = Notification::new("foo");
+    assert!(notification.eq_ignore_version(&notification.clone().set_jsonrpc_version("1.0")));
+    assert!(!notification.eq_ignore_version(&Notification::new("bar")));
+
+    let response = Response::ok(1, true);
+    assert!(response.eq_ignore_version(&response.clone().set_jsonrpc_version("1.0")));
+    assert!(!response.eq_ignore_version(&Response::ok(1, false)));
+    let err = Response::err(
+        1,
+        Error {
+            code: Error::INTERNAL_ERROR,
+            message: "boom".to_string(),
+            data: None,
+        },
+    );
+    assert!(err.eq_ignore_version(&err.clone()));
+    assert!(!response.eq_ignore_version(&err));
+}
+
+#[test]
+fn test_request_key_order() {
+    let json = r#"{"jsonrpc":"2.0","id":1,"method":"foo","params":{"b":1,"a":{"d":2,"c":3}}}"#;
+    let request = Request::parse_json(json).unwrap();
+    let canonical = r#"{"id":1,"jsonrpc":"2.0","method":"foo","params":{"a":{"c":3,"d":2},"b":1}}"#;
+    assert_eq!(request.to_json_canonical().unwrap(), canonical);
+
+    #[cfg(feature = "preserve-order")]
+    assert_eq!(request.to_compact_json().unwrap(), json);
+    #[cfg(not(feature = "preserve-order"))]
+    assert_eq!(
+        request.to_compact_json().unwrap(),
+        r#"{"jsonrpc":"2.0","id":1,"method":"foo","params":{"a":{"c":3,"d":2},"b":1}}"#
+    );
+}
+
+#[test]
+fn test_request_canonicalize() {
+    let a = Request::parse_json(
+        r#"{"jsonrpc":"2.0","id":1,"method":"foo","params":{"b":[1,{"d":2,"c":3}],"a":null}}"#,
+    )
+    .unwrap();
+    let b = Request::parse_json(concat!(
+        r#"{ "params": { "a": null, "b": [1, {"c": 3, "d": 2}] }, "#,
+        r#""method": "foo", "id": 1, "jsonrpc": "2.0" }"#
+    ))
+    .unwrap();
+    assert_eq!(a.canonicalize().unwrap(), b.canonicalize().unwrap());
+    assert_eq!(
+        a.canonicalize().unwrap(),
+        br#"{"id":1,"jsonrpc":"2.0","method":"foo","params":{"a":null,"b":[1,{"c":3,"d":2}]}}"#
+    );
+
+    let c = a.clone().with_params([1]).unwrap();
+    assert_ne!(a.canonicalize().unwrap(), c.canonicalize().unwrap());
+}
+
+#[test]
+fn test_request_params_as() {
+    #[derive(Debug, Deserialize)]
+    struct Params {
+        foo: u64,
     }
+
+    let request = Request::new("bar").with_id(3);
+    let err = request.params_as::<Params>().unwrap_err();
+    assert_eq!(err.code, Error::INVALID_PARAMS);
+    let data = err.data.unwrap();
+    assert_eq!(data["method"], "bar");
+    assert_eq!(data["id"], 3);
+    assert!(data["reason"].is_string());
+
+    let request = request.with_params_value(serde_json::json!({"foo": 1}));
+    assert_eq!(request.params_as::<Params>().unwrap().foo, 1);
+}
+
+#[test]
+fn test_request_parse_json_requires_method() {
+    let json = r#"{"jsonrpc":"2.0","id":1,"result":true}"#;
+    let err = Request::parse_json(json).unwrap_err();
+    assert_eq!(err.code, Error::INVALID_REQUEST);
+}
+
+#[test]
+fn test_request_parse_json_strict() {
+    let json = r#"{"jsonrpc":"2.0","id":1,"method":"foo","meta":{"deadline":10}}"#;
+    assert!(Request::parse_json(json).is_ok());
+    assert_eq!(
+        Request::parse_json_strict(json).unwrap_err().code,
+        Error::INVALID_REQUEST
+    );
+
+    let json = r#"{"jsonrpc":"2.0","id":1,"method":"foo"}"#;
+    assert!(Request::parse_json_strict(json).is_ok());
+}
+
+#[cfg(feature = "meta")]
+#[test]
+fn test_request_meta() {
+    let request = Request::new("foo").with_id(1);
+    assert!(request.meta().is_none());
+    assert!(!request.to_string().contains("meta"));
+
+    let request = request.with_meta(serde_json::json!({"trace": "abc"}));
+    let json = serde_json::to_string(&request).unwrap();
+    let parsed = Request::parse_json(&json).unwrap();
+    assert_eq!(parsed.meta(), Some(&serde_json::json!({"trace": "abc"})));
+}
+
+#[test]
+fn test_try_into_call() {
+    let request = Request::new("add").with_id(4).with_params([1, 2]).unwrap();
+    let (id, (a, b)) = request.try_into_call::<(u8, u8)>().unwrap();
+    assert_eq!(id, Value::from(4));
+    assert_eq!(a + b, 3);
+
+    let err = Request::new("add").try_into_call::<(u8, u8)>().unwrap_err();
+    assert_eq!(err.code, Error::INVALID_PARAMS);
+
+    let request = Request::new("add").with_params(["a"]).unwrap();
+    let err = request.try_into_call::<(u8, u8)>().unwrap_err();
+    assert_eq!(err.code, Error::INVALID_PARAMS);
+}
+
+#[test]
+fn test_request_with_params_or_panic() {
+    let request = Request::new("foo").with_params_or_panic([1, 2, 3]);
+    assert_eq!(request.params, Some(serde_json::json!([1, 2, 3])));
+}
+
+#[test]
+#[should_panic(expected = "invalid params")]
+fn test_request_with_params_or_panic_scalar() {
+    Request::new("foo").with_params_or_panic(42);
+}
+
+#[test]
+fn test_request_accessors() {
+    let request = Request::new("foo").with_id(1).with_params([2]).unwrap();
+    assert_eq!(request.method(), "foo");
+    assert_eq!(request.id(), &Value::from(1));
+    assert_eq!(request.params(), Some(&serde_json::json!([2])));
+    assert!(Request::new("foo").params().is_none());
+}
+
+#[test]
+fn test_request_with_namespaced_id() {
+    let request = Request::new("foo").with_namespaced_id("s", 4);
+    assert_eq!(request.id, Value::from("s-4"));
+    assert!(crate::IdSpace::new("s").contains(&request.id));
+}
+
+#[test]
+fn test_request_params_or_default() {
+    #[derive(Debug, Default, PartialEq, Deserialize)]
+    struct Options {
+        verbose: bool,
+    }
+
+    let request = Request::new("foo");
+    assert_eq!(
+        request.params_or_default::<Options>().unwrap(),
+        Options::default()
+    );
+    let request = request.with_params_value(Value::Null);
+    assert_eq!(
+        request.params_or_default::<Options>().unwrap(),
+        Options::default()
+    );
+
+    let request = request.with_params_value(serde_json::json!({"verbose": true}));
+    assert!(request.params_or_default::<Options>().unwrap().verbose);
+
+    let request = request.with_params_value(serde_json::json!([1]));
+    let err = request.params_or_default::<Options>().unwrap_err();
+    assert_eq!(err.code, Error::INVALID_PARAMS);
+}
+
+#[test]
+fn test_request_params_or_empty() {
+    let request = Request::new("sum");
+    assert!(request.params_or_empty_array().is_empty());
+    assert!(request.params_or_empty_object().is_empty());
+
+    let request = request.with_params_value(serde_json::json!([1, 2]));
+    assert_eq!(
+        request.params_or_empty_array(),
+        [Value::from(1), Value::from(2)]
+    );
+    assert!(request.params_or_empty_object().is_empty());
+
+    let request = request.with_params_value(serde_json::json!({"a": 1}));
+    assert!(request.params_or_empty_array().is_empty());
+    assert!(matches!(request.params_or_empty_object(), Cow::Borrowed(_)));
+    assert_eq!(request.params_or_empty_object()["a"], 1);
+}
+
+#[test]
+fn test_request_positional_as() {
+    let request = Request::new("move").with_params_value(serde_json::json!([1, 2, "fast"]));
+    let (x, y, speed): (i32, i32, String) = request.positional_as().unwrap();
+    assert_eq!((x, y, speed.as_str()), (1, 2, "fast"));
+
+    for json in [
+        serde_json::json!([1, 2]),
+        serde_json::json!([1, 2, "fast", 4]),
+    ] {
+        let len = json.as_array().unwrap().len();
+        let request = request.clone().with_params_value(json);
+        let err = request.positional_as::<(i32, i32, String)>().unwrap_err();
+        assert_eq!(err.code, Error::INVALID_PARAMS);
+        let data = err.data.unwrap();
+        assert_eq!(data["expected"], 3);
+        assert_eq!(data["actual"], len);
+    }
+
+    let request = request.with_params_value(serde_json::json!({"x": 1, "y": 2}));
+    let err = request.positional_as::<(i32, i32)>().unwrap_err();
+    assert_eq!(err.code, Error::INVALID_PARAMS);
+    assert_eq!(err.data.unwrap()["expected"], "array");
+
+    let request = Request::new("move").with_params_value(serde_json::json!([1, "two"]));
+    let err = request.positional_as::<(i32, i32)>().unwrap_err();
+    assert_eq!(err.data.unwrap()["expected"], 2);
+    assert!(Request::new("ping").positional_as::<()>().is_err());
+}
+
+#[test]
+fn test_request_params_as_typed() {
+    let request = Request::new("add").with_params([1, 2]).unwrap();
+    assert_eq!(request.params_as_typed::<(u8, u8)>().unwrap(), (1, 2));
+
+    let request = Request::new("add");
+    assert!(request.params_as::<Option<(u8, u8)>>().unwrap().is_none());
+    let err = request.params_as_typed::<Option<(u8, u8)>>().unwrap_err();
+    assert_eq!(err.code, Error::INVALID_PARAMS);
 }
 
 #[cfg(feature = "std")]
@@ -132,23 +974,75 @@ mod io {
         where
             R: Read,
         {
-            let (n, contents) = helpers::get_content_from_reader(reader)?;
-            let request = Request::parse_json(&contents)?;
+            let span = telemetry::IoSpan::read();
+            let (n, header_len, contents) = helpers::read_frame(reader)?;
+            let request =
+                Request::parse_json(&contents).map_err(|e| helpers::in_frame(e, header_len))?;
+            span.finish(&request, n);
             Ok((n, request))
         }
 
+        /// Read a request from a reader, reusing the provided buffer for the frame.
+        ///
+        /// Returns the number of consumed bytes and the request.
+        pub fn try_from_reader_buffered<R>(
+            reader: R,
+            buf: &mut Vec<u8>,
+        ) -> Result<(usize, Self), Error>
+        where
+            R: Read,
+        {
+            let span = telemetry::IoSpan::read();
+            let (n, header_len, contents) =
+                helpers::read_frame_into(reader, buf, helpers::MAX_DECOMPRESSED_LEN)?;
+            let request =
+                Request::parse_json(contents).map_err(|e| helpers::in_frame(e, header_len))?;
+            span.finish(&request, n);
+            Ok((n, request))
+        }
+
+        /// Write the frame of the request to a writer with a vectored write of its header and
+        /// body, and return the number of bytes written.
+        ///
+        /// Partial writes are retried until the frame is complete, so frames written behind a
+        /// lock are never interleaved.
+        pub fn try_to_writer_vectored<W>(&self, writer: W) -> Result<usize, Error>
+        where
+            W: Write,
+        {
+            self.try_to_writer_vectored_buffered(writer, &mut Vec::new())
+        }
+
+        /// Write the frame of the request as [`Request::try_to_writer_vectored`], serializing
+        /// its body into the provided buffer.
+        ///
+        /// The buffer is cleared first, and can be reused across writes.
+        pub fn try_to_writer_vectored_buffered<W>(
+            &self,
+            writer: W,
+            buf: &mut Vec<u8>,
+        ) -> Result<usize, Error>
+        where
+            W: Write,
+        {
+            let span = telemetry::IoSpan::write();
+            helpers::write_vectored(self, writer, buf).inspect(|n| span.finish(self, *n))
+        }
+
         /// Write a request to a writer and return the number of bytes written.
         pub fn try_to_writer<W>(&self, mut writer: W) -> Result<usize, Error>
         where
             W: Write,
         {
+            let span = telemetry::IoSpan::write();
             writer
                 .write(self.to_string().as_bytes())
                 .map_err(|e| Error {
                     code: Error::PARSE_ERROR,
                     message: e.to_string(),
-                    data: serde_json::to_value(&self).ok(),
+                    data: serde_json::to_value(self).ok(),
                 })
+                .inspect(|n| span.finish(self, *n))
         }
     }
 }
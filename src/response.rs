@@ -1,25 +1,132 @@
-use super::{helpers, Error};
-use alloc::string::{String, ToString};
+use super::{helpers, telemetry, Error, JsonRpcVersion, Preserved, Request};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
 use core::{fmt, str::FromStr};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Number, Value};
+
+/// Outcome of a response: exactly one of a result or an error.
+#[derive(Debug, Clone)]
+pub enum Outcome {
+    /// Result of a successful call, serialized as the `result` member
+    Success(Value),
+    /// Error of a failed call, serialized as the `error` member
+    Failure(Error),
+}
+
+impl Outcome {
+    /// Convert the outcome into a `Result`
+    pub fn into_result(self) -> Result<Value, Error> {
+        match self {
+            Outcome::Success(value) => Ok(value),
+            Outcome::Failure(err) => Err(err),
+        }
+    }
+}
+
+impl From<Result<Value, Error>> for Outcome {
+    fn from(result: Result<Value, Error>) -> Self {
+        match result {
+            Ok(value) => Outcome::Success(value),
+            Err(err) => Outcome::Failure(err),
+        }
+    }
+}
 
 /// JSON-RPC response
-#[derive(Serialize, Deserialize, Debug, Clone)]
+///
+/// Deserialization fails unless exactly one of `result` and `error` is present. A `null` error
+/// is taken as absent, while a `null` result is a success.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(try_from = "Fields")]
 pub struct Response {
     /// Protocol header
-    pub jsonrpc: String,
+    pub jsonrpc: JsonRpcVersion,
 
-    /// Result variant representing success
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub result: Option<Value>,
-
-    /// Result variant representing error
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub error: Option<Error>,
+    /// Result or error of the call
+    pub outcome: Outcome,
 
     /// ID of the request that originated the response
     pub id: Value,
+
+    /// Optional non-standard metadata, such as routing information
+    #[cfg(feature = "meta")]
+    pub meta: Option<Value>,
+}
+
+/// Wire format of a [`Response`] being parsed
+#[derive(Deserialize)]
+struct Fields {
+    jsonrpc: JsonRpcVersion,
+    #[serde(default, deserialize_with = "present")]
+    result: Option<Value>,
+    #[serde(default)]
+    error: Option<Error>,
+    id: Value,
+    #[cfg(feature = "meta")]
+    #[serde(default)]
+    meta: Option<Value>,
+}
+
+/// Wire format of a [`Response`] being serialized
+#[derive(Serialize)]
+struct FieldsRef<'a> {
+    jsonrpc: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<&'a Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a Error>,
+    id: &'a Value,
+    #[cfg(feature = "meta")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    meta: Option<&'a Value>,
+}
+
+/// Deserialize a member that is present, even if `null`
+fn present<'de, D>(deserializer: D) -> Result<Option<Value>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Value::deserialize(deserializer).map(Some)
+}
+
+impl Serialize for Response {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        FieldsRef {
+            jsonrpc: &self.jsonrpc,
+            result: self.result(),
+            error: self.error(),
+            id: &self.id,
+            #[cfg(feature = "meta")]
+            meta: self.meta.as_ref(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl TryFrom<Fields> for Response {
+    type Error = &'static str;
+
+    fn try_from(fields: Fields) -> Result<Self, Self::Error> {
+        let outcome = match (fields.result, fields.error) {
+            (Some(value), None) => Outcome::Success(value),
+            (None, Some(err)) => Outcome::Failure(err),
+            (Some(_), Some(_)) => return Err("the response contains both a result and an error"),
+            (None, None) => return Err("the response contains neither a result nor an error"),
+        };
+        Ok(Self {
+            jsonrpc: fields.jsonrpc,
+            outcome,
+            id: fields.id,
+            #[cfg(feature = "meta")]
+            meta: fields.meta,
+        })
+    }
 }
 
 impl Response {
@@ -30,10 +137,11 @@ impl Response {
         V: Into<Value>,
     {
         Self {
-            jsonrpc: "2.0".to_string(),
-            result: Some(value.into()),
-            error: None,
+            jsonrpc: JsonRpcVersion::default(),
+            outcome: Outcome::Success(value.into()),
             id: id.into(),
+            #[cfg(feature = "meta")]
+            meta: None,
         }
     }
 
@@ -44,27 +152,399 @@ impl Response {
         E: Into<Error>,
     {
         Self {
-            jsonrpc: "2.0".to_string(),
-            result: None,
-            error: Some(err.into()),
+            jsonrpc: JsonRpcVersion::default(),
+            outcome: Outcome::Failure(err.into()),
             id: id.into(),
+            #[cfg(feature = "meta")]
+            meta: None,
+        }
+    }
+
+    /// Create a success response to the request, serializing the provided value.
+    ///
+    /// This is the recommended way to reply from a server handler. Fails with
+    /// [`Error::INTERNAL_ERROR`] if the value cannot be serialized.
+    pub fn ok_from_request<V>(request: &Request, value: V) -> Result<Self, Error>
+    where
+        V: Serialize,
+    {
+        serde_json::to_value(value)
+            .map(|value| Self::ok(request.id.clone(), value))
+            .map_err(|e| Error {
+                code: Error::INTERNAL_ERROR,
+                message: e.to_string(),
+                data: None,
+            })
+    }
+
+    /// Create an error response to the request.
+    pub fn err_from_request(request: &Request, error: Error) -> Self {
+        Self::err(request.id.clone(), error)
+    }
+
+    /// Create a new response representing an implementation-defined server error.
+    ///
+    /// Fails if the code is outside [`Error::SERVER_ERRORS`], or if the data cannot be serialized.
+    pub fn server_error<I, M, D>(id: I, code: i32, message: M, data: D) -> Result<Self, Error>
+    where
+        I: Into<Value>,
+        M: Into<String>,
+        D: Serialize,
+    {
+        let data = serde_json::to_value(data).map_err(|e| Error {
+            code: Error::PARSE_ERROR,
+            message: e.to_string(),
+            data: None,
+        })?;
+        let error = Error {
+            data: Some(data),
+            ..Error::server(code, message.into())?
+        };
+        Ok(Self::err(id, error))
+    }
+
+    /// Create a new response from the result of a handler.
+    ///
+    /// The success value is serialized into the result; if it cannot be serialized, the response
+    /// is an [`Error::INTERNAL_ERROR`] instead.
+    pub fn from_result<I, T>(id: I, result: Result<T, Error>) -> Self
+    where
+        I: Into<Value>,
+        T: Serialize,
+    {
+        let result = result.and_then(|value| {
+            serde_json::to_value(value).map_err(|e| Error {
+                code: Error::INTERNAL_ERROR,
+                message: e.to_string(),
+                data: None,
+            })
+        });
+
+        match result {
+            Ok(value) => Self::ok(id, value),
+            Err(err) => Self::err(id, err),
+        }
+    }
+
+    /// Compare the ID and outcome with another response, ignoring the protocol version.
+    pub fn eq_ignore_version(&self, other: &Response) -> bool {
+        let outcome = match (&self.outcome, &other.outcome) {
+            (Outcome::Success(a), Outcome::Success(b)) => a == b,
+            (Outcome::Failure(a), Outcome::Failure(b)) => {
+                a.code == b.code && a.message == b.message && a.data == b.data
+            }
+            _ => false,
+        };
+        self.id == other.id && outcome
+    }
+
+    /// Version of the protocol of the response
+    #[inline]
+    pub fn jsonrpc_version(&self) -> &str {
+        &self.jsonrpc
+    }
+
+    /// Replace the version of the protocol.
+    ///
+    /// The only version compliant with the spec is `"2.0"`, set by the constructors; other
+    /// versions are meant for bridges to JSON-RPC 1.0 peers.
+    pub fn set_jsonrpc_version<V>(mut self, version: V) -> Self
+    where
+        V: ToString,
+    {
+        self.jsonrpc = JsonRpcVersion::from(version.to_string());
+        self
+    }
+
+    /// ID of the response, as a borrowed JSON value
+    #[inline]
+    pub fn id_value(&self) -> &Value {
+        &self.id
+    }
+
+    /// Replace the ID of the response with the provided value
+    pub fn set_id<I>(mut self, id: I) -> Self
+    where
+        I: Into<Value>,
+    {
+        self.id = id.into();
+        self
+    }
+
+    /// ID of the response if it is a number representable as `u64`
+    pub fn id_as_u64(&self) -> Option<u64> {
+        self.id.as_u64()
+    }
+
+    /// ID of the response if it is a number representable as `i64`
+    pub fn id_as_i64(&self) -> Option<i64> {
+        self.id.as_i64()
+    }
+
+    /// ID of the response if it is a number, without converting it to a primitive.
+    ///
+    /// With the `arbitrary-precision` feature, the number keeps the exact digits of the JSON.
+    pub fn id_as_raw_number(&self) -> Option<&Number> {
+        match &self.id {
+            Value::Number(n) => Some(n),
+            _ => None,
         }
     }
 
+    /// ID of the response if it is a string
+    pub fn id_as_str(&self) -> Option<&str> {
+        self.id.as_str()
+    }
+
+    /// Result of a success response
+    pub fn result(&self) -> Option<&Value> {
+        match &self.outcome {
+            Outcome::Success(value) => Some(value),
+            Outcome::Failure(_) => None,
+        }
+    }
+
+    /// Error of an error response
+    pub fn error(&self) -> Option<&Error> {
+        match &self.outcome {
+            Outcome::Success(_) => None,
+            Outcome::Failure(err) => Some(err),
+        }
+    }
+
+    /// Convert the response into the `Result` of the call, discarding its ID
+    pub fn into_result(self) -> Result<Value, Error> {
+        self.outcome.into_result()
+    }
+
+    /// Convert the response into the deserialized result of the call, or an [`anyhow::Error`].
+    ///
+    /// The error of an error response is kept as the source of the `anyhow::Error`, so it can be
+    /// recovered with `downcast_ref::<Error>()`. Its context is `JSON-RPC error {code}`, followed
+    /// by ` with data {data}` if the error has data; the alternate display `{:#}` then reads
+    /// `JSON-RPC error -32601: method not found`.
+    #[cfg(feature = "anyhow")]
+    pub fn into_anyhow_result<T>(self) -> anyhow::Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        let value = self.into_result().map_err(|err| {
+            let context = match &err.data {
+                Some(data) => alloc::format!("JSON-RPC error {} with data {}", err.code, data),
+                None => alloc::format!("JSON-RPC error {}", err.code),
+            };
+            anyhow::Error::new(err).context(context)
+        })?;
+        serde_json::from_value(value)
+            .map_err(|e| anyhow::Error::new(e).context("the result of the response is invalid"))
+    }
+
+    /// Split the response into its ID and outcome, without cloning them.
+    ///
+    /// Supersedes the deprecated [`Response::into_parts`], which discards the ID.
+    pub fn into_id_and_outcome(self) -> (Value, Outcome) {
+        (self.id, self.outcome)
+    }
+
+    /// Split the response into the `result` and `error` members it had before 0.3.0.
+    ///
+    /// Exactly one of the two is `Some`.
+    #[deprecated(
+        since = "0.3.0",
+        note = "use `Response::result`, `Response::error` or `Response::into_result`"
+    )]
+    pub fn into_parts(self) -> (Option<Value>, Option<Error>) {
+        match self.outcome {
+            Outcome::Success(value) => (Some(value), None),
+            Outcome::Failure(err) => (None, Some(err)),
+        }
+    }
+
+    /// Optional non-standard metadata of the response
+    #[cfg(feature = "meta")]
+    pub fn meta(&self) -> Option<&Value> {
+        self.meta.as_ref()
+    }
+
+    /// Replace the non-standard metadata with the provided value
+    #[cfg(feature = "meta")]
+    pub fn with_meta(mut self, meta: Value) -> Self {
+        self.meta = Some(meta);
+        self
+    }
+
+    /// Transform the result of a success response, leaving error responses unchanged
+    pub fn map_result<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(Value) -> Value,
+    {
+        self.outcome = match self.outcome {
+            Outcome::Success(value) => Outcome::Success(f(value)),
+            outcome => outcome,
+        };
+        self
+    }
+
+    /// Transform the error of an error response, leaving success responses unchanged
+    pub fn map_error<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(Error) -> Error,
+    {
+        self.outcome = match self.outcome {
+            Outcome::Failure(err) => Outcome::Failure(f(err)),
+            outcome => outcome,
+        };
+        self
+    }
+
     /// Parse a message into the response
     pub fn parse(s: &str) -> Result<(Self, &str), Error> {
         let (message, remainder) = helpers::get_content_length(s)?;
-        let response = Response::parse_json(message)?;
+        let header_len = s.len() - remainder.len() - message.len();
+        let response =
+            Response::parse_json(message).map_err(|e| helpers::in_frame(e, header_len))?;
         Ok((response, remainder))
     }
 
-    /// Parse a response from the provided JSON
+    /// Append the framed response to the buffer, returning the number of bytes appended
+    #[cfg(feature = "bytes")]
+    pub fn encode_to(&self, buf: &mut bytes::BytesMut) -> Result<usize, Error> {
+        helpers::encode_to(self, buf)
+    }
+
+    /// Parse the response framed at the start of the buffer, advancing past its frame.
+    ///
+    /// Returns `None`, leaving the buffer untouched, if the frame is not complete yet. A frame
+    /// with an invalid body is consumed, but not one with an invalid header or one declaring more
+    /// than [`helpers::MAX_FRAME_LEN`] bytes.
+    #[cfg(feature = "bytes")]
+    pub fn decode_from(buf: &mut bytes::BytesMut) -> Result<Option<Self>, Error> {
+        helpers::decode_from(buf, Response::parse_json)
+    }
+
+    /// Serialize the response to canonical JSON.
+    ///
+    /// The output is deterministic: object keys are sorted at every level, including inside
+    /// arguments and results, with no whitespace. Numbers are written in the ECMAScript form of
+    /// their value, as in RFC 8785, so `1` and `1.0` canonicalize identically; integers keep
+    /// their digits.
+    pub fn to_json_canonical(&self) -> Result<String, Error> {
+        helpers::to_json_canonical(self)
+    }
+
+    /// Bytes of the canonical JSON of the response, to be fed into a hasher
+    pub fn canonical_hash(&self) -> Result<Vec<u8>, Error> {
+        self.to_json_canonical().map(String::into_bytes)
+    }
+
+    /// Approximate length of the JSON serialization of the response, to pre-size buffers.
+    ///
+    /// This is a hint computed without serializing: it is not exact, and not a bound. Use
+    /// [`Response::json_len`] for the exact length.
+    pub fn serialized_len_hint(&self) -> usize {
+        let outcome = match &self.outcome {
+            Outcome::Success(value) => helpers::member_len_hint("result", Some(value)),
+            Outcome::Failure(err) => helpers::member_len("error", helpers::error_len_hint(err)),
+        };
+        let hint = "{}".len()
+            + helpers::str_member_len("jsonrpc", &self.jsonrpc)
+            + helpers::member_len_hint("id", Some(&self.id))
+            + outcome;
+        #[cfg(feature = "meta")]
+        let hint = hint + helpers::member_len_hint("meta", self.meta.as_ref());
+        hint
+    }
+
+    /// Length of the JSON serialization of the response, without the frame header
+    pub fn json_len(&self) -> Result<usize, Error> {
+        helpers::json_len(self)
+    }
+
+    /// Length of the frame of the response, header included.
+    ///
+    /// Equals the length of its `Display` output, computed without building the frame.
+    pub fn encoded_len(&self) -> Result<usize, Error> {
+        self.json_len().map(helpers::frame_len)
+    }
+
+    /// Serialize the response to compact JSON, without the frame header
+    pub fn to_compact_json(&self) -> Result<String, Error> {
+        helpers::to_json_compact(self)
+    }
+
+    /// Serialize the response to indented JSON, without the frame header
+    pub fn to_pretty_json(&self) -> Result<String, Error> {
+        helpers::to_json_pretty(self)
+    }
+
+    /// Serialize the response to JSON, escaping every non-ASCII character as `\uXXXX`
+    pub fn to_json_ascii(&self) -> Result<String, Error> {
+        helpers::to_json_ascii(self)
+    }
+
+    /// Serialize the response to a frame with an ASCII-only body.
+    ///
+    /// The `Content-Length` header is computed over the escaped body.
+    pub fn to_string_ascii(&self) -> Result<String, Error> {
+        self.to_json_ascii().map(|m| helpers::frame(&m))
+    }
+
+    /// Frame the responses as a batch
+    #[cfg(feature = "std")]
+    fn batch_frame(responses: &[Response]) -> Result<String, Error> {
+        serde_json::to_string(responses)
+            .map(|batch| helpers::frame(&batch))
+            .map_err(|e| Error {
+                code: Error::PARSE_ERROR,
+                message: e.to_string(),
+                data: None,
+            })
+    }
+
+    /// Parse a response from the provided JSON.
+    ///
+    /// An object with a `method` is a request or a notification, and fails with
+    /// [`Error::INVALID_REQUEST`].
     pub fn parse_json(json: &str) -> Result<Self, Error> {
-        serde_json::from_str(json).map_err(|e| Error {
-            code: Error::INVALID_REQUEST,
-            message: e.to_string(),
-            data: Some(Value::String(json.to_string())),
-        })
+        helpers::parse_json_without(json, "method", "the provided response contains a method")
+            .inspect(telemetry::parsed_response)
+            .inspect_err(|e| telemetry::parse_failed(e, json))
+    }
+
+    /// Parse a response from the provided JSON, keeping its unknown top-level members
+    pub fn parse_json_preserving(json: &str) -> Result<Preserved<Self>, Error> {
+        Preserved::parse_json(json)
+    }
+
+    /// Parse a response from the provided JSON, rejecting the non-standard `meta` member
+    pub fn parse_json_strict(json: &str) -> Result<Self, Error> {
+        helpers::reject_member(json, "meta", "the provided response contains metadata")?;
+        Self::parse_json(json)
+    }
+
+    /// Parse a response from the provided JSON, accepting a string `error` member.
+    ///
+    /// Some servers reply with `"error": "message"` instead of an error object. The string is
+    /// taken as the message of an [`Error::INTERNAL_ERROR`].
+    pub fn parse_json_lenient(json: &str) -> Result<Self, Error> {
+        let invalid = |e| helpers::json_error(e, json);
+
+        let mut value: Value = serde_json::from_str(json).map_err(invalid)?;
+        if let Some(error) = value.get_mut("error") {
+            if let Value::String(message) = error {
+                *error = Error {
+                    code: Error::INTERNAL_ERROR,
+                    message: core::mem::take(message),
+                    data: None,
+                }
+                .into();
+            }
+        }
+
+        serde_json::from_value(value)
+            .map_err(invalid)
+            .inspect(telemetry::parsed_response)
+            .inspect_err(|e| telemetry::parse_failed(e, json))
     }
 }
 
@@ -74,24 +554,33 @@ where
     E: From<Error>,
 {
     fn from(value: Response) -> Self {
-        match (value.result, value.error) {
-            (Some(result), _) => Ok(result.into()),
-            (_, Some(err)) => Err(err.into()),
-            (_, _) => Err(Error {
-                code: Error::INVALID_REQUEST,
-                message: "the provided respose header is invalid".to_string(),
-                data: None,
-            }
-            .into()),
+        match value.outcome {
+            Outcome::Success(result) => Ok(result.into()),
+            Outcome::Failure(err) => Err(err.into()),
         }
     }
 }
 
+/// Create a success response from an `(id, result)` pair.
+///
+/// The type of the second element selects between this and the error response conversion of
+/// `(id, Error)` at compile time.
+impl From<(Value, Value)> for Response {
+    fn from((id, result): (Value, Value)) -> Self {
+        Self::ok(id, result)
+    }
+}
+
+/// Create an error response from an `(id, error)` pair.
+impl From<(Value, Error)> for Response {
+    fn from((id, err): (Value, Error)) -> Self {
+        Self::err(id, err)
+    }
+}
+
 impl fmt::Display for Response {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        serde_json::to_string(&self)
-            .map_err(|_| fmt::Error)
-            .and_then(|m| write!(f, "Content-Length: {}\r\n\r\n{}", m.len(), m))
+        helpers::write_framed(f, self)
     }
 }
 
@@ -103,6 +592,351 @@ impl FromStr for Response {
     }
 }
 
+#[test]
+fn test_map_result_and_error() {
+    let response = Response::ok(1, 2)
+        .map_result(|v| Value::from(v.as_i64().unwrap() * 2))
+        .map_error(|_| unreachable!());
+    assert_eq!(response.result(), Some(&Value::from(4)));
+
+    let err = Error {
+        code: Error::INVALID_REQUEST,
+        message: "secret".to_string(),
+        data: None,
+    };
+    let response = Response::err(1, err)
+        .map_result(|_| unreachable!())
+        .map_error(|e| Error {
+            message: "redacted".to_string(),
+            ..e
+        });
+    assert_eq!(response.error().unwrap().message, "redacted");
+}
+
+#[test]
+fn test_response_into_id_and_outcome() {
+    let response = Response::ok(1, true);
+    let (id, outcome) = response.clone().into_id_and_outcome();
+    assert_eq!(id, Value::from(1));
+    assert!(matches!(&outcome, Outcome::Success(Value::Bool(true))));
+    let rebuilt = Response {
+        id,
+        outcome,
+        ..Response::ok(0, ())
+    };
+    assert_eq!(rebuilt.to_string(), response.to_string());
+}
+
+#[test]
+fn test_response_from_tuple() {
+    let response: Response = (Value::from(1), Value::from("foo")).into();
+    assert_eq!(response.result(), Some(&Value::from("foo")));
+    assert!(response.error().is_none());
+
+    let err = Error {
+        code: Error::INTERNAL_ERROR,
+        message: "bar".to_string(),
+        data: None,
+    };
+    let response: Response = (Value::from(1), err).into();
+    assert!(response.result().is_none());
+    assert_eq!(response.error().unwrap().code, Error::INTERNAL_ERROR);
+}
+
+#[test]
+fn test_response_parse_json_strict() {
+    let json = r#"{"jsonrpc":"2.0","id":1,"result":true,"meta":{"route":"a"}}"#;
+    assert!(Response::parse_json(json).is_ok());
+    assert_eq!(
+        Response::parse_json_strict(json).unwrap_err().code,
+        Error::INVALID_REQUEST
+    );
+
+    let json = r#"{"jsonrpc":"2.0","id":1,"result":true}"#;
+    assert!(Response::parse_json_strict(json).is_ok());
+
+    let json = r#"{"jsonrpc":"2.0","id":1,"result":true,"method":"foo"}"#;
+    assert_eq!(
+        Response::parse_json(json).unwrap_err().code,
+        Error::INVALID_REQUEST
+    );
+    assert!(Response::parse_json_strict(json).is_err());
+}
+
+#[test]
+fn test_response_parse_json_lenient() {
+    let json = r#"{"jsonrpc":"2.0","error":"something went wrong","id":1}"#;
+    assert!(Response::parse_json(json).is_err());
+
+    let response = Response::parse_json_lenient(json).unwrap();
+    let error = response.error().unwrap();
+    assert_eq!(error.code, Error::INTERNAL_ERROR);
+    assert_eq!(error.message, "something went wrong");
+    assert!(error.data.is_none());
+    assert_eq!(response.id, Value::from(1));
+
+    let json = r#"{"jsonrpc":"2.0","error":{"code":-32601,"message":"foo"},"id":2}"#;
+    let response = Response::parse_json_lenient(json).unwrap();
+    assert_eq!(response.error().unwrap().code, Error::METHOD_NOT_FOUND);
+}
+
+#[test]
+fn test_response_from_request() {
+    use alloc::collections::BTreeMap;
+
+    let request = Request::new("foo").with_id(3);
+    let response = Response::ok_from_request(&request, [1, 2]).unwrap();
+    assert_eq!(response.id, request.id);
+    assert_eq!(response.result(), Some(&serde_json::json!([1, 2])));
+
+    let unserializable = BTreeMap::from([((1, 2), 3)]);
+    let err = Response::ok_from_request(&request, unserializable).unwrap_err();
+    assert_eq!(err.code, Error::INTERNAL_ERROR);
+
+    let error = Error {
+        code: Error::METHOD_NOT_FOUND,
+        message: "foo".to_string(),
+        data: None,
+    };
+    let response = Response::err_from_request(&request, error);
+    assert_eq!(response.id, request.id);
+    assert_eq!(response.error().unwrap().code, Error::METHOD_NOT_FOUND);
+}
+
+#[test]
+fn test_response_server_error() {
+    let data = serde_json::json!({"retry_after": 5});
+    let response = Response::server_error(1, -32001, "busy", &data).unwrap();
+    assert_eq!(response.id, Value::from(1));
+    assert!(response.result().is_none());
+    let error = response.error().unwrap();
+    assert_eq!(error.code, -32001);
+    assert_eq!(error.message, "busy");
+    assert_eq!(error.data, Some(data));
+
+    let err = Response::server_error(1, -32100, "busy", ()).unwrap_err();
+    assert_eq!(err.data, Some(Value::from(-32100)));
+}
+
+#[test]
+fn test_response_from_result() {
+    use alloc::collections::BTreeMap;
+
+    let response = Response::from_result(1, Ok([1, 2]));
+    assert_eq!(response.result(), Some(&serde_json::json!([1, 2])));
+    assert!(response.error().is_none());
+
+    let err = Error {
+        code: Error::INVALID_PARAMS,
+        message: "foo".to_string(),
+        data: None,
+    };
+    let response = Response::from_result::<_, ()>(2, Err(err));
+    assert!(response.result().is_none());
+    assert_eq!(response.error().unwrap().code, Error::INVALID_PARAMS);
+
+    let unserializable = BTreeMap::from([((1, 2), 3)]);
+    let response = Response::from_result(3, Ok(unserializable));
+    assert!(response.result().is_none());
+    assert_eq!(response.error().unwrap().code, Error::INTERNAL_ERROR);
+    assert_eq!(response.id, Value::from(3));
+}
+
+#[test]
+fn test_response_id_value() {
+    let response = Response::ok(1, true).set_id("abc");
+    assert_eq!(response.id_value(), "abc");
+    assert_eq!(response.set_id(2).id_value(), &Value::from(2));
+}
+
+#[test]
+fn test_response_id_as() {
+    let response = Response::ok(u64::MAX, true);
+    assert_eq!(response.id_as_u64(), Some(u64::MAX));
+    assert_eq!(response.id_as_i64(), None);
+    assert_eq!(response.id_as_str(), None);
+
+    let response = Response::ok("abc", true);
+    assert_eq!(response.id_as_u64(), None);
+    assert_eq!(response.id_as_str(), Some("abc"));
+}
+
+#[cfg(feature = "arbitrary-precision")]
+#[test]
+fn test_response_arbitrary_precision() {
+    let big = "1234567890123456789012345678901234567890";
+    let json = alloc::format!(r#"{{"jsonrpc":"2.0","result":{big},"id":{big}}}"#);
+    let response = Response::parse_json(&json).unwrap();
+    assert_eq!(response.id_as_raw_number().unwrap().to_string(), big);
+    assert_eq!(response.id_as_u64(), None);
+    assert_eq!(response.result().unwrap().to_string(), big);
+    assert_eq!(response.to_compact_json().unwrap(), json);
+}
+
+#[cfg(feature = "anyhow")]
+#[test]
+fn test_response_into_anyhow_result() {
+    let sum: u32 = Response::ok(1, 3).into_anyhow_result().unwrap();
+    assert_eq!(sum, 3);
+
+    let err = Response::ok(1, "three")
+        .into_anyhow_result::<u32>()
+        .unwrap_err();
+    assert!(err.downcast_ref::<serde_json::Error>().is_some());
+
+    let response = Response::err(
+        1,
+        Error {
+            code: Error::METHOD_NOT_FOUND,
+            message: "method not found".to_string(),
+            data: None,
+        },
+    );
+    let err = response.into_anyhow_result::<u32>().unwrap_err();
+    assert_eq!(
+        alloc::format!("{:#}", err),
+        "JSON-RPC error -32601: method not found"
+    );
+    assert_eq!(
+        err.downcast_ref::<Error>().unwrap().code,
+        Error::METHOD_NOT_FOUND
+    );
+
+    let response = Response::err(
+        1,
+        Error {
+            code: Error::INVALID_PARAMS,
+            message: "invalid params".to_string(),
+            data: Some(serde_json::json!({"param": "a"})),
+        },
+    );
+    let err = response.into_anyhow_result::<u32>().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        r#"JSON-RPC error -32602 with data {"param":"a"}"#
+    );
+    assert_eq!(
+        err.downcast_ref::<Error>().unwrap().data,
+        Some(serde_json::json!({"param": "a"}))
+    );
+}
+
+#[test]
+fn test_response_outcome() {
+    let json = r#"{"jsonrpc":"2.0","result":null,"id":1}"#;
+    let response = Response::parse_json(json).unwrap();
+    assert_eq!(response.result(), Some(&Value::Null));
+    assert_eq!(serde_json::to_string(&response).unwrap(), json);
+
+    let json = r#"{"jsonrpc":"2.0","error":{"code":-32601,"message":"foo","data":null},"id":1}"#;
+    let response = Response::parse_json(json).unwrap();
+    assert!(matches!(response.outcome, Outcome::Failure(_)));
+    assert_eq!(serde_json::to_string(&response).unwrap(), json);
+
+    let json = r#"{"jsonrpc":"2.0","result":1,"error":null,"id":1}"#;
+    let response = Response::parse_json(json).unwrap();
+    assert_eq!(response.into_result().unwrap(), Value::from(1));
+
+    for json in [
+        r#"{"jsonrpc":"2.0","result":1,"error":{"code":-32601,"message":"foo"},"id":1}"#,
+        r#"{"jsonrpc":"2.0","id":1}"#,
+    ] {
+        let err = Response::parse_json(json).unwrap_err();
+        assert_eq!(err.code, Error::INVALID_REQUEST);
+    }
+}
+
+#[cfg(feature = "meta")]
+#[test]
+fn test_response_meta() {
+    let response = Response::ok(1, true).with_meta(Value::from("route"));
+    let json = serde_json::to_string(&response).unwrap();
+    assert_eq!(
+        Response::parse_json(&json).unwrap().meta(),
+        Some(&Value::from("route"))
+    );
+}
+
+#[cfg(feature = "v1-compat")]
+mod v1 {
+    use super::*;
+
+    /// Wire format of a JSON-RPC 1.0 response, with both members always present
+    #[derive(Serialize)]
+    struct FieldsV1<'a> {
+        result: Option<&'a Value>,
+        error: Option<&'a Error>,
+        id: &'a Value,
+    }
+
+    impl Response {
+        /// Parse a JSON-RPC 1.0 response from the provided JSON.
+        ///
+        /// Unlike 2.0, a 1.0 response has both `result` and `error`, one of them `null`, and no
+        /// `jsonrpc` member. An `error` of `null` is a success with the result; otherwise the
+        /// response is an error, and its `result` must be `null`. The `jsonrpc` member is set to
+        /// `"1.0"` if absent.
+        pub fn parse_json_v1(json: &str) -> Result<Self, Error> {
+            let invalid = |e| helpers::json_error(e, json);
+
+            let mut value: Value = serde_json::from_str(json).map_err(invalid)?;
+            if let Value::Object(object) = &mut value {
+                if object.get("error").is_some_and(Value::is_null) {
+                    object.remove("error");
+                } else if object.get("result").is_some_and(Value::is_null) {
+                    object.remove("result");
+                }
+                object
+                    .entry("jsonrpc")
+                    .or_insert_with(|| Value::from("1.0"));
+            }
+
+            serde_json::from_value(value)
+                .map_err(invalid)
+                .inspect(telemetry::parsed_response)
+                .inspect_err(|e| telemetry::parse_failed(e, json))
+        }
+
+        /// Serialize the response to a frame in the JSON-RPC 1.0 shape.
+        ///
+        /// Both `result` and `error` are written, the absent one as `null`, and the `jsonrpc`
+        /// member and non-standard metadata are omitted.
+        pub fn to_v1_string(&self) -> Result<String, Error> {
+            let fields = FieldsV1 {
+                result: self.result(),
+                error: self.error(),
+                id: &self.id,
+            };
+            helpers::to_json_compact(&fields).map(|json| helpers::frame(&json))
+        }
+    }
+
+    #[test]
+    fn test_response_v1() {
+        let json = r#"{"result":[1,2],"error":null,"id":1}"#;
+        let response = Response::parse_json_v1(json).unwrap();
+        assert_eq!(response.result(), Some(&serde_json::json!([1, 2])));
+        assert_eq!(response.jsonrpc, "1.0");
+        assert_eq!(response.to_v1_string().unwrap(), helpers::frame(json));
+
+        let json = r#"{"result":null,"error":{"code":-32601,"message":"foo","data":null},"id":2}"#;
+        let response = Response::parse_json_v1(json).unwrap();
+        assert_eq!(response.error().unwrap().code, Error::METHOD_NOT_FOUND);
+        assert_eq!(response.to_v1_string().unwrap(), helpers::frame(json));
+
+        let json = r#"{"result":null,"error":null,"id":3}"#;
+        let response = Response::parse_json_v1(json).unwrap();
+        assert_eq!(response.result(), Some(&Value::Null));
+
+        let json = r#"{"result":1,"error":{"code":-32601,"message":"foo"},"id":4}"#;
+        let err = Response::parse_json_v1(json).unwrap_err();
+        assert_eq!(err.code, Error::INVALID_REQUEST);
+
+        let json = r#"{"result":null,"error":{"code":-32601,"message":"foo"},"id":5}"#;
+        assert!(Response::parse_json(json).is_err());
+    }
+}
+
 #[cfg(feature = "std")]
 mod io {
     use super::*;
@@ -116,23 +950,178 @@ mod io {
         where
             R: Read,
         {
-            let (n, contents) = helpers::get_content_from_reader(reader)?;
-            let response = Response::parse_json(&contents)?;
+            let span = telemetry::IoSpan::read();
+            let (n, header_len, contents) = helpers::read_frame(reader)?;
+            let response =
+                Response::parse_json(&contents).map_err(|e| helpers::in_frame(e, header_len))?;
+            span.finish(&response, n);
             Ok((n, response))
         }
 
+        /// Read a response from a reader, reusing the provided buffer for the frame.
+        ///
+        /// Returns the number of consumed bytes and the response.
+        pub fn try_from_reader_buffered<R>(
+            reader: R,
+            buf: &mut Vec<u8>,
+        ) -> Result<(usize, Self), Error>
+        where
+            R: Read,
+        {
+            let span = telemetry::IoSpan::read();
+            let (n, header_len, contents) =
+                helpers::read_frame_into(reader, buf, helpers::MAX_DECOMPRESSED_LEN)?;
+            let response =
+                Response::parse_json(contents).map_err(|e| helpers::in_frame(e, header_len))?;
+            span.finish(&response, n);
+            Ok((n, response))
+        }
+
+        /// Write the frame of the response to a writer with a vectored write of its header and
+        /// body, and return the number of bytes written.
+        ///
+        /// Partial writes are retried until the frame is complete, so frames written behind a
+        /// lock are never interleaved.
+        pub fn try_to_writer_vectored<W>(&self, writer: W) -> Result<usize, Error>
+        where
+            W: Write,
+        {
+            self.try_to_writer_vectored_buffered(writer, &mut Vec::new())
+        }
+
+        /// Write the frame of the response as [`Response::try_to_writer_vectored`], serializing
+        /// its body into the provided buffer.
+        ///
+        /// The buffer is cleared first, and can be reused across writes.
+        pub fn try_to_writer_vectored_buffered<W>(
+            &self,
+            writer: W,
+            buf: &mut Vec<u8>,
+        ) -> Result<usize, Error>
+        where
+            W: Write,
+        {
+            let span = telemetry::IoSpan::write();
+            helpers::write_vectored(self, writer, buf).inspect(|n| span.finish(self, *n))
+        }
+
         /// Write a response to a writer and return the number of bytes written.
         pub fn try_to_writer<W>(&self, mut writer: W) -> Result<usize, Error>
         where
             W: Write,
         {
+            let span = telemetry::IoSpan::write();
             writer
                 .write(self.to_string().as_bytes())
                 .map_err(|e| Error {
                     code: Error::PARSE_ERROR,
                     message: e.to_string(),
-                    data: serde_json::to_value(&self).ok(),
+                    data: serde_json::to_value(self).ok(),
                 })
+                .inspect(|n| span.finish(self, *n))
+        }
+
+        /// Write the responses as a batch in a single frame and return the number of bytes written.
+        ///
+        /// Responses to notifications must not be included, as the spec forbids them.
+        pub fn write_batch<W>(responses: &[Response], mut writer: W) -> Result<usize, Error>
+        where
+            W: Write,
+        {
+            let frame = Response::batch_frame(responses)?;
+            writer.write_all(frame.as_bytes()).map_err(|e| Error {
+                code: Error::PARSE_ERROR,
+                message: e.to_string(),
+                data: None,
+            })?;
+            Ok(frame.len())
+        }
+    }
+
+    #[test]
+    fn test_write_batch() {
+        let responses = [Response::ok(1, true), Response::ok("2", false)];
+        let mut buffer = Vec::new();
+        let n = Response::write_batch(&responses, &mut buffer).unwrap();
+        assert_eq!(n, buffer.len());
+
+        let buffer = String::from_utf8(buffer).unwrap();
+        let (batch, remainder) = helpers::get_content_length(&buffer).unwrap();
+        assert!(remainder.is_empty());
+        let batch: Vec<Response> = serde_json::from_str(batch).unwrap();
+        assert_eq!(batch.len(), 2);
+        assert_eq!(batch[1].id, Value::from("2"));
+    }
+}
+
+#[cfg(feature = "async")]
+mod io_async {
+    use super::*;
+    use core::{future::poll_fn, pin::Pin};
+    use futures_io::AsyncWrite;
+    use std::io;
+
+    impl Response {
+        /// Write the responses as a batch in a single frame to an async writer, and return the
+        /// number of bytes written.
+        ///
+        /// The frame is the one written by [`Response::write_batch`]; partial writes are retried
+        /// until it is fully written, then the writer is flushed.
+        pub async fn write_batch_async<W>(
+            responses: &[Response],
+            writer: &mut W,
+        ) -> Result<usize, Error>
+        where
+            W: AsyncWrite + Unpin,
+        {
+            let write_failed = |e: io::Error| Error {
+                code: Error::PARSE_ERROR,
+                message: e.to_string(),
+                data: None,
+            };
+
+            let frame = Response::batch_frame(responses)?;
+            let mut bytes = frame.as_bytes();
+            while !bytes.is_empty() {
+                let n = poll_fn(|cx| Pin::new(&mut *writer).poll_write(cx, bytes))
+                    .await
+                    .map_err(write_failed)?;
+                if n == 0 {
+                    return Err(write_failed(io::ErrorKind::WriteZero.into()));
+                }
+                bytes = &bytes[n..];
+            }
+            poll_fn(|cx| Pin::new(&mut *writer).poll_flush(cx))
+                .await
+                .map_err(write_failed)?;
+
+            Ok(frame.len())
         }
     }
+
+    #[test]
+    fn test_write_batch_async() {
+        use crate::test_helpers::Trickle;
+        use core::{
+            future::Future,
+            task::{Context, Poll, Waker},
+        };
+
+        let responses = [Response::ok(1, true), Response::ok("2", false)];
+        let mut writer = Trickle::new(7);
+        let n = {
+            let mut future = core::pin::pin!(Response::write_batch_async(&responses, &mut writer));
+            let mut cx = Context::from_waker(Waker::noop());
+            loop {
+                if let Poll::Ready(n) = future.as_mut().poll(&mut cx) {
+                    break n.unwrap();
+                }
+            }
+        };
+
+        let mut expected = Vec::new();
+        Response::write_batch(&responses, &mut expected).unwrap();
+        assert_eq!(n, expected.len());
+        assert_eq!(writer.written(), expected);
+    }
 }
@@ -0,0 +1,117 @@
+use super::{helpers, preserved::Fields, Error};
+use alloc::string::String;
+use core::{fmt, ops::Deref};
+use serde::{Serialize, Serializer};
+
+/// Request, notification or response with its JSON serialized once, to be sent to several
+/// destinations.
+///
+/// The message cannot be mutated, so the JSON always matches it. `as_ref` returns the JSON, and
+/// `Display` and `try_to_writer` frame it without serializing the message again.
+#[derive(Debug, Clone)]
+pub struct Serialized<T> {
+    message: T,
+    json: String,
+}
+
+impl<T> Serialized<T>
+where
+    T: Fields + Serialize,
+{
+    /// Serialize the message to compact JSON, failing with an [`Error::PARSE_ERROR`].
+    pub fn new(message: T) -> Result<Self, Error> {
+        let json = helpers::to_json_compact(&message)?;
+        Ok(Self { message, json })
+    }
+
+    /// Write the framed message to a writer and return the number of bytes written.
+    #[cfg(feature = "std")]
+    pub fn try_to_writer<W>(&self, mut writer: W) -> Result<usize, Error>
+    where
+        W: std::io::Write,
+    {
+        let frame = helpers::frame(&self.json);
+        writer
+            .write_all(frame.as_bytes())
+            .and_then(|_| writer.flush())
+            .map_err(|e| Error {
+                code: Error::PARSE_ERROR,
+                message: e.to_string(),
+                data: serde_json::to_value(&self.message).ok(),
+            })?;
+        Ok(frame.len())
+    }
+}
+
+impl<T> Serialized<T> {
+    /// Return the message, dropping its JSON.
+    pub fn into_inner(self) -> T {
+        self.message
+    }
+}
+
+impl<T> AsRef<str> for Serialized<T> {
+    fn as_ref(&self) -> &str {
+        &self.json
+    }
+}
+
+impl<T> Deref for Serialized<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.message
+    }
+}
+
+impl<T> Serialize for Serialized<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.message.serialize(serializer)
+    }
+}
+
+impl<T> fmt::Display for Serialized<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        helpers::write_frame(f, &self.json)
+    }
+}
+
+#[test]
+fn test_serialized() {
+    use super::{Notification, Response};
+    use alloc::string::ToString;
+
+    let notification = Notification::new("progress").with_params_value(serde_json::json!([50]));
+    let serialized = Serialized::new(notification.clone()).unwrap();
+    assert_eq!(serialized.as_ref(), notification.to_compact_json().unwrap());
+    assert_eq!(serialized.to_string(), notification.to_string());
+    assert_eq!(serialized.method, "progress");
+
+    let response = Response::ok(1, true);
+    let serialized = Serialized::new(response.clone()).unwrap();
+    assert_eq!(serialized.to_string(), response.to_string());
+    assert_eq!(serialized.into_inner().result(), response.result());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_serialized_fan_out() {
+    use super::Notification;
+    use alloc::{string::ToString, vec::Vec};
+
+    let notification = Notification::new("tick");
+    let serialized = Serialized::new(notification.clone()).unwrap();
+    let mut destinations = [Vec::new(), Vec::new()];
+    for destination in &mut destinations {
+        let n = serialized.try_to_writer(destination).unwrap();
+        assert_eq!(n, notification.to_string().len());
+    }
+    assert_eq!(destinations[0], notification.to_string().as_bytes());
+    assert_eq!(destinations[0], destinations[1]);
+}
@@ -0,0 +1,38 @@
+use super::{Extensions, Notification, Request, Response};
+
+/// Handler of the messages received by a server.
+///
+/// Implemented for any `Fn(Request) -> Response`, ignoring notifications.
+///
+/// Servers dispatch through [`Service::call_with`] and [`Service::notify_with`], which ignore the
+/// [`Extensions`] of the message unless overridden.
+pub trait Service {
+    /// Handle a request, returning its response
+    fn call(&self, request: Request) -> Response;
+
+    /// Handle a notification
+    fn notify(&self, notification: Notification) {
+        let _ = notification;
+    }
+
+    /// Handle a request with the extensions attached to it, returning its response
+    fn call_with(&self, request: Request, extensions: &Extensions) -> Response {
+        let _ = extensions;
+        self.call(request)
+    }
+
+    /// Handle a notification with the extensions attached to it
+    fn notify_with(&self, notification: Notification, extensions: &Extensions) {
+        let _ = extensions;
+        self.notify(notification)
+    }
+}
+
+impl<F> Service for F
+where
+    F: Fn(Request) -> Response,
+{
+    fn call(&self, request: Request) -> Response {
+        self(request)
+    }
+}
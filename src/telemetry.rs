@@ -0,0 +1,399 @@
+//! Diagnostics of the parse and I/O paths.
+//!
+//! Every hook is a no-op unless the `tracing` or `log` features are enabled. The two features are
+//! independent. Params and results are never recorded.
+
+#![cfg_attr(
+    not(any(feature = "tracing", feature = "log")),
+    allow(unused_variables, dead_code)
+)]
+
+use super::{Error, Notification, Request, Response};
+
+/// Maximum length of the input snippet recorded on parse failures.
+#[cfg(any(feature = "tracing", feature = "log"))]
+const SNIPPET_LEN: usize = 64;
+
+#[cfg(any(feature = "tracing", feature = "log"))]
+fn snippet(input: &str) -> &str {
+    let mut end = input.len().min(SNIPPET_LEN);
+    while !input.is_char_boundary(end) {
+        end -= 1;
+    }
+    &input[..end]
+}
+
+/// Record the failure to parse the provided input.
+pub fn parse_failed(error: &Error, input: &str) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(
+        code = error.code,
+        input = snippet(input),
+        "failed to parse message"
+    );
+
+    #[cfg(feature = "log")]
+    log::warn!(
+        "failed to parse message: code={} input={:?}",
+        error.code,
+        snippet(input)
+    );
+}
+
+/// Record the use of a protocol version older than `2.0`.
+pub fn legacy_version(version: &str) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(version, "legacy protocol version");
+
+    #[cfg(feature = "log")]
+    log::warn!("legacy protocol version: {}", version);
+}
+
+/// Record a frame recovered from a `Content-Length` not matching its body.
+pub fn length_mismatch(declared: usize, actual: usize) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(declared, actual, "content length mismatch");
+
+    #[cfg(feature = "log")]
+    log::warn!(
+        "content length mismatch: declared={} actual={}",
+        declared,
+        actual
+    );
+}
+
+/// Record a parsed request.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub fn parsed_request(request: &Request) {
+    #[cfg(feature = "tracing")]
+    tracing::debug!(method = %request.method, id = %request.id, "parsed request");
+}
+
+/// Record a parsed notification.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub fn parsed_notification(notification: &Notification) {
+    #[cfg(feature = "tracing")]
+    tracing::debug!(method = %notification.method, "parsed notification");
+}
+
+/// Record a parsed response.
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub fn parsed_response(response: &Response) {
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        id = %response.id,
+        error = response.error().map(|e| e.code),
+        "parsed response"
+    );
+}
+
+/// Record the failure to accept a connection of a listener.
+#[cfg(feature = "std")]
+pub fn accept_failed(error: &std::io::Error) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(%error, "failed to accept connection");
+
+    #[cfg(feature = "log")]
+    log::warn!("failed to accept connection: {}", error);
+}
+
+#[cfg(feature = "std")]
+pub use io::IoSpan;
+
+#[cfg(feature = "std")]
+mod io {
+    use super::*;
+    use crate::Message;
+    use serde_json::Value;
+
+    /// Kind, method, and ID of a message, as recorded by the hooks.
+    #[cfg_attr(not(feature = "log"), allow(dead_code))]
+    pub trait Described {
+        /// Kind of the message
+        fn kind(&self) -> &'static str;
+        /// Method name, if any
+        fn method(&self) -> Option<&str>;
+        /// ID, if any
+        fn id(&self) -> Option<&Value>;
+    }
+
+    impl Described for Request {
+        fn kind(&self) -> &'static str {
+            "request"
+        }
+
+        fn method(&self) -> Option<&str> {
+            Some(&self.method)
+        }
+
+        fn id(&self) -> Option<&Value> {
+            Some(&self.id)
+        }
+    }
+
+    impl Described for Notification {
+        fn kind(&self) -> &'static str {
+            "notification"
+        }
+
+        fn method(&self) -> Option<&str> {
+            Some(&self.method)
+        }
+
+        fn id(&self) -> Option<&Value> {
+            None
+        }
+    }
+
+    impl Described for Response {
+        fn kind(&self) -> &'static str {
+            "response"
+        }
+
+        fn method(&self) -> Option<&str> {
+            None
+        }
+
+        fn id(&self) -> Option<&Value> {
+            Some(&self.id)
+        }
+    }
+
+    impl Described for Message {
+        fn kind(&self) -> &'static str {
+            match self {
+                Message::Request(r) => r.kind(),
+                Message::Notification(n) => n.kind(),
+                Message::Response(r) => r.kind(),
+            }
+        }
+
+        fn method(&self) -> Option<&str> {
+            match self {
+                Message::Request(r) => Described::method(r),
+                Message::Notification(n) => Described::method(n),
+                Message::Response(r) => Described::method(r),
+            }
+        }
+
+        fn id(&self) -> Option<&Value> {
+            match self {
+                Message::Request(r) => Described::id(r),
+                Message::Notification(n) => Described::id(n),
+                Message::Response(r) => Described::id(r),
+            }
+        }
+    }
+
+    /// Span of a frame read or write, entered until dropped.
+    pub struct IoSpan {
+        #[cfg(feature = "tracing")]
+        span: tracing::span::EnteredSpan,
+        #[cfg(feature = "log")]
+        direction: &'static str,
+    }
+
+    impl IoSpan {
+        /// Enter the span of a frame read.
+        pub fn read() -> Self {
+            Self {
+                #[cfg(feature = "tracing")]
+                span: tracing::debug_span!(
+                    "read",
+                    kind = tracing::field::Empty,
+                    bytes = tracing::field::Empty
+                )
+                .entered(),
+                #[cfg(feature = "log")]
+                direction: "read",
+            }
+        }
+
+        /// Enter the span of a frame write.
+        pub fn write() -> Self {
+            Self {
+                #[cfg(feature = "tracing")]
+                span: tracing::debug_span!(
+                    "write",
+                    kind = tracing::field::Empty,
+                    bytes = tracing::field::Empty
+                )
+                .entered(),
+                #[cfg(feature = "log")]
+                direction: "written",
+            }
+        }
+
+        /// Record the message and the number of bytes of its frame.
+        pub fn finish<M>(&self, message: &M, bytes: usize)
+        where
+            M: Described,
+        {
+            #[cfg(feature = "tracing")]
+            self.span
+                .record("kind", message.kind())
+                .record("bytes", bytes);
+
+            #[cfg(feature = "log")]
+            log::debug!(
+                "{} frame {}: method={} id={} bytes={}",
+                message.kind(),
+                self.direction,
+                message.method().unwrap_or("-"),
+                message
+                    .id()
+                    .map(Value::to_string)
+                    .unwrap_or_else(|| "-".into()),
+                bytes
+            );
+        }
+    }
+}
+
+#[cfg(all(feature = "tracing", feature = "std"))]
+#[test]
+fn test_tracing_events() {
+    use super::Message;
+    use std::{
+        io,
+        sync::{Arc, Mutex},
+    };
+
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let capture = Capture::default();
+    let writer = capture.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_ansi(false)
+        .with_span_events(tracing_subscriber::fmt::format::FmtSpan::CLOSE)
+        .with_writer(move || writer.clone())
+        .finish();
+
+    let frame = Request::new("foo")
+        .with_id(1)
+        .with_params(["secret"])
+        .unwrap()
+        .to_string();
+    tracing::subscriber::with_default(subscriber, || {
+        Message::try_from_reader(frame.as_bytes()).unwrap();
+        Message::parse_json("{\"jsonrpc\":").unwrap_err();
+    });
+
+    let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+    assert!(output.contains("parsed request method=foo id=1"));
+    assert!(output.contains(&format!("bytes={}", frame.len())));
+    assert!(output.contains("failed to parse message code=-32700"));
+    assert!(!output.contains("secret"));
+}
+
+#[cfg(all(feature = "log", feature = "std", test))]
+mod capture {
+    use std::{
+        sync::{Mutex, Once},
+        thread::{self, ThreadId},
+    };
+
+    static RECORDS: Mutex<Vec<(ThreadId, log::Level, String)>> = Mutex::new(Vec::new());
+
+    struct Capture;
+
+    impl log::Log for Capture {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            let entry = (
+                thread::current().id(),
+                record.level(),
+                record.args().to_string(),
+            );
+            RECORDS.lock().unwrap().push(entry);
+        }
+
+        fn flush(&self) {}
+    }
+
+    /// Run `f`, returning the records it logged from the current thread.
+    pub fn records<F>(f: F) -> Vec<(log::Level, String)>
+    where
+        F: FnOnce(),
+    {
+        static INIT: Once = Once::new();
+        INIT.call_once(|| {
+            log::set_logger(&Capture).unwrap();
+            log::set_max_level(log::LevelFilter::Trace);
+        });
+
+        let id = thread::current().id();
+        RECORDS.lock().unwrap().retain(|(t, _, _)| *t != id);
+        f();
+        RECORDS
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(t, _, _)| *t == id)
+            .map(|(_, level, args)| (*level, args.clone()))
+            .collect()
+    }
+}
+
+#[cfg(all(feature = "log", feature = "std"))]
+#[test]
+fn test_log_malformed_frame() {
+    use super::Message;
+
+    let records = capture::records(|| {
+        Message::try_from_reader("Content-Length: 3\r\n\r\n{x}".as_bytes()).unwrap_err();
+    });
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].0, log::Level::Warn);
+    assert!(records[0].1.contains("code=-32700"));
+}
+
+#[cfg(all(feature = "log", feature = "std"))]
+#[test]
+fn test_log_round_trip() {
+    use super::Message;
+
+    let records = capture::records(|| {
+        let mut buffer = Vec::new();
+        Message::from(Request::new("foo").with_id(1))
+            .try_to_writer(&mut buffer)
+            .unwrap();
+        Message::try_from_reader(buffer.as_slice()).unwrap();
+    });
+    assert_eq!(records.len(), 2);
+    assert!(records.iter().all(|(level, _)| *level == log::Level::Debug));
+    assert_eq!(
+        records[0].1,
+        "request frame written: method=foo id=1 bytes=61"
+    );
+    assert_eq!(records[1].1, "request frame read: method=foo id=1 bytes=61");
+}
+
+#[cfg(all(feature = "log", feature = "std"))]
+#[test]
+fn test_log_legacy_version() {
+    use super::JsonRpcVersion;
+
+    let records = capture::records(|| {
+        JsonRpcVersion::new("2.0").unwrap();
+        JsonRpcVersion::new("1.0").unwrap();
+    });
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].0, log::Level::Warn);
+    assert!(records[0].1.contains("1.0"));
+}
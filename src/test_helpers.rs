@@ -0,0 +1,628 @@
+//! Sample messages and mock peers for tests, enabled with the `test-utils` feature.
+//!
+//! The values of the samples are fixed, so tests may assert on them. With the feature `std`, an
+//! in-memory [`pipe`] connects the code under test to a scripted [`MockServer`] or
+//! [`MockClient`], which read and write through the framing of the crate.
+
+use super::{Error, Notification, Request, Response};
+use alloc::string::{String, ToString};
+use serde_json::json;
+
+/// Request `sample/request` with ID `1` and params `{"foo": "bar"}`
+pub fn sample_request() -> Request {
+    Request::new("sample/request")
+        .with_id(1)
+        .with_params_value(json!({"foo": "bar"}))
+}
+
+/// Notification `sample/notification` with params `[1, 2, 3]`
+pub fn sample_notification() -> Notification {
+    Notification::new("sample/notification").with_params_value(json!([1, 2, 3]))
+}
+
+/// Success response to [`sample_request`] with result `true`
+pub fn sample_ok_response() -> Response {
+    Response::ok(1, true)
+}
+
+/// [`Error::METHOD_NOT_FOUND`] response to [`sample_request`]
+pub fn sample_err_response() -> Response {
+    Response::err(
+        1,
+        Error {
+            code: Error::METHOD_NOT_FOUND,
+            message: "method not found".to_string(),
+            data: None,
+        },
+    )
+}
+
+/// [`sample_request`] framed with its `Content-Length` header
+pub fn sample_framed_request() -> String {
+    sample_request().to_string()
+}
+
+#[cfg(feature = "std")]
+pub use mock::{pipe, Endpoint, ExpectRequest, MockClient, MockServer, MockServerHandle, Trickle};
+
+#[cfg(feature = "std")]
+mod mock {
+    use super::super::{Message, MessageIterator, Notification, Request, Response};
+    use super::*;
+    use alloc::{collections::VecDeque, format, sync::Arc, vec::Vec};
+    use serde::Serialize;
+    use serde_json::Value;
+    use std::{
+        io::{self, Read, Write},
+        sync::{Condvar, Mutex},
+        thread::{self, JoinHandle},
+    };
+
+    /// Bytes in flight in one direction of a pipe
+    #[derive(Debug, Default)]
+    struct Channel {
+        state: Mutex<(VecDeque<u8>, bool)>,
+        ready: Condvar,
+    }
+
+    impl Channel {
+        fn close(&self) {
+            self.state.lock().unwrap().1 = true;
+            self.ready.notify_all();
+        }
+    }
+
+    /// One end of an in-memory duplex pipe, created by [`pipe`].
+    ///
+    /// Reads block until the peer writes or closes its end. Both `Endpoint` and `&Endpoint`
+    /// implement `Read` and `Write`, so a single end can back both halves of a
+    /// [`Connection`](crate::Connection).
+    #[derive(Debug)]
+    pub struct Endpoint {
+        rx: Arc<Channel>,
+        tx: Arc<Channel>,
+    }
+
+    /// Create an in-memory duplex pipe; what is written to one end is read from the other.
+    pub fn pipe() -> (Endpoint, Endpoint) {
+        let (a, b) = (Arc::new(Channel::default()), Arc::new(Channel::default()));
+        (
+            Endpoint {
+                rx: a.clone(),
+                tx: b.clone(),
+            },
+            Endpoint { rx: b, tx: a },
+        )
+    }
+
+    impl Endpoint {
+        /// Close both directions, as dropping the end does.
+        ///
+        /// The peer reads EOF once it consumed the bytes already written, and its writes fail
+        /// with `BrokenPipe`.
+        pub fn close(&self) {
+            self.rx.close();
+            self.tx.close();
+        }
+    }
+
+    impl Drop for Endpoint {
+        fn drop(&mut self) {
+            self.close();
+        }
+    }
+
+    impl Read for &Endpoint {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let mut state = self.rx.state.lock().unwrap();
+            while state.0.is_empty() && !state.1 && !buf.is_empty() {
+                state = self.rx.ready.wait(state).unwrap();
+            }
+            let n = state.0.len().min(buf.len());
+            for (b, byte) in buf.iter_mut().zip(state.0.drain(..n)) {
+                *b = byte;
+            }
+            Ok(n)
+        }
+    }
+
+    impl Write for &Endpoint {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let mut state = self.tx.state.lock().unwrap();
+            if state.1 {
+                return Err(io::ErrorKind::BrokenPipe.into());
+            }
+            state.0.extend(buf);
+            self.tx.ready.notify_all();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Read for Endpoint {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            (&*self).read(buf)
+        }
+    }
+
+    impl Write for Endpoint {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            (&*self).write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Transport moving at most `chunk` bytes per call, failing every other call, to exercise
+    /// the retries of short reads and writes.
+    ///
+    /// Reads drain the input and writes append to the output. Failed calls return
+    /// `ErrorKind::Interrupted`, or `Poll::Pending` on the async writes of the feature `async`.
+    #[derive(Debug, Default)]
+    pub struct Trickle {
+        input: VecDeque<u8>,
+        output: Vec<u8>,
+        chunk: usize,
+        failed: bool,
+        slices: Vec<usize>,
+    }
+
+    impl Trickle {
+        /// Create a transport with no input, moving at most `chunk` bytes per call.
+        pub fn new(chunk: usize) -> Self {
+            Self {
+                chunk,
+                ..Self::default()
+            }
+        }
+
+        /// Replace the bytes left to read.
+        pub fn with_input<I>(mut self, input: I) -> Self
+        where
+            I: AsRef<[u8]>,
+        {
+            self.input = input.as_ref().iter().copied().collect();
+            self
+        }
+
+        /// Bytes written so far
+        pub fn written(&self) -> &[u8] {
+            &self.output
+        }
+
+        /// Number of non-empty slices presented by each successful write
+        pub fn slices_per_write(&self) -> &[usize] {
+            &self.slices
+        }
+
+        /// Return `true` if the call fails, alternating with those that go through.
+        fn fail(&mut self) -> bool {
+            self.failed = !self.failed;
+            self.failed
+        }
+
+        fn accept(&mut self, bufs: &[io::IoSlice<'_>]) -> usize {
+            self.slices
+                .push(bufs.iter().filter(|b| !b.is_empty()).count());
+            let mut n = 0;
+            for b in bufs {
+                let take = b.len().min(self.chunk - n);
+                self.output.extend_from_slice(&b[..take]);
+                n += take;
+            }
+            n
+        }
+    }
+
+    impl Read for Trickle {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.fail() {
+                return Err(io::ErrorKind::Interrupted.into());
+            }
+            let n = buf.len().min(self.chunk).min(self.input.len());
+            for (b, byte) in buf.iter_mut().zip(self.input.drain(..n)) {
+                *b = byte;
+            }
+            Ok(n)
+        }
+    }
+
+    impl Write for Trickle {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.write_vectored(&[io::IoSlice::new(buf)])
+        }
+
+        fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+            match self.fail() {
+                true => Err(io::ErrorKind::Interrupted.into()),
+                false => Ok(self.accept(bufs)),
+            }
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "async")]
+    impl futures_io::AsyncWrite for Trickle {
+        fn poll_write(
+            mut self: core::pin::Pin<&mut Self>,
+            cx: &mut core::task::Context<'_>,
+            buf: &[u8],
+        ) -> core::task::Poll<io::Result<usize>> {
+            if self.fail() {
+                cx.waker().wake_by_ref();
+                return core::task::Poll::Pending;
+            }
+            core::task::Poll::Ready(Ok(self.accept(&[io::IoSlice::new(buf)])))
+        }
+
+        fn poll_flush(
+            self: core::pin::Pin<&mut Self>,
+            _: &mut core::task::Context<'_>,
+        ) -> core::task::Poll<io::Result<()>> {
+            core::task::Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(
+            self: core::pin::Pin<&mut Self>,
+            _: &mut core::task::Context<'_>,
+        ) -> core::task::Poll<io::Result<()>> {
+            core::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    #[derive(Debug)]
+    enum Rule {
+        Request(String, Result<Value, Error>),
+        Notification(String),
+    }
+
+    /// Peer answering the requests of the code under test with scripted responses.
+    ///
+    /// The expectations are matched in order. A message that does not match the next
+    /// expectation is a failure; an unexpected request is still answered with an
+    /// [`Error::METHOD_NOT_FOUND`], so the caller does not block. The failures, and the
+    /// expectations never met, panic when the [`MockServerHandle`] is finished or dropped.
+    ///
+    /// ```
+    /// use jsonrpc_base::test_helpers::{pipe, MockServer};
+    /// use jsonrpc_base::{Connection, Request};
+    ///
+    /// let (client, server) = pipe();
+    /// let server = MockServer::new()
+    ///     .expect_request("sum")
+    ///     .respond_ok(3)
+    ///     .spawn(server);
+    ///
+    /// let mut connection = Connection::new(&client, &client);
+    /// let result = connection.send_request(Request::new("sum").with_id(1));
+    /// assert_eq!(result.unwrap(), 3);
+    /// server.finish();
+    /// ```
+    #[derive(Debug, Default)]
+    pub struct MockServer {
+        rules: VecDeque<Rule>,
+    }
+
+    impl MockServer {
+        /// Create a server without expectations.
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Expect a request with the provided method, to be answered as set on the returned
+        /// builder.
+        pub fn expect_request<M>(self, method: M) -> ExpectRequest
+        where
+            M: ToString,
+        {
+            ExpectRequest {
+                server: self,
+                method: method.to_string(),
+            }
+        }
+
+        /// Expect a notification with the provided method.
+        pub fn expect_notification<M>(mut self, method: M) -> Self
+        where
+            M: ToString,
+        {
+            self.rules.push_back(Rule::Notification(method.to_string()));
+            self
+        }
+
+        /// Serve the expectations on the endpoint from a new thread.
+        pub fn spawn(self, endpoint: Endpoint) -> MockServerHandle {
+            let rx = endpoint.rx.clone();
+            let thread = thread::spawn(move || self.serve(endpoint));
+            MockServerHandle {
+                rx,
+                thread: Some(thread),
+            }
+        }
+
+        fn serve(mut self, endpoint: Endpoint) -> Vec<String> {
+            let mut failures = Vec::new();
+            for message in MessageIterator::new(&endpoint) {
+                let response = match message {
+                    Ok(Message::Request(request)) => Some(self.answer(request, &mut failures)),
+                    Ok(Message::Notification(notification)) => {
+                        self.notified(notification, &mut failures);
+                        None
+                    }
+                    Ok(Message::Response(response)) => {
+                        failures.push(format!("unexpected response {}", response.id));
+                        None
+                    }
+                    Err(e) => {
+                        failures.push(format!("malformed frame: {}", e.message));
+                        break;
+                    }
+                };
+                if let Some(response) = response {
+                    if let Err(e) = Message::from(response).try_to_writer(&endpoint) {
+                        failures.push(format!("failed to respond: {}", e.message));
+                    }
+                }
+            }
+            failures.extend(
+                self.rules
+                    .iter()
+                    .map(|rule| format!("{:?} was not met", rule)),
+            );
+            failures
+        }
+
+        fn answer(&mut self, request: Request, failures: &mut Vec<String>) -> Response {
+            match self.rules.pop_front() {
+                Some(Rule::Request(method, result)) if method == request.method => match result {
+                    Ok(result) => Response::ok(request.id, result),
+                    Err(err) => Response::err(request.id, err),
+                },
+                rule => {
+                    failures.push(format!(
+                        "unexpected request {}, expected {:?}",
+                        request.method, rule
+                    ));
+                    self.rules.extend(rule);
+                    self.rules.rotate_right(1);
+                    Response::err(
+                        request.id,
+                        Error {
+                            code: Error::METHOD_NOT_FOUND,
+                            message: format!("unexpected request {}", request.method),
+                            data: None,
+                        },
+                    )
+                }
+            }
+        }
+
+        fn notified(&mut self, notification: Notification, failures: &mut Vec<String>) {
+            match self.rules.pop_front() {
+                Some(Rule::Notification(method)) if method == notification.method => (),
+                rule => {
+                    failures.push(format!(
+                        "unexpected notification {}, expected {:?}",
+                        notification.method, rule
+                    ));
+                    self.rules.extend(rule);
+                    self.rules.rotate_right(1);
+                }
+            }
+        }
+    }
+
+    /// Expected request of a [`MockServer`], waiting for its response
+    #[derive(Debug)]
+    pub struct ExpectRequest {
+        server: MockServer,
+        method: String,
+    }
+
+    impl ExpectRequest {
+        /// Answer the request with the provided result.
+        pub fn respond_ok<R>(mut self, result: R) -> MockServer
+        where
+            R: Into<Value>,
+        {
+            let rule = Rule::Request(self.method, Ok(result.into()));
+            self.server.rules.push_back(rule);
+            self.server
+        }
+
+        /// Answer the request with an error with the provided code and message.
+        pub fn respond_err<M>(mut self, code: i32, message: M) -> MockServer
+        where
+            M: ToString,
+        {
+            let error = Error {
+                code,
+                message: message.to_string(),
+                data: None,
+            };
+            let rule = Rule::Request(self.method, Err(error));
+            self.server.rules.push_back(rule);
+            self.server
+        }
+    }
+
+    /// Running [`MockServer`].
+    ///
+    /// Dropping the handle stops the server once it has consumed the bytes written to it so far,
+    /// and panics if an expectation was not met.
+    #[derive(Debug)]
+    pub struct MockServerHandle {
+        rx: Arc<Channel>,
+        thread: Option<JoinHandle<Vec<String>>>,
+    }
+
+    impl MockServerHandle {
+        /// Stop the server, as dropping the handle does.
+        pub fn finish(mut self) {
+            self.stop();
+        }
+
+        fn stop(&mut self) {
+            let Some(thread) = self.thread.take() else {
+                return;
+            };
+            self.rx.close();
+            let failures = thread
+                .join()
+                .unwrap_or_else(|_| alloc::vec!["panicked".into()]);
+            if !failures.is_empty() && !thread::panicking() {
+                panic!("mock server failures: {:#?}", failures);
+            }
+        }
+    }
+
+    impl Drop for MockServerHandle {
+        fn drop(&mut self) {
+            self.stop();
+        }
+    }
+
+    /// Peer sending requests to the code under test, which serves the other end of the pipe.
+    ///
+    /// Requests are sent with sequential IDs from `1`. Transport failures panic.
+    #[derive(Debug)]
+    pub struct MockClient {
+        endpoint: Endpoint,
+        next_id: u64,
+    }
+
+    impl MockClient {
+        /// Send requests through the provided endpoint.
+        pub fn new(endpoint: Endpoint) -> Self {
+            Self {
+                endpoint,
+                next_id: 1,
+            }
+        }
+
+        /// Send a request and wait for its response.
+        ///
+        /// Panics if the next message of the peer is not the response to the request.
+        pub fn call<M, P>(&mut self, method: M, params: P) -> Response
+        where
+            M: ToString,
+            P: Serialize,
+        {
+            let request = Request::new(method)
+                .with_id(self.next_id)
+                .with_params(params)
+                .unwrap();
+            self.next_id += 1;
+            Message::from(request.clone())
+                .try_to_writer(&self.endpoint)
+                .unwrap();
+
+            let (_, message) = Message::try_from_reader(&self.endpoint).unwrap();
+            match message {
+                Message::Response(response) if response.id == request.id => response,
+                message => panic!("expected the response to {}, got {:?}", request.id, message),
+            }
+        }
+
+        /// Send a notification.
+        pub fn notify<M, P>(&mut self, method: M, params: P)
+        where
+            M: ToString,
+            P: Serialize,
+        {
+            let notification = Notification::new(method).with_params(params).unwrap();
+            Message::from(notification)
+                .try_to_writer(&self.endpoint)
+                .unwrap();
+        }
+
+        /// Close the pipe, so the peer reads EOF.
+        pub fn close(self) {}
+    }
+}
+
+#[test]
+fn test_samples() {
+    let framed = sample_framed_request();
+    let (request, remainder) = Request::parse(&framed).unwrap();
+    assert!(remainder.is_empty());
+    assert_eq!(request.method, "sample/request");
+    assert_eq!(request.id, sample_ok_response().id);
+    assert_eq!(request.id, sample_err_response().id);
+    assert!(sample_ok_response().result().is_some());
+    assert!(sample_err_response().error().is_some());
+    assert_eq!(sample_notification().params, Some(json!([1, 2, 3])));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_mock_server() {
+    use super::{Connection, Notification};
+    use std::panic;
+
+    let (client, server) = pipe();
+    let server = MockServer::new()
+        .expect_request("sum")
+        .respond_ok(3)
+        .expect_notification("log")
+        .expect_request("div")
+        .respond_err(Error::INVALID_PARAMS, "division by zero")
+        .spawn(server);
+
+    let mut connection = Connection::new(&client, &client);
+    let sum = Request::with("sum", [1, 2]).unwrap().with_id(1);
+    assert_eq!(connection.send_request(sum).unwrap(), 3);
+    connection
+        .send_notification(Notification::new("log"))
+        .unwrap();
+    let err = connection
+        .send_request(Request::new("div").with_id(2))
+        .unwrap_err();
+    assert_eq!(err.code, Error::INVALID_PARAMS);
+    server.finish();
+
+    // Unexpected requests are answered, then reported
+    let (client, server) = pipe();
+    let server = MockServer::new()
+        .expect_request("sum")
+        .respond_ok(3)
+        .spawn(server);
+    let mut connection = Connection::new(&client, &client);
+    let err = connection
+        .send_request(Request::new("mul").with_id(1))
+        .unwrap_err();
+    assert_eq!(err.code, Error::METHOD_NOT_FOUND);
+    let failures = panic::catch_unwind(panic::AssertUnwindSafe(|| drop(server))).unwrap_err();
+    let failures = failures.downcast_ref::<String>().unwrap();
+    assert!(failures.contains("unexpected request mul"));
+    assert!(failures.contains("was not met"));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_mock_client() {
+    use super::Connection;
+    use std::thread;
+
+    let (client, server) = pipe();
+    let served = thread::spawn(move || {
+        Connection::new(&server, &server)
+            .serve(&|request: Request| Response::ok(request.id, request.params))
+    });
+
+    let mut client = MockClient::new(client);
+    let response = client.call("echo", ["foo"]);
+    assert_eq!(response.result(), Some(&json!(["foo"])));
+    client.notify("ignored", ());
+    assert_eq!(client.call("echo", ()).id, json!(2));
+    client.close();
+    served.join().unwrap().unwrap();
+}
@@ -0,0 +1,211 @@
+use super::{helpers, telemetry, Connection, Error, Service};
+use std::{
+    fs, io,
+    os::unix::{
+        fs::{FileTypeExt, MetadataExt},
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+/// Longest pause of the accept loop after consecutive failures, such as running out of file
+/// descriptors
+const MAX_ACCEPT_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Options of [`serve_unix`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct UnixServeOptions {
+    /// Remove an existing socket at the socket path before binding.
+    ///
+    /// Any other kind of file at the path is left in place, and fails the bind.
+    pub remove_existing: bool,
+}
+
+/// Handle of a server started by [`serve_unix`].
+///
+/// The server is shut down and the socket file removed when the handle is dropped.
+#[derive(Debug)]
+pub struct UnixServer {
+    path: PathBuf,
+    /// Device and inode of the bound socket
+    socket: Option<(u64, u64)>,
+    shutdown: Arc<AtomicBool>,
+    accept: Option<JoinHandle<()>>,
+}
+
+impl UnixServer {
+    /// Path of the bound socket
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Stop accepting connections and remove the socket file.
+    ///
+    /// Connections already accepted are served until the peer closes them. If the socket file was
+    /// removed or replaced, it is left as is, and the accepting thread is detached instead of
+    /// woken.
+    pub fn shutdown(self) {
+        drop(self)
+    }
+}
+
+impl Drop for UnixServer {
+    fn drop(&mut self) {
+        if let Some(accept) = self.accept.take() {
+            self.shutdown.store(true, Ordering::SeqCst);
+            let bound = self.socket.is_some() && socket_id(&self.path) == self.socket;
+            // Wake the blocking accept so the flag is observed; without our socket to connect
+            // to, the thread stays blocked and is detached
+            if bound && UnixStream::connect(&self.path).is_ok() {
+                let _ = accept.join();
+            }
+            if bound {
+                let _ = fs::remove_file(&self.path);
+            }
+        }
+    }
+}
+
+impl Connection<UnixStream, UnixStream> {
+    /// Connect to the socket at the provided path, using a clone of the stream as reader.
+    pub fn connect_unix<P>(path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let writer = UnixStream::connect(path).map_err(helpers::io_error)?;
+        let reader = writer.try_clone().map_err(helpers::io_error)?;
+        Ok(Self::new(reader, writer))
+    }
+}
+
+/// Bind a socket to the provided path and serve every accepted connection with the service.
+///
+/// Each connection is served in its own thread, until the peer closes it. A failure to accept a
+/// connection is recorded, and the next accept is delayed, up to a second while they keep failing.
+pub fn serve_unix<P, S>(path: P, service: S, options: UnixServeOptions) -> Result<UnixServer, Error>
+where
+    P: AsRef<Path>,
+    S: Service + Send + Sync + 'static,
+{
+    let path = path.as_ref().to_path_buf();
+    if options.remove_existing {
+        remove_socket(&path).map_err(helpers::io_error)?;
+    }
+
+    let listener = UnixListener::bind(&path).map_err(helpers::io_error)?;
+    let socket = socket_id(&path);
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let service = Arc::new(service);
+
+    let accept = {
+        let shutdown = Arc::clone(&shutdown);
+        thread::spawn(move || {
+            let mut backoff = Duration::ZERO;
+            for stream in listener.incoming() {
+                if shutdown.load(Ordering::SeqCst) {
+                    break;
+                }
+                let writer = match stream {
+                    Ok(writer) => writer,
+                    Err(e) => {
+                        telemetry::accept_failed(&e);
+                        backoff =
+                            (backoff * 2).clamp(Duration::from_millis(10), MAX_ACCEPT_BACKOFF);
+                        thread::sleep(backoff);
+                        continue;
+                    }
+                };
+                backoff = Duration::ZERO;
+                let service = Arc::clone(&service);
+                thread::spawn(move || {
+                    if let Ok(reader) = writer.try_clone() {
+                        let _ = Connection::new(reader, writer).serve(&*service);
+                    }
+                });
+            }
+        })
+    };
+
+    Ok(UnixServer {
+        path,
+        socket,
+        shutdown,
+        accept: Some(accept),
+    })
+}
+
+/// Device and inode of the socket at the path, if any
+fn socket_id(path: &Path) -> Option<(u64, u64)> {
+    fs::symlink_metadata(path)
+        .ok()
+        .filter(|metadata| metadata.file_type().is_socket())
+        .map(|metadata| (metadata.dev(), metadata.ino()))
+}
+
+/// Remove the socket at the path, if any, refusing to remove any other kind of file.
+fn remove_socket(path: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => fs::remove_file(path),
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} exists and is not a socket", path.display()),
+        )),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+#[test]
+fn test_serve_unix_round_trip() {
+    use super::{Request, Response};
+    use serde_json::Value;
+
+    let path = std::env::temp_dir().join(format!("jsonrpc-base-{}.sock", std::process::id()));
+    let options = UnixServeOptions {
+        remove_existing: true,
+    };
+
+    // A regular file is never removed
+    fs::write(&path, b"user data").unwrap();
+    let err = serve_unix(&path, |r: Request| Response::ok(r.id, r.method), options).unwrap_err();
+    assert_eq!(err.data, Some(Value::from("AlreadyExists")));
+    assert_eq!(fs::read(&path).unwrap(), b"user data");
+    fs::remove_file(&path).unwrap();
+
+    // A stale socket is removed only with the option
+    drop(UnixListener::bind(&path).unwrap());
+    assert!(serve_unix(
+        &path,
+        |r: Request| Response::ok(r.id, r.method),
+        Default::default()
+    )
+    .is_err());
+    let server = serve_unix(&path, |r: Request| Response::ok(r.id, r.method), options).unwrap();
+    assert_eq!(server.path(), path);
+
+    let mut connection = Connection::connect_unix(&path).unwrap();
+    let result = connection.send_request(Request::new("echo")).unwrap();
+    assert_eq!(result, Value::from("echo"));
+    drop(connection);
+
+    server.shutdown();
+    assert!(!path.exists());
+
+    // A server whose socket file was removed or replaced shuts down without touching the path
+    let server = serve_unix(&path, |r: Request| Response::ok(r.id, r.method), options).unwrap();
+    fs::remove_file(&path).unwrap();
+    server.shutdown();
+
+    let server = serve_unix(&path, |r: Request| Response::ok(r.id, r.method), options).unwrap();
+    fs::remove_file(&path).unwrap();
+    fs::write(&path, b"user data").unwrap();
+    server.shutdown();
+    assert_eq!(fs::read(&path).unwrap(), b"user data");
+    fs::remove_file(&path).unwrap();
+}
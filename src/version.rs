@@ -0,0 +1,110 @@
+use super::{telemetry, Error};
+use alloc::string::{String, ToString};
+use core::{fmt, ops::Deref};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Version of the protocol, carried by the `jsonrpc` member of every message.
+///
+/// [`JsonRpcVersion::new`] accepts `"2.0"` and, for bridges to older peers, `"1.0"`. Parsed
+/// messages and [`From<String>`] keep any string, for compatibility with peers sending other
+/// versions.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct JsonRpcVersion(String);
+
+impl JsonRpcVersion {
+    /// Version of the specification implemented by the crate
+    pub const V2: &'static str = "2.0";
+    /// Version of the predecessor of the specification, without the `jsonrpc` member on the wire
+    pub const V1: &'static str = "1.0";
+
+    /// Create a version, failing with [`Error::INVALID_REQUEST`] unless it is `"2.0"` or `"1.0"`.
+    ///
+    /// A warning is recorded for `"1.0"`.
+    pub fn new(version: &str) -> Result<Self, Error> {
+        match version {
+            Self::V2 => Ok(Self::default()),
+            Self::V1 => {
+                telemetry::legacy_version(version);
+                Ok(Self(version.to_string()))
+            }
+            _ => Err(Error {
+                code: Error::INVALID_REQUEST,
+                message: alloc::format!("the protocol version {:?} is not supported", version),
+                data: Some(Value::String(version.to_string())),
+            }),
+        }
+    }
+
+    /// Check if the version is `"2.0"`
+    pub fn is_v2(&self) -> bool {
+        self.0 == Self::V2
+    }
+}
+
+impl Default for JsonRpcVersion {
+    /// Version `"2.0"`
+    fn default() -> Self {
+        Self(Self::V2.to_string())
+    }
+}
+
+impl From<String> for JsonRpcVersion {
+    /// Wrap any string, without validation; prefer [`JsonRpcVersion::new`].
+    fn from(version: String) -> Self {
+        Self(version)
+    }
+}
+
+impl From<JsonRpcVersion> for String {
+    fn from(version: JsonRpcVersion) -> Self {
+        version.0
+    }
+}
+
+impl Deref for JsonRpcVersion {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for JsonRpcVersion {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for JsonRpcVersion {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl fmt::Display for JsonRpcVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[test]
+fn test_version() {
+    let version = JsonRpcVersion::new("2.0").unwrap();
+    assert_eq!(version, JsonRpcVersion::default());
+    assert!(version.is_v2());
+    assert_eq!(&*version, "2.0");
+    assert_eq!(serde_json::to_string(&version).unwrap(), r#""2.0""#);
+
+    let legacy = JsonRpcVersion::new("1.0").unwrap();
+    assert!(!legacy.is_v2());
+    assert_eq!(legacy, "1.0");
+
+    let err = JsonRpcVersion::new("3.0").unwrap_err();
+    assert_eq!(err.code, Error::INVALID_REQUEST);
+    assert_eq!(err.data, Some(Value::from("3.0")));
+
+    let parsed: JsonRpcVersion = serde_json::from_str(r#""3.0""#).unwrap();
+    assert_eq!(parsed, JsonRpcVersion::from(String::from("3.0")));
+}